@@ -1,6 +1,6 @@
 use ecow::EcoString;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct Location {
     pub filename: EcoString,
     /// 0-indexed
@@ -15,30 +15,122 @@ impl Location {
     }
 }
 
+// 6.4.4.1
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum IntegerSuffix {
+    None,
+    Unsigned,
+    Long,
+    UnsignedLong,
+    LongLong,
+    UnsignedLongLong,
+}
+
+// 6.4.4.2
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum FloatingSuffix {
+    None,
+    Float,
+    Long,
+}
+
 // 6.4
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum TokenKind {
     // keywords
+    Break,
+    Case,
+    Char,
+    Const,
+    Continue,
+    Default,
+    Do,
+    Double,
+    Else,
+    Enum,
+    Extern,
+    Float,
+    For,
+    Goto,
+    If,
     Int,
+    Long,
     Return,
+    Short,
+    Signed,
+    Sizeof,
+    Static,
+    Struct,
+    Switch,
+    Typedef,
+    Union,
+    Unsigned,
+    Void,
+    While,
+    Alignas,
+    Bool,
+    Complex,
+    Generic,
+    StaticAssert,
+    // GCC extension
+    Int128,
+    // <stdarg.h> 7.16 as builtins since there is no preprocessor
+    VaArg,
+    VaCopy,
+    VaEnd,
+    VaList,
+    VaStart,
     // identifier
     Identifier(EcoString),
     // constant
-    Integer(i64),
-    // string-literal
+    Integer {
+        value: u64,
+        // Octal and hexadecimal constants may have unsigned types without a suffix
+        decimal: bool,
+        suffix: IntegerSuffix,
+    },
+    Floating {
+        value: f64,
+        suffix: FloatingSuffix,
+    },
+    // string-literal without the terminating null character
+    StringLiteral(Vec<u8>),
     // punctuator 6.4.6 keep the order
+    LBracket,
+    RBracket,
     LParen,
     RParen,
     LBrace,
     RBrace,
+    Dot,
+    Arrow,
+    PlusPlus,
+    MinusMinus,
+    Ampersand,
     Asterisk,
     Plus,
     Minus,
+    Tilde,
+    Exclamation,
     Slash,
     Percent,
+    LessLess,
+    GreaterGreater,
+    Less,
+    Greater,
+    LessEqual,
+    GreaterEqual,
+    EqualEqual,
+    ExclamationEqual,
+    Colon,
     SemiColon,
+    Ellipsis,
+    Equal,
+    Comma,
     // Error
     Unknown(u8),
+    // 6.4.4.1 An integer constant that does not fit in 64 bits
+    TooLargeInteger,
 }
 
 pub struct Token {
@@ -52,6 +144,41 @@ pub struct Lexer {
     current_column: usize,
     source: Vec<u8>,
     index: usize,
+    // `#pragma pack` directives with their indices in the source, in order
+    pragmas: Vec<(usize, Pack)>,
+}
+
+// The arguments of `#pragma pack`, whose alignment is `None` for the default
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Pack {
+    Set(Option<u64>),
+    Push(Option<u64>),
+    Pop,
+}
+
+impl Pack {
+    // `pack(n)`, `pack()`, `pack(push)`, `pack(push, n)` or `pack(pop)`
+    fn parse(pragma: &str) -> Option<Pack> {
+        let arguments = pragma
+            .trim()
+            .strip_prefix("pack")?
+            .trim()
+            .strip_prefix('(')?
+            .strip_suffix(')')?;
+        let alignment = |argument: &str| match argument.trim().parse::<u64>() {
+            Ok(alignment) if alignment.is_power_of_two() && alignment <= 16 => Some(alignment),
+            _ => None,
+        };
+        let arguments = arguments.split(',').map(str::trim).collect::<Vec<_>>();
+        match arguments.as_slice() {
+            [""] => Some(Pack::Set(None)),
+            ["push"] => Some(Pack::Push(None)),
+            ["push", argument] => Some(Pack::Push(Some(alignment(argument)?))),
+            ["pop"] => Some(Pack::Pop),
+            [argument] => Some(Pack::Set(Some(alignment(argument)?))),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -69,6 +196,7 @@ impl Lexer {
             current_column: 0,
             source,
             index: 0,
+            pragmas: Vec::new(),
         }
     }
 
@@ -112,6 +240,23 @@ impl Lexer {
         std::str::from_utf8(&self.source[line_start..line_end]).unwrap()
     }
 
+    /// The maximum alignment of members set by the `#pragma pack` directives lexed so far
+    pub fn pack_alignment(&self) -> Option<u64> {
+        let mut alignment = None;
+        let mut stack = Vec::new();
+        for (_, pack) in self.pragmas.iter().filter(|(index, _)| *index < self.index) {
+            match *pack {
+                Pack::Set(pack) => alignment = pack,
+                Pack::Push(pack) => {
+                    stack.push(alignment);
+                    alignment = pack.or(alignment);
+                }
+                Pack::Pop => alignment = stack.pop().flatten(),
+            }
+        }
+        alignment
+    }
+
     fn current_char(&self) -> Option<u8> {
         self.source.get(self.index).copied()
     }
@@ -155,6 +300,176 @@ impl Lexer {
         }
         std::str::from_utf8(&self.source[start..self.index]).unwrap()
     }
+
+    // 6.10.6 Skips a `#pragma` line, returning `false` for other lines. Unknown pragmas are
+    // ignored, and `#pragma pack` is recorded for the layout of structures.
+    fn skip_pragma(&mut self) -> bool {
+        let rest = &self.source[self.index..];
+        let length = rest.iter().position(|&c| c == b'\n').unwrap_or(rest.len());
+        let line = String::from_utf8_lossy(&rest[1..length]);
+        let Some(pragma) = line.trim_start().strip_prefix("pragma ") else {
+            return false;
+        };
+        // Directives are seen again after backtracking
+        if let Some(pack) = Pack::parse(pragma) {
+            if !self.pragmas.iter().any(|(index, _)| *index == self.index) {
+                self.pragmas.push((self.index, pack));
+            }
+        }
+        for _ in 0..length {
+            self.skip1();
+        }
+        true
+    }
+
+    // 6.4.4.1
+    fn read_integer_constant(&mut self) -> TokenKind {
+        let (value, decimal) = if self.current_char() == Some(b'0')
+            && matches!(self.source.get(self.index + 1), Some(b'x' | b'X'))
+        {
+            self.skip1();
+            let x = self.current_char().unwrap();
+            self.skip1();
+            let digits = self.read_while(|c| c.is_ascii_hexdigit());
+            if digits.is_empty() {
+                return TokenKind::Unknown(x);
+            }
+            (u64::from_str_radix(digits, 16), false)
+        } else if self.current_char() == Some(b'0') {
+            let digits = self.read_while(|c| (b'0'..=b'7').contains(&c));
+            (u64::from_str_radix(digits, 8), false)
+        } else {
+            let digits = self.read_while(|c| c.is_ascii_digit());
+            (digits.parse(), true)
+        };
+        // Digits are nonempty, so it only fails by overflow
+        let Ok(value) = value else {
+            self.read_while(|c| c.is_ascii_alphanumeric() || c == b'_');
+            return TokenKind::TooLargeInteger;
+        };
+
+        let suffix = match self.read_while(|c| c.is_ascii_alphanumeric() || c == b'_') {
+            "" => IntegerSuffix::None,
+            "u" | "U" => IntegerSuffix::Unsigned,
+            "l" | "L" => IntegerSuffix::Long,
+            "ul" | "uL" | "Ul" | "UL" | "lu" | "lU" | "Lu" | "LU" => IntegerSuffix::UnsignedLong,
+            "ll" | "LL" => IntegerSuffix::LongLong,
+            "ull" | "uLL" | "Ull" | "ULL" | "llu" | "llU" | "LLu" | "LLU" => {
+                IntegerSuffix::UnsignedLongLong
+            }
+            suffix => return TokenKind::Unknown(suffix.as_bytes()[0]),
+        };
+
+        TokenKind::Integer {
+            value,
+            decimal,
+            suffix,
+        }
+    }
+
+    // 6.4.4.2 Decimal digits followed by a period or an exponent, or a period followed by a digit
+    // TODO hexadecimal floating constants
+    fn at_floating_constant(&self) -> bool {
+        let rest = &self.source[self.index..];
+        if rest.starts_with(b"0x") || rest.starts_with(b"0X") {
+            return false;
+        }
+        let digits = rest.iter().take_while(|c| c.is_ascii_digit()).count();
+        match rest.get(digits) {
+            Some(b'.') => digits > 0 || rest.get(1).is_some_and(u8::is_ascii_digit),
+            Some(b'e' | b'E') => digits > 0,
+            _ => false,
+        }
+    }
+
+    // 6.4.4.2
+    fn read_floating_constant(&mut self) -> TokenKind {
+        let start = self.index;
+        self.read_while(|c| c.is_ascii_digit());
+        if self.current_char() == Some(b'.') {
+            self.skip1();
+            self.read_while(|c| c.is_ascii_digit());
+        }
+        if let Some(c @ (b'e' | b'E')) = self.current_char() {
+            self.skip1();
+            if let Some(b'+' | b'-') = self.current_char() {
+                self.skip1();
+            }
+            if self.read_while(|c| c.is_ascii_digit()).is_empty() {
+                return TokenKind::Unknown(c);
+            }
+        }
+        let value = std::str::from_utf8(&self.source[start..self.index])
+            .unwrap()
+            .parse()
+            .unwrap();
+
+        let suffix = match self.read_while(|c| c.is_ascii_alphanumeric() || c == b'_') {
+            "" => FloatingSuffix::None,
+            "f" | "F" => FloatingSuffix::Float,
+            "l" | "L" => FloatingSuffix::Long,
+            suffix => return TokenKind::Unknown(suffix.as_bytes()[0]),
+        };
+
+        TokenKind::Floating { value, suffix }
+    }
+
+    // 6.4.5
+    fn read_string_literal(&mut self) -> TokenKind {
+        self.skip1();
+        let mut value = Vec::new();
+        loop {
+            match self.current_char() {
+                Some(b'"') => {
+                    self.skip1();
+                    return TokenKind::StringLiteral(value);
+                }
+                Some(b'\\') => {
+                    self.skip1();
+                    match self.read_escape_sequence() {
+                        Some(c) => value.push(c),
+                        None => return TokenKind::Unknown(b'\\'),
+                    }
+                }
+                None | Some(b'\n') => return TokenKind::Unknown(b'"'),
+                Some(c) => {
+                    self.skip1();
+                    value.push(c);
+                }
+            }
+        }
+    }
+
+    // 6.4.4.4 Following the backslash
+    fn read_escape_sequence(&mut self) -> Option<u8> {
+        let c = self.current_char()?;
+        let value = match c {
+            b'\'' | b'"' | b'?' | b'\\' => c,
+            b'a' => 0x07,
+            b'b' => 0x08,
+            b'f' => 0x0C,
+            b'n' => b'\n',
+            b'r' => b'\r',
+            b't' => b'\t',
+            b'v' => 0x0B,
+            b'0'..=b'7' => {
+                let start = self.index;
+                while self.index - start < 3 && matches!(self.current_char(), Some(b'0'..=b'7')) {
+                    self.skip1();
+                }
+                let digits = std::str::from_utf8(&self.source[start..self.index]).unwrap();
+                return u8::try_from(u32::from_str_radix(digits, 8).unwrap()).ok();
+            }
+            b'x' => {
+                self.skip1();
+                let digits = self.read_while(|c| c.is_ascii_hexdigit());
+                return u8::from_str_radix(digits, 16).ok();
+            }
+            _ => return None,
+        };
+        self.skip1();
+        Some(value)
+    }
 }
 
 impl Iterator for Lexer {
@@ -165,23 +480,84 @@ impl Iterator for Lexer {
         let location = self.current_location();
 
         match self.current_char()? {
-            b'0'..=b'9' => {
-                let value = self.read_while(|c| c.is_ascii_digit());
-                let value = value.parse().unwrap();
-                Some(Token {
-                    location,
-                    kind: TokenKind::Integer(value),
-                })
-            }
+            b'0'..=b'9' if self.at_floating_constant() => Some(Token {
+                location,
+                kind: self.read_floating_constant(),
+            }),
+            b'0'..=b'9' => Some(Token {
+                location,
+                kind: self.read_integer_constant(),
+            }),
+            b'.' if self.at_floating_constant() => Some(Token {
+                location,
+                kind: self.read_floating_constant(),
+            }),
             b'a'..=b'z' | b'A'..=b'Z' | b'_' => {
                 let ident = self.read_while(|c| c.is_ascii_alphanumeric() || c == b'_');
                 let kind = match ident {
+                    "break" => TokenKind::Break,
+                    "case" => TokenKind::Case,
+                    "char" => TokenKind::Char,
+                    "const" => TokenKind::Const,
+                    "continue" => TokenKind::Continue,
+                    "default" => TokenKind::Default,
+                    "do" => TokenKind::Do,
+                    "double" => TokenKind::Double,
+                    "else" => TokenKind::Else,
+                    "enum" => TokenKind::Enum,
+                    "extern" => TokenKind::Extern,
+                    "float" => TokenKind::Float,
+                    "for" => TokenKind::For,
+                    "goto" => TokenKind::Goto,
+                    "if" => TokenKind::If,
                     "int" => TokenKind::Int,
+                    "long" => TokenKind::Long,
                     "return" => TokenKind::Return,
+                    "short" => TokenKind::Short,
+                    "signed" => TokenKind::Signed,
+                    "sizeof" => TokenKind::Sizeof,
+                    "static" => TokenKind::Static,
+                    "struct" => TokenKind::Struct,
+                    "switch" => TokenKind::Switch,
+                    "typedef" => TokenKind::Typedef,
+                    "union" => TokenKind::Union,
+                    "unsigned" => TokenKind::Unsigned,
+                    "void" => TokenKind::Void,
+                    "while" => TokenKind::While,
+                    "_Alignas" => TokenKind::Alignas,
+                    "_Bool" => TokenKind::Bool,
+                    "_Complex" => TokenKind::Complex,
+                    "_Generic" => TokenKind::Generic,
+                    // C23 keyword, which is a macro in <assert.h> before
+                    "_Static_assert" | "static_assert" => TokenKind::StaticAssert,
+                    "__int128" => TokenKind::Int128,
+                    "va_arg" | "__builtin_va_arg" => TokenKind::VaArg,
+                    "va_copy" | "__builtin_va_copy" => TokenKind::VaCopy,
+                    "va_end" | "__builtin_va_end" => TokenKind::VaEnd,
+                    "va_list" | "__builtin_va_list" => TokenKind::VaList,
+                    "va_start" | "__builtin_va_start" => TokenKind::VaStart,
                     _ => TokenKind::Identifier(ident.into()),
                 };
                 Some(Token { location, kind })
             }
+            b'"' => Some(Token {
+                location,
+                kind: self.read_string_literal(),
+            }),
+            b'[' => {
+                self.skip1();
+                Some(Token {
+                    location,
+                    kind: TokenKind::LBracket,
+                })
+            }
+            b']' => {
+                self.skip1();
+                Some(Token {
+                    location,
+                    kind: TokenKind::RBracket,
+                })
+            }
             b'(' => {
                 self.skip1();
                 Some(Token {
@@ -210,6 +586,15 @@ impl Iterator for Lexer {
                     kind: TokenKind::RBrace,
                 })
             }
+            b'#' if self.skip_pragma() => self.next(),
+            // TODO `&&`
+            b'&' if self.source.get(self.index + 1) != Some(&b'&') => {
+                self.skip1();
+                Some(Token {
+                    location,
+                    kind: TokenKind::Ampersand,
+                })
+            }
             b'*' => {
                 self.skip1();
                 Some(Token {
@@ -218,31 +603,109 @@ impl Iterator for Lexer {
                 })
             }
             b'+' => {
+                self.skip1();
+                let kind = match self.current_char() {
+                    Some(b'+') => {
+                        self.skip1();
+                        TokenKind::PlusPlus
+                    }
+                    _ => TokenKind::Plus,
+                };
+                Some(Token { location, kind })
+            }
+            b'-' => {
+                self.skip1();
+                let kind = match self.current_char() {
+                    Some(b'-') => {
+                        self.skip1();
+                        TokenKind::MinusMinus
+                    }
+                    Some(b'>') => {
+                        self.skip1();
+                        TokenKind::Arrow
+                    }
+                    _ => TokenKind::Minus,
+                };
+                Some(Token { location, kind })
+            }
+            b'/' => {
                 self.skip1();
                 Some(Token {
                     location,
-                    kind: TokenKind::Plus,
+                    kind: TokenKind::Slash,
                 })
             }
-            b'-' => {
+            b'%' => {
                 self.skip1();
                 Some(Token {
                     location,
-                    kind: TokenKind::Minus,
+                    kind: TokenKind::Percent,
                 })
             }
-            b'/' => {
+            b'<' => {
+                self.skip1();
+                let kind = match self.current_char() {
+                    Some(b'<') => {
+                        self.skip1();
+                        TokenKind::LessLess
+                    }
+                    Some(b'=') => {
+                        self.skip1();
+                        TokenKind::LessEqual
+                    }
+                    _ => TokenKind::Less,
+                };
+                Some(Token { location, kind })
+            }
+            b'>' => {
+                self.skip1();
+                let kind = match self.current_char() {
+                    Some(b'>') => {
+                        self.skip1();
+                        TokenKind::GreaterGreater
+                    }
+                    Some(b'=') => {
+                        self.skip1();
+                        TokenKind::GreaterEqual
+                    }
+                    _ => TokenKind::Greater,
+                };
+                Some(Token { location, kind })
+            }
+            b'=' => {
+                self.skip1();
+                let kind = match self.current_char() {
+                    Some(b'=') => {
+                        self.skip1();
+                        TokenKind::EqualEqual
+                    }
+                    _ => TokenKind::Equal,
+                };
+                Some(Token { location, kind })
+            }
+            b'~' => {
                 self.skip1();
                 Some(Token {
                     location,
-                    kind: TokenKind::Slash,
+                    kind: TokenKind::Tilde,
                 })
             }
-            b'%' => {
+            b'!' => {
+                self.skip1();
+                let kind = match self.current_char() {
+                    Some(b'=') => {
+                        self.skip1();
+                        TokenKind::ExclamationEqual
+                    }
+                    _ => TokenKind::Exclamation,
+                };
+                Some(Token { location, kind })
+            }
+            b':' => {
                 self.skip1();
                 Some(Token {
                     location,
-                    kind: TokenKind::Percent,
+                    kind: TokenKind::Colon,
                 })
             }
             b';' => {
@@ -252,6 +715,29 @@ impl Iterator for Lexer {
                     kind: TokenKind::SemiColon,
                 })
             }
+            b'.' if self.source[self.index..].starts_with(b"...") => {
+                self.skip1();
+                self.skip1();
+                self.skip1();
+                Some(Token {
+                    location,
+                    kind: TokenKind::Ellipsis,
+                })
+            }
+            b'.' => {
+                self.skip1();
+                Some(Token {
+                    location,
+                    kind: TokenKind::Dot,
+                })
+            }
+            b',' => {
+                self.skip1();
+                Some(Token {
+                    location,
+                    kind: TokenKind::Comma,
+                })
+            }
             c => {
                 self.skip1();
                 Some(Token {
@@ -277,7 +763,151 @@ fn test_lexer() {
     assert_eq!(tokens[3].kind, TokenKind::RParen);
     assert_eq!(tokens[4].kind, TokenKind::LBrace);
     assert_eq!(tokens[5].kind, TokenKind::Return);
-    assert_eq!(tokens[6].kind, TokenKind::Integer(42));
+    assert_eq!(
+        tokens[6].kind,
+        TokenKind::Integer {
+            value: 42,
+            decimal: true,
+            suffix: IntegerSuffix::None
+        }
+    );
     assert_eq!(tokens[7].kind, TokenKind::SemiColon);
     assert_eq!(tokens[8].kind, TokenKind::RBrace);
 }
+
+#[test]
+fn test_lexer_integer_constant() {
+    let src = "0x1F 017 4294967295u 10UL 7llu".as_bytes().to_vec();
+    let lexer = Lexer::new("test.c".into(), src);
+
+    let tokens: Vec<_> = lexer.map(|token| token.kind).collect();
+
+    assert_eq!(
+        tokens,
+        vec![
+            TokenKind::Integer {
+                value: 31,
+                decimal: false,
+                suffix: IntegerSuffix::None
+            },
+            TokenKind::Integer {
+                value: 15,
+                decimal: false,
+                suffix: IntegerSuffix::None
+            },
+            TokenKind::Integer {
+                value: 4294967295,
+                decimal: true,
+                suffix: IntegerSuffix::Unsigned
+            },
+            TokenKind::Integer {
+                value: 10,
+                decimal: true,
+                suffix: IntegerSuffix::UnsignedLong
+            },
+            TokenKind::Integer {
+                value: 7,
+                decimal: true,
+                suffix: IntegerSuffix::UnsignedLongLong
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_lexer_invalid_integer_constant() {
+    let src = "0x; 99999999999999999999 0xFFFFFFFFFFFFFFFF0u 18446744073709551615"
+        .as_bytes()
+        .to_vec();
+    let lexer = Lexer::new("test.c".into(), src);
+
+    let tokens: Vec<_> = lexer.map(|token| token.kind).collect();
+
+    assert_eq!(
+        tokens,
+        vec![
+            TokenKind::Unknown(b'x'),
+            TokenKind::SemiColon,
+            TokenKind::TooLargeInteger,
+            TokenKind::TooLargeInteger,
+            TokenKind::Integer {
+                value: u64::MAX,
+                decimal: true,
+                suffix: IntegerSuffix::None
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_lexer_floating_constant() {
+    let src = "1.5 .25f 2. 1e3 3E-2L 4.e1 x.y".as_bytes().to_vec();
+    let lexer = Lexer::new("test.c".into(), src);
+
+    let tokens: Vec<_> = lexer.map(|token| token.kind).collect();
+
+    let floating = |value, suffix| TokenKind::Floating { value, suffix };
+    assert_eq!(
+        tokens,
+        vec![
+            floating(1.5, FloatingSuffix::None),
+            floating(0.25, FloatingSuffix::Float),
+            floating(2.0, FloatingSuffix::None),
+            floating(1000.0, FloatingSuffix::None),
+            floating(0.03, FloatingSuffix::Long),
+            floating(40.0, FloatingSuffix::None),
+            TokenKind::Identifier("x".into()),
+            TokenKind::Dot,
+            TokenKind::Identifier("y".into()),
+        ]
+    );
+}
+
+#[test]
+fn test_lexer_string_literal() {
+    let src = r#"a["x\ty" "\101\x42\0"]"#.as_bytes().to_vec();
+    let lexer = Lexer::new("test.c".into(), src);
+
+    let tokens: Vec<_> = lexer.map(|token| token.kind).collect();
+
+    assert_eq!(
+        tokens,
+        vec![
+            TokenKind::Identifier("a".into()),
+            TokenKind::LBracket,
+            TokenKind::StringLiteral(b"x\ty".to_vec()),
+            TokenKind::StringLiteral(b"AB\0".to_vec()),
+            TokenKind::RBracket,
+        ]
+    );
+}
+
+#[test]
+fn test_lexer_pragma_pack() {
+    let src = "#pragma pack(push, 1)\na.b\n#pragma once\n->c\n#pragma pack(pop)\nd"
+        .as_bytes()
+        .to_vec();
+    let mut lexer = Lexer::new("test.c".into(), src);
+
+    assert_eq!(lexer.pack_alignment(), None);
+    assert_eq!(
+        lexer.next().unwrap().kind,
+        TokenKind::Identifier("a".into())
+    );
+    assert_eq!(lexer.pack_alignment(), Some(1));
+    assert_eq!(lexer.next().unwrap().kind, TokenKind::Dot);
+    assert_eq!(
+        lexer.next().unwrap().kind,
+        TokenKind::Identifier("b".into())
+    );
+    assert_eq!(lexer.next().unwrap().kind, TokenKind::Arrow);
+    assert_eq!(
+        lexer.next().unwrap().kind,
+        TokenKind::Identifier("c".into())
+    );
+    assert_eq!(
+        lexer.next().unwrap().kind,
+        TokenKind::Identifier("d".into())
+    );
+    assert_eq!(lexer.pack_alignment(), None);
+}
@@ -1,3 +1,4 @@
 pub mod lexer;
 pub mod mlir;
 pub mod parser;
+pub mod types;
@@ -1,6 +1,6 @@
 use c2mlir::{
     lexer::Lexer,
-    mlir::AddModule,
+    mlir::{AddModule, ControlFlow, MemoryModel, Options},
     parser::{Parse, Parser, TranslationUnit},
 };
 use clap::Parser as _;
@@ -9,7 +9,11 @@ use std::path::PathBuf;
 use melior::{
     dialect::DialectRegistry,
     ir::{operation::OperationPrintingFlags, Location, Module},
-    pass::{conversion::create_to_llvm, transform::create_inliner, PassManager},
+    pass::{
+        conversion::{create_math_to_libm, create_scf_to_control_flow, create_to_llvm},
+        transform::create_inliner,
+        PassManager,
+    },
     utility::register_all_dialects,
     Context,
 };
@@ -19,6 +23,45 @@ struct Opts {
     filepath: PathBuf,
     #[clap(short = 'O')]
     optimize: bool,
+    #[clap(long, value_enum, default_value_t)]
+    control_flow: ControlFlow,
+    #[clap(long, value_enum, default_value_t)]
+    memory_model: MemoryModel,
+    /// Code generation options like those of GCC, given as `-funsigned-char`
+    #[clap(short = 'f', value_enum)]
+    flags: Vec<Flag>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum Flag {
+    /// Plain char is signed, which is the default
+    SignedChar,
+    /// Plain char is unsigned
+    UnsignedChar,
+    /// Functions of <math.h> and <complex.h> are lowered to the `math` and `complex` dialects,
+    /// which is the default
+    Builtin,
+    /// Functions of <math.h> and <complex.h> are called unless named with `__builtin_`
+    NoBuiltin,
+}
+
+impl Opts {
+    // The last one of the pair wins
+    fn last_flag(&self, flags: [Flag; 2]) -> Option<Flag> {
+        self.flags
+            .iter()
+            .rev()
+            .find(|flag| flags.contains(flag))
+            .copied()
+    }
+
+    fn unsigned_char(&self) -> bool {
+        self.last_flag([Flag::SignedChar, Flag::UnsignedChar]) == Some(Flag::UnsignedChar)
+    }
+
+    fn no_builtin(&self) -> bool {
+        self.last_flag([Flag::Builtin, Flag::NoBuiltin]) == Some(Flag::NoBuiltin)
+    }
 }
 
 fn main() {
@@ -28,6 +71,7 @@ fn main() {
     let filename = opts.filepath.to_string_lossy().to_string();
     let lexer = Lexer::new((&filename).into(), source);
     let mut parser = Parser::new(lexer);
+    parser.set_unsigned_char(opts.unsigned_char());
 
     let registry = DialectRegistry::new();
     register_all_dialects(&registry);
@@ -37,6 +81,11 @@ fn main() {
     context.load_all_available_dialects();
 
     let mut module = Module::new(Location::new(&context, &filename, 1, 1));
+    let options = Options {
+        control_flow: opts.control_flow,
+        memory_model: opts.memory_model,
+        no_builtin: opts.no_builtin(),
+    };
 
     let translation_unit = match TranslationUnit::parse(&mut parser) {
         Ok(translation_unit) => translation_unit,
@@ -45,12 +94,9 @@ fn main() {
             std::process::exit(1);
         }
     };
-    for external_declaration in translation_unit.0 {
-        match external_declaration {
-            c2mlir::parser::ExternalDeclaration::FunctionDefinition(function_definition) => {
-                function_definition.add_module(&context, &module);
-            }
-        }
+    if let Err(compile_error) = translation_unit.add_module(&context, &module, &options) {
+        eprintln!("{}", compile_error);
+        std::process::exit(1);
     }
 
     assert!(module.as_operation().verify());
@@ -59,6 +105,9 @@ fn main() {
         // register_all_passes();
         let pass_manager = PassManager::new(&context);
         pass_manager.add_pass(create_inliner());
+        pass_manager.add_pass(create_scf_to_control_flow());
+        // Math operations are called from libm, since not all have an LLVM intrinsic
+        pass_manager.add_pass(create_math_to_libm());
         // pass_manager.add_pass(create_canonicalizer());
         // pass_manager.add_pass(create_cse());
         pass_manager.add_pass(create_to_llvm());
@@ -75,3 +124,18 @@ fn main() {
             .unwrap()
     );
 }
+
+#[test]
+fn test_flags() {
+    let opts = |arguments: &[&str]| {
+        Opts::try_parse_from(["c2mlir", "test.c"].iter().chain(arguments)).unwrap()
+    };
+    assert!(!opts(&[]).unsigned_char());
+    assert!(!opts(&[]).no_builtin());
+    assert!(opts(&["-funsigned-char"]).unsigned_char());
+    assert!(!opts(&["-funsigned-char", "-fsigned-char"]).unsigned_char());
+    assert!(opts(&["-fno-builtin"]).no_builtin());
+    assert!(opts(&["-fbuiltin", "-fno-builtin"]).no_builtin());
+    assert!(!opts(&["-fno-builtin", "-funsigned-char", "-fbuiltin"]).no_builtin());
+    assert!(Opts::try_parse_from(["c2mlir", "test.c", "-fno-such-flag"]).is_err());
+}
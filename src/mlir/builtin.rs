@@ -0,0 +1,282 @@
+// Identifiers of the standard headers that are lowered directly, since there is no preprocessor to
+// include their declarations. They are recognized by name like the builtins of GCC.
+
+use melior::{
+    dialect::arith,
+    ir::{attribute::FloatAttribute, operation::OperationBuilder, r#type::Type},
+};
+
+use crate::{
+    lexer,
+    parser::expression::AssignmentExpression,
+    types::{CType, FloatingKind},
+};
+
+use super::{
+    add_complex, add_complex_part, add_zero, expression::add_assignment, CompileError,
+    FunctionBuilder, TypedValue,
+};
+
+/// 7.3.1 `_Complex_I` of <complex.h>, also spelled `I`, which is the imaginary unit as a
+/// `_Complex float`. Returns `None` for other identifiers, which are undeclared.
+pub fn add_builtin_constant<'c, 'a>(
+    builder: &FunctionBuilder<'c, 'a>,
+    identifier: &str,
+    location: &lexer::Location,
+) -> Option<TypedValue<'c, 'a>> {
+    if !matches!(identifier, "_Complex_I" | "I") {
+        return None;
+    }
+    let context = builder.context;
+    let location = location.mlir_location(context);
+    let r#type = CType::Complex(FloatingKind::Float);
+    let zero = add_zero(builder, &CType::Floating(FloatingKind::Float), location);
+    let one = builder
+        .append_operation(arith::constant(
+            context,
+            FloatAttribute::new(context, Type::float32(context), 1.0).into(),
+            location,
+        ))
+        .result(0)
+        .unwrap()
+        .into();
+    Some(TypedValue {
+        value: add_complex(builder, zero, one, &r#type, location),
+        r#type,
+    })
+}
+
+// 7.12 functions of <math.h> by the name of their `double` variant, the `math` operation and the
+// number of parameters. The `float` variant is suffixed with `f`.
+const MATH_FUNCTIONS: &[(&str, &str, usize)] = &[
+    ("acos", "math.acos", 1),
+    ("asin", "math.asin", 1),
+    ("atan", "math.atan", 1),
+    ("atan2", "math.atan2", 2),
+    ("cos", "math.cos", 1),
+    ("sin", "math.sin", 1),
+    ("tan", "math.tan", 1),
+    ("acosh", "math.acosh", 1),
+    ("asinh", "math.asinh", 1),
+    ("atanh", "math.atanh", 1),
+    ("cosh", "math.cosh", 1),
+    ("sinh", "math.sinh", 1),
+    ("tanh", "math.tanh", 1),
+    ("exp", "math.exp", 1),
+    ("exp2", "math.exp2", 1),
+    ("expm1", "math.expm1", 1),
+    ("log", "math.log", 1),
+    ("log10", "math.log10", 1),
+    ("log1p", "math.log1p", 1),
+    ("log2", "math.log2", 1),
+    ("cbrt", "math.cbrt", 1),
+    ("fabs", "math.absf", 1),
+    ("pow", "math.powf", 2),
+    ("sqrt", "math.sqrt", 1),
+    ("erf", "math.erf", 1),
+    ("ceil", "math.ceil", 1),
+    ("floor", "math.floor", 1),
+    ("round", "math.round", 1),
+    ("trunc", "math.trunc", 1),
+    ("copysign", "math.copysign", 2),
+    ("fma", "math.fma", 3),
+];
+
+// A function recognized by name
+#[derive(Debug, PartialEq)]
+enum Builtin {
+    // 7.3.9 `creal` or `cimag`
+    ComplexPart {
+        kind: FloatingKind,
+        imaginary: bool,
+    },
+    // A function in `MATH_FUNCTIONS`, whose parameters and result are all of `kind`
+    Math {
+        operation_name: &'static str,
+        arity: usize,
+        kind: FloatingKind,
+    },
+}
+
+impl Builtin {
+    // Without the `__builtin_` prefix, which is taken off before
+    fn new(identifier: &str) -> Option<Builtin> {
+        let complex_part = |kind, imaginary| Some(Builtin::ComplexPart { kind, imaginary });
+        match identifier {
+            "creal" => return complex_part(FloatingKind::Double, false),
+            "crealf" => return complex_part(FloatingKind::Float, false),
+            "cimag" => return complex_part(FloatingKind::Double, true),
+            "cimagf" => return complex_part(FloatingKind::Float, true),
+            _ => {}
+        }
+        let find = |name: &str| {
+            MATH_FUNCTIONS
+                .iter()
+                .find(|(function, _, _)| *function == name)
+        };
+        let (kind, &(_, operation_name, arity)) = match find(identifier) {
+            Some(function) => (FloatingKind::Double, function),
+            None => (FloatingKind::Float, find(identifier.strip_suffix('f')?)?),
+        };
+        Some(Builtin::Math {
+            operation_name,
+            arity,
+            kind,
+        })
+    }
+
+    fn arity(&self) -> usize {
+        match self {
+            Builtin::ComplexPart { .. } => 1,
+            Builtin::Math { arity, .. } => *arity,
+        }
+    }
+
+    // The type of the declaration in the standard header
+    fn prototype(&self) -> CType {
+        let (parameter, kind) = match *self {
+            Builtin::ComplexPart { kind, .. } => (CType::Complex(kind), kind),
+            Builtin::Math { kind, .. } => (CType::Floating(kind), kind),
+        };
+        CType::Function {
+            return_type: Box::new(CType::Floating(kind)),
+            parameters: vec![parameter; self.arity()],
+            variadic: false,
+        }
+    }
+}
+
+fn check_arity(arity: usize, arguments: usize) -> Result<(), String> {
+    if arguments != arity {
+        return Err(format!(
+            "too {} arguments to function call, expected {}, have {}",
+            if arguments < arity { "few" } else { "many" },
+            arity,
+            arguments
+        ));
+    }
+    Ok(())
+}
+
+/// 7.3.9 `creal`, `cimag` and the functions of <math.h> in `MATH_FUNCTIONS`, with their `float`
+/// variants. Arguments are converted as if by assignment to the types of the parameters. Without
+/// the `__builtin_` prefix, they are recognized only unless `-fno-builtin`, and unless declared
+/// with another type like GCC. Returns `None` for other functions, which are called.
+pub fn add_builtin_call<'c, 'a>(
+    builder: &mut FunctionBuilder<'c, 'a>,
+    identifier: &str,
+    arguments: &[AssignmentExpression],
+    location: &lexer::Location,
+) -> Result<Option<TypedValue<'c, 'a>>, CompileError> {
+    let (name, prefixed) = match identifier.strip_prefix("__builtin_") {
+        Some(name) => (name, true),
+        None => (identifier, false),
+    };
+    let Some(builtin) = Builtin::new(name) else {
+        return Ok(None);
+    };
+    if !prefixed
+        && (!builder.is_builtin()
+            || builder
+                .file_scope()
+                .lookup(identifier)
+                .is_some_and(|symbol| symbol.r#type != builtin.prototype()))
+    {
+        return Ok(None);
+    }
+    check_arity(builtin.arity(), arguments.len())
+        .map_err(|message| CompileError::new(location.clone(), message))?;
+
+    let context = builder.context;
+    match builtin {
+        Builtin::ComplexPart { kind, imaginary } => {
+            let value = add_assignment(builder, &arguments[0], &CType::Complex(kind), location)?;
+            let location = location.mlir_location(context);
+            Ok(Some(TypedValue {
+                value: add_complex_part(builder, value, kind, imaginary, location),
+                r#type: CType::Floating(kind),
+            }))
+        }
+        Builtin::Math {
+            operation_name,
+            kind,
+            ..
+        } => {
+            let r#type = CType::Floating(kind);
+            let operands = arguments
+                .iter()
+                .map(|argument| add_assignment(builder, argument, &r#type, location))
+                .collect::<Result<Vec<_>, _>>()?;
+            let value = builder
+                .append_operation(
+                    OperationBuilder::new(operation_name, location.mlir_location(context))
+                        .add_operands(&operands)
+                        .add_results(&[r#type.mlir_type(context)])
+                        .build()
+                        .unwrap(),
+                )
+                .result(0)
+                .unwrap()
+                .into();
+            Ok(Some(TypedValue { value, r#type }))
+        }
+    }
+}
+
+#[test]
+fn test_builtin() {
+    assert_eq!(
+        Builtin::new("sqrt"),
+        Some(Builtin::Math {
+            operation_name: "math.sqrt",
+            arity: 1,
+            kind: FloatingKind::Double
+        })
+    );
+    assert_eq!(
+        Builtin::new("fmaf"),
+        Some(Builtin::Math {
+            operation_name: "math.fma",
+            arity: 3,
+            kind: FloatingKind::Float
+        })
+    );
+    assert_eq!(
+        Builtin::new("cimagf"),
+        Some(Builtin::ComplexPart {
+            kind: FloatingKind::Float,
+            imaginary: true
+        })
+    );
+    // Only a single `f` suffix
+    assert_eq!(Builtin::new("sqrtff"), None);
+    assert_eq!(Builtin::new("f"), None);
+    assert_eq!(Builtin::new("printf"), None);
+
+    assert_eq!(
+        Builtin::new("pow").unwrap().prototype(),
+        CType::Function {
+            return_type: Box::new(CType::DOUBLE),
+            parameters: vec![CType::DOUBLE, CType::DOUBLE],
+            variadic: false
+        }
+    );
+    assert_eq!(
+        Builtin::new("crealf").unwrap().prototype(),
+        CType::Function {
+            return_type: Box::new(CType::Floating(FloatingKind::Float)),
+            parameters: vec![CType::Complex(FloatingKind::Float)],
+            variadic: false
+        }
+    );
+
+    assert_eq!(check_arity(2, 2), Ok(()));
+    assert_eq!(
+        check_arity(1, 0),
+        Err("too few arguments to function call, expected 1, have 0".to_string())
+    );
+    assert_eq!(
+        check_arity(3, 4),
+        Err("too many arguments to function call, expected 3, have 4".to_string())
+    );
+}
@@ -0,0 +1,983 @@
+use ecow::EcoString;
+use melior::{
+    dialect::{arith, llvm},
+    ir::{
+        attribute::{BoolAttribute, DenseI32ArrayAttribute, IntegerAttribute},
+        operation::OperationBuilder,
+        Identifier, Location as MlirLocation, Type,
+    },
+    Context,
+};
+
+use crate::{
+    lexer::Location,
+    parser::{
+        declaration::{
+            ArrayDeclarator, Declaration, DeclarationSpecifiers, Derivation, InitDeclarator,
+            StorageClassSpecifier,
+        },
+        expression::{
+            AdditiveExpression, ArithmeticConstant, AssignmentExpression, CastExpression,
+            ConstantError, EqualityExpression, Evaluate, EvaluateArithmetic,
+            MultiplicativeExpression, OperandType, PostfixExpression, PrimaryExpression,
+            RelationalExpression, ShiftExpression, UnaryExpression,
+        },
+    },
+    types::{BitField, CType, FloatingKind, IntegerKind},
+};
+
+use super::{
+    add_variable_lengths, add_zero, addressof_operation, constant_error, declarator_type,
+    expression::{add_assignment, is_null_pointer_constant, member_at},
+    initializer::{string_literal, Initialization},
+    AddBlock, CompileError, ControlFlow, Definition, FileScope, FileSymbol, FunctionBuilder,
+    Initializer, Lvalue, TypedValue, Variable,
+};
+
+impl AddBlock for Declaration {
+    fn add_block<'c, 'a>(&self, builder: &mut FunctionBuilder<'c, 'a>) -> Result<(), CompileError> {
+        match self {
+            Declaration::NoAttr {
+                declaration_specifiers,
+                init_declarator_list,
+            } => {
+                for init_declarator in init_declarator_list {
+                    init_declarator.add_block(builder, declaration_specifiers)?;
+                }
+            }
+            Declaration::StaticAssert { .. } => {
+                self.check_static_assert(&|operand, location| {
+                    builder.operand_type(operand, location)
+                })?
+            }
+            Declaration::Tag { .. } | Declaration::Typedef { .. } => {}
+        }
+        Ok(())
+    }
+}
+
+impl InitDeclarator {
+    // Declares an identifier at block scope, where an object without a storage class specifier
+    // is a local
+    fn add_block<'c, 'a>(
+        &self,
+        builder: &mut FunctionBuilder<'c, 'a>,
+        declaration_specifiers: &DeclarationSpecifiers,
+    ) -> Result<(), CompileError> {
+        let context = builder.context;
+        let location = self.location.mlir_location(context);
+        let (r#type, r#const, initialization) = self.object_type(
+            declaration_specifiers,
+            Some(builder),
+            &|operand, location| builder.operand_type(operand, location),
+        )?;
+        let storage_class = declaration_specifiers.storage_class;
+        if r#type.is_function() || storage_class.is_some() {
+            return self.add_block_symbol(
+                builder,
+                declaration_specifiers,
+                r#type,
+                r#const,
+                initialization,
+            );
+        }
+        if !r#type.is_complete() {
+            return Err(CompileError::new(
+                self.location.clone(),
+                format!(
+                    "variable '{}' has incomplete type '{}'",
+                    self.declarator, r#type
+                ),
+            ));
+        }
+        let alignment = declaration_specifiers
+            .alignment
+            .unwrap_or(0)
+            .max(r#type.alignment());
+        if r#type.is_variably_modified() {
+            add_variable_lengths(builder, &self.declarator, &self.derivations, &r#type)?;
+        }
+        let variable = match builder.control_flow {
+            _ if r#type.is_variably_sized() => {
+                builder.add_variable_array(r#type.clone(), r#const, alignment, location)
+            }
+            // Reading it before an assignment is undefined. A complex local is kept in
+            // memory, since `llvm.poison` takes only LLVM types.
+            ControlFlow::Scf
+                if !r#type.is_array() && !r#type.is_record() && !r#type.is_complex() =>
+            {
+                Variable::Value {
+                    value: TypedValue {
+                        value: builder
+                            .append_operation(llvm::poison(r#type.mlir_type(context), location))
+                            .result(0)
+                            .unwrap()
+                            .into(),
+                        r#type: r#type.clone(),
+                    },
+                    r#const,
+                }
+            }
+            // Arrays are accessed through their address or as a `memref`, and structures
+            // through the addresses of their members
+            _ => builder.add_local(
+                &self.declarator,
+                r#type.clone(),
+                r#const,
+                alignment,
+                location,
+            ),
+        };
+        let lvalue = variable.lvalue(&self.declarator);
+
+        // 6.2.1 The scope begins just after the completion of its declarator
+        if !builder.declare(self.declarator.clone(), variable) {
+            return Err(CompileError::new(
+                self.location.clone(),
+                format!("redefinition of '{}'", self.declarator),
+            ));
+        }
+
+        if let Some(initialization) = initialization {
+            add_initialization(builder, &lvalue, &initialization, location)?;
+        }
+        Ok(())
+    }
+
+    // 6.2.2, 6.2.4 A function or an object declared `extern` at block scope has linkage, and an
+    // object declared `static` has static storage duration. They are declared at file scope,
+    // where the `static` one is unique to its declaration, and the object is accessed through
+    // its address.
+    // TODO Address constants of objects declared at block scope
+    fn add_block_symbol<'c, 'a>(
+        &self,
+        builder: &mut FunctionBuilder<'c, 'a>,
+        declaration_specifiers: &DeclarationSpecifiers,
+        r#type: CType,
+        r#const: bool,
+        initialization: Option<Initialization>,
+    ) -> Result<(), CompileError> {
+        let context = builder.context;
+        let error = |message: String| Err(CompileError::new(self.location.clone(), message));
+        let storage_class = declaration_specifiers.storage_class;
+        let (definition, initializer) = match storage_class {
+            _ if r#type.is_function() => {
+                if storage_class == Some(StorageClassSpecifier::Static) {
+                    return error(
+                        "function declared in block scope cannot have 'static' storage class"
+                            .to_string(),
+                    );
+                }
+                (Definition::Declaration, None)
+            }
+            _ if r#type.is_variably_modified() => {
+                return error(format!(
+                    "{} declaration cannot have '{}' storage class",
+                    if r#type.is_variably_sized() {
+                        "variable length array"
+                    } else {
+                        "variably modified type"
+                    },
+                    match storage_class {
+                        Some(StorageClassSpecifier::Extern) => "extern",
+                        _ => "static",
+                    }
+                ))
+            }
+            // TODO It has no constant initializer
+            _ if r#type == CType::VaList => {
+                return error(
+                    "'va_list' objects with static storage duration are not supported".to_string(),
+                )
+            }
+            Some(StorageClassSpecifier::Extern) => {
+                if initialization.is_some() {
+                    return error(
+                        "declaration of block scope identifier with linkage cannot have an \
+                         initializer"
+                            .to_string(),
+                    );
+                }
+                (Definition::Declaration, None)
+            }
+            _ if !r#type.is_complete() => {
+                return error(format!(
+                    "variable '{}' has incomplete type '{}'",
+                    self.declarator, r#type
+                ))
+            }
+            // 6.7.9 It is initialized before program startup, so only with constants
+            _ => {
+                let initializer = match &initialization {
+                    Some(initialization) => Some(static_initializer(
+                        initialization,
+                        &r#type,
+                        &builder.file_scope,
+                        &|operand, location| builder.operand_type(operand, location),
+                    )?),
+                    None => None,
+                };
+                (Definition::Definition, initializer)
+            }
+        };
+        let identifier = builder.file_scope.declare_block_symbol(
+            &self.declarator,
+            storage_class,
+            FileSymbol {
+                location: self.location.clone(),
+                r#type: r#type.clone(),
+                r#const,
+                internal: storage_class == Some(StorageClassSpecifier::Static),
+                definition,
+                initializer,
+                alignment: declaration_specifiers.alignment,
+            },
+        )?;
+        if r#type.is_function() {
+            return Ok(());
+        }
+        // In the entry block, since a jump may enter the scope past the declaration
+        let pointer = builder
+            .alloca_block
+            .insert_operation(
+                0,
+                addressof_operation(context, &identifier, self.location.mlir_location(context)),
+            )
+            .result(0)
+            .unwrap()
+            .into();
+        let variable = Variable::Memory {
+            pointer,
+            r#type,
+            r#const,
+        };
+        if !builder.declare(self.declarator.clone(), variable) {
+            return error(format!("redefinition of '{}'", self.declarator));
+        }
+        Ok(())
+    }
+
+    // Returns the declared type with whether the object is const, and its initializer resolved
+    // against the type
+    fn object_type(
+        &self,
+        declaration_specifiers: &DeclarationSpecifiers,
+        builder: Option<&FunctionBuilder>,
+        operand_type: OperandType,
+    ) -> Result<(CType, bool, Option<Initialization<'_>>), CompileError> {
+        // 6.7.9 An array of unknown size gets the length of its initializer, which is
+        // resolved with the largest length first
+        let unknown_length = matches!(
+            self.derivations.last(),
+            Some(Derivation::Array(ArrayDeclarator { length: None, .. }))
+        );
+        let (mut r#type, r#const) = declarator_type(
+            declaration_specifiers,
+            &self.declarator,
+            &self.derivations,
+            self.initializer.as_ref().map(|_| u32::MAX as u64),
+            operand_type,
+        )?;
+        if r#type.is_void() {
+            return Err(CompileError::new(
+                self.location.clone(),
+                format!("variable '{}' has incomplete type 'void'", self.declarator),
+            ));
+        }
+        // 6.7.6.2 Element types are complete
+        let mut element = &r#type;
+        while let CType::Array { element: inner, .. }
+        | CType::VariableArray { element: inner, .. } = element
+        {
+            element = inner;
+        }
+        if r#type.is_array() && !element.is_complete() {
+            return Err(CompileError::new(
+                self.location.clone(),
+                format!("array has incomplete element type '{}'", element),
+            ));
+        }
+        // The alignment of a type cannot be reduced
+        if declaration_specifiers
+            .alignment
+            .is_some_and(|alignment| r#type.is_complete() && alignment < r#type.alignment())
+        {
+            return Err(CompileError::new(
+                declaration_specifiers.location.clone(),
+                format!(
+                    "requested alignment is less than minimum alignment of {} for type '{}'",
+                    r#type.alignment(),
+                    r#type
+                ),
+            ));
+        }
+        let Some(initializer) = &self.initializer else {
+            return Ok((r#type, r#const, None));
+        };
+        if let CType::Function { .. } = r#type {
+            return Err(CompileError::new(
+                initializer.location().clone(),
+                "illegal initializer (only variables can be initialized)".to_string(),
+            ));
+        }
+        if r#type.is_variably_sized() {
+            return Err(CompileError::new(
+                initializer.location().clone(),
+                "variable-sized object may not be initialized".to_string(),
+            ));
+        }
+        if !r#type.is_complete() {
+            return Err(CompileError::new(
+                self.location.clone(),
+                format!(
+                    "variable '{}' has incomplete type '{}'",
+                    self.declarator, r#type
+                ),
+            ));
+        }
+        let initialization = Initialization::new(builder, initializer, &r#type)?;
+        if let (true, CType::Array { length, .. }) = (unknown_length, &mut r#type) {
+            *length = initialization.length();
+            if *length == 0 {
+                return Err(CompileError::new(
+                    self.location.clone(),
+                    format!(
+                        "'{}' declared as an array with a zero size",
+                        self.declarator
+                    ),
+                ));
+            }
+        }
+        Ok((r#type, r#const, Some(initialization)))
+    }
+
+    // Declares an identifier with file scope
+    fn declare(
+        &self,
+        context: &Context,
+        declaration_specifiers: &DeclarationSpecifiers,
+        file_scope: &mut FileScope,
+    ) -> Result<(), CompileError> {
+        let operand_type = |operand: &UnaryExpression, location: &Location| {
+            file_scope.operand_type(context, operand, location)
+        };
+        let (r#type, r#const, initialization) =
+            self.object_type(declaration_specifiers, None, &operand_type)?;
+        if r#type.is_variably_modified() {
+            return Err(CompileError::new(
+                self.location.clone(),
+                if r#type.is_variably_sized() {
+                    "variable length array declaration not allowed at file scope"
+                } else {
+                    "variably modified type declaration not allowed at file scope"
+                }
+                .to_string(),
+            ));
+        }
+        // TODO It has no constant initializer
+        if r#type == CType::VaList {
+            return Err(CompileError::new(
+                self.location.clone(),
+                "'va_list' objects with file scope are not supported".to_string(),
+            ));
+        }
+        let storage_class = declaration_specifiers.storage_class;
+        let (definition, initializer) = match &initialization {
+            Some(initialization) => (
+                Definition::Definition,
+                Some(static_initializer(
+                    initialization,
+                    &r#type,
+                    file_scope,
+                    &operand_type,
+                )?),
+            ),
+            // 6.7.8 A function may be declared with a typedef name
+            None if storage_class == Some(StorageClassSpecifier::Extern)
+                || matches!(r#type, CType::Function { .. }) =>
+            {
+                (Definition::Declaration, None)
+            }
+            None => (Definition::Tentative, None),
+        };
+        file_scope.declare(
+            &self.declarator,
+            storage_class,
+            FileSymbol {
+                location: self.location.clone(),
+                r#type,
+                r#const,
+                internal: storage_class == Some(StorageClassSpecifier::Static),
+                definition,
+                initializer,
+                alignment: declaration_specifiers.alignment,
+            },
+        )
+    }
+}
+
+impl Declaration {
+    /// Declares an object with file scope 6.9.2. It is emitted after the whole translation unit
+    /// is seen, since a later declaration may define it.
+    pub(super) fn declare(
+        &self,
+        context: &Context,
+        file_scope: &mut FileScope,
+    ) -> Result<(), CompileError> {
+        let operand_type = |operand: &UnaryExpression, location: &Location| {
+            file_scope.operand_type(context, operand, location)
+        };
+        match self {
+            Declaration::NoAttr {
+                declaration_specifiers,
+                init_declarator_list,
+            } => {
+                for init_declarator in init_declarator_list {
+                    init_declarator.declare(context, declaration_specifiers, file_scope)?;
+                }
+                Ok(())
+            }
+            Declaration::StaticAssert { .. } => self.check_static_assert(&operand_type),
+            Declaration::Tag { .. } | Declaration::Typedef { .. } => Ok(()),
+        }
+    }
+
+    // 6.7.10 The constant expression must not be 0
+    fn check_static_assert(&self, operand_type: OperandType) -> Result<(), CompileError> {
+        let Declaration::StaticAssert {
+            location,
+            expression,
+            message,
+        } = self
+        else {
+            unreachable!()
+        };
+        let value = expression.evaluate_with(operand_type).map_err(|error| {
+            constant_error(
+                error,
+                "static assertion expression is not an integral constant expression",
+            )
+        })?;
+        if value.value != 0 {
+            return Ok(());
+        }
+        Err(CompileError::new(
+            location.clone(),
+            match message {
+                Some(message) => format!(
+                    "static assertion failed: {}",
+                    String::from_utf8_lossy(message)
+                ),
+                None => "static assertion failed".to_string(),
+            },
+        ))
+    }
+}
+
+// 6.7.9 Initializes an object with automatic storage duration. It is not an assignment, so it is
+// allowed for const objects.
+fn add_initialization<'c, 'a>(
+    builder: &mut FunctionBuilder<'c, 'a>,
+    lvalue: &Lvalue<'c, 'a>,
+    initialization: &Initialization,
+    location: MlirLocation<'c>,
+) -> Result<(), CompileError> {
+    let context = builder.context;
+    let r#type = lvalue.r#type().clone();
+    match initialization {
+        // Copied from an array of static storage duration
+        Initialization::Expression {
+            location: expression_location,
+            expression,
+        } if r#type.is_array() => {
+            let value = array_initializer(&r#type, expression, expression_location)?;
+            let identifier = builder
+                .file_scope()
+                .string_literal(&value, expression_location);
+            let pointer = builder
+                .append_operation(addressof_operation(context, &identifier, location))
+                .result(0)
+                .unwrap()
+                .into();
+            let v0 = builder.load(
+                &Lvalue::Memory {
+                    pointer,
+                    r#type,
+                    r#const: true,
+                    alignment: None,
+                },
+                location,
+            );
+            builder.store(lvalue, v0.value, location);
+        }
+        Initialization::Expression {
+            location: expression_location,
+            expression,
+        } => {
+            let v0 = add_assignment(builder, expression, &r#type, expression_location)?;
+            builder.store(lvalue, v0, location);
+        }
+        Initialization::List(_) if r#type.is_scalar() => {
+            let v0 = add_zero(builder, &r#type, location);
+            builder.store(lvalue, v0, location);
+        }
+        // The subobjects without an initializer are 0, so the whole object is cleared first
+        Initialization::List(subobjects) => {
+            let pointer = builder.address(lvalue, location);
+            let value = builder.add_constant(8, 0, location);
+            let size = builder.add_constant(64, r#type.size(), location);
+            builder.append_operation(
+                OperationBuilder::new("llvm.intr.memset", location)
+                    .add_operands(&[pointer, value, size])
+                    .add_attributes(&[(
+                        Identifier::new(context, "isVolatile"),
+                        BoolAttribute::new(context, false).into(),
+                    )])
+                    .build()
+                    .unwrap(),
+            );
+            for (index, initialization) in subobjects {
+                let subobject = match &r#type {
+                    CType::Array { .. } => element(builder, lvalue, *index, location),
+                    _ => member_at(
+                        builder,
+                        pointer,
+                        &r#type,
+                        lvalue.is_const(),
+                        lvalue.alignment(),
+                        *index as usize,
+                        location,
+                    ),
+                };
+                add_initialization(builder, &subobject, initialization, location)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// The element at `index` of an array, which is indexed in a `memref`
+fn element<'c, 'a>(
+    builder: &FunctionBuilder<'c, 'a>,
+    lvalue: &Lvalue<'c, 'a>,
+    index: u64,
+    location: MlirLocation<'c>,
+) -> Lvalue<'c, 'a> {
+    let context = builder.context;
+    match lvalue {
+        Lvalue::Memref {
+            memref,
+            memref_type,
+            indices,
+            r#const,
+        } => {
+            let index = builder
+                .append_operation(arith::constant(
+                    context,
+                    IntegerAttribute::new(Type::index(context), index as i64).into(),
+                    location,
+                ))
+                .result(0)
+                .unwrap()
+                .into();
+            let mut indices = indices.clone();
+            indices.push(index);
+            Lvalue::Memref {
+                memref: *memref,
+                memref_type: memref_type.clone(),
+                indices,
+                r#const: *r#const,
+            }
+        }
+        _ => {
+            let CType::Array { element, .. } = lvalue.r#type() else {
+                unreachable!()
+            };
+            let pointer = builder
+                .append_operation(llvm::get_element_ptr(
+                    context,
+                    builder.address(lvalue, location),
+                    DenseI32ArrayAttribute::new(context, &[0, index as i32]),
+                    lvalue.r#type().mlir_type(context),
+                    llvm::r#type::pointer(context, 0),
+                    location,
+                ))
+                .result(0)
+                .unwrap()
+                .into();
+            // Elements are at multiples of their size, so they are as aligned as the array
+            Lvalue::Memory {
+                pointer,
+                r#type: (**element).clone(),
+                r#const: lvalue.is_const(),
+                alignment: lvalue.alignment(),
+            }
+        }
+    }
+}
+
+// 6.7.9 Initializers of objects with static storage duration are constant
+fn static_initializer(
+    initialization: &Initialization,
+    r#type: &CType,
+    file_scope: &FileScope,
+    operand_type: OperandType,
+) -> Result<Initializer, CompileError> {
+    let error = |error| constant_error(error, "initializer element is not a compile-time constant");
+    let (location, initializer) = match initialization {
+        Initialization::Expression {
+            location,
+            expression,
+        } => (*location, *expression),
+        Initialization::List(_) if r#type.is_scalar() => return Ok(Initializer::Integer(0)),
+        Initialization::List(_) => {
+            let mut value = vec![0; r#type.size() as usize];
+            let mut addresses = Vec::new();
+            add_bytes(
+                initialization,
+                r#type,
+                0,
+                None,
+                &|initialization, r#type| {
+                    static_initializer(initialization, r#type, file_scope, operand_type)
+                },
+                &mut value,
+                &mut addresses,
+            )?;
+            if addresses.is_empty() {
+                return Ok(Initializer::String(value));
+            }
+            return Ok(Initializer::Aggregate { value, addresses });
+        }
+    };
+    if r#type.is_array() {
+        return Ok(Initializer::String(array_initializer(
+            r#type,
+            initializer,
+            location,
+        )?));
+    }
+    if r#type.is_record() {
+        return Err(error(ConstantError::NotConstant(location.clone())));
+    }
+    if r#type.is_floating() || r#type.is_complex() {
+        return Ok(Initializer::String(
+            floating_initializer(initializer, r#type, operand_type).map_err(error)?,
+        ));
+    }
+    if !r#type.is_pointer() {
+        let ArithmeticConstant::Integer(value) = initializer
+            .evaluate_arithmetic(operand_type)
+            .and_then(|value| value.convert(r#type, location))
+            .map_err(error)?
+        else {
+            unreachable!()
+        };
+        return Ok(Initializer::Integer(value.value));
+    }
+    if is_null_pointer_constant(initializer.evaluate_with(operand_type)) {
+        return Ok(Initializer::Integer(0));
+    }
+    // Such as `(void *)0`
+    if let Some(CastExpression::Cast {
+        type_name,
+        expression,
+        ..
+    }) = initializer.as_cast_expression()
+    {
+        if type_name.r#type().is_pointer()
+            && is_null_pointer_constant(expression.evaluate_with(operand_type))
+        {
+            return Ok(Initializer::Integer(0));
+        }
+    }
+    let AssignmentExpression::EqualityExpression(EqualityExpression::RelationalExpression(
+        RelationalExpression::ShiftExpression(ShiftExpression::AdditiveExpression(
+            additive_expression,
+        )),
+    )) = initializer
+    else {
+        return Err(error(ConstantError::NotConstant(location.clone())));
+    };
+    let (identifier, address_type, offset) =
+        address_constant(additive_expression, file_scope, operand_type, location).map_err(error)?;
+    if !address_type.is_pointer_convertible(r#type) {
+        return Err(CompileError::new(
+            location.clone(),
+            format!(
+                "incompatible types initializing '{}' with '{}'",
+                r#type, address_type
+            ),
+        ));
+    }
+    Ok(Initializer::Address { identifier, offset })
+}
+
+// 6.6 The bytes of a floating or complex object initialized with an arithmetic constant
+// expression. The imaginary part is 0.
+fn floating_initializer(
+    initializer: &AssignmentExpression,
+    r#type: &CType,
+    operand_type: OperandType,
+) -> Result<Vec<u8>, ConstantError> {
+    let value = initializer.evaluate_arithmetic(operand_type)?.to_f64();
+    let (CType::Floating(kind) | CType::Complex(kind)) = r#type else {
+        unreachable!("{} is not floating", r#type)
+    };
+    let mut bytes = match kind {
+        FloatingKind::Float => (value as f32).to_le_bytes().to_vec(),
+        FloatingKind::Double => value.to_le_bytes().to_vec(),
+    };
+    bytes.resize(r#type.size() as usize, 0);
+    Ok(bytes)
+}
+
+// Writes the bytes of a subobject at `offset` in an aggregate, where `scalar` gives the
+// initializers of scalars and arrays. Address constants are collected with their offsets
+// instead, since they have no bytes until linked.
+fn add_bytes(
+    initialization: &Initialization,
+    r#type: &CType,
+    offset: u64,
+    bit_field: Option<BitField>,
+    scalar: &dyn Fn(&Initialization, &CType) -> Result<Initializer, CompileError>,
+    bytes: &mut [u8],
+    addresses: &mut Vec<(u64, EcoString, i64)>,
+) -> Result<(), CompileError> {
+    match (initialization, r#type) {
+        (Initialization::List(subobjects), CType::Array { element, .. }) => {
+            for (index, initialization) in subobjects {
+                add_bytes(
+                    initialization,
+                    element,
+                    offset + index * element.size(),
+                    None,
+                    scalar,
+                    bytes,
+                    addresses,
+                )?;
+            }
+        }
+        (Initialization::List(subobjects), CType::Record(record)) => {
+            let layout = record.layout().unwrap();
+            for (index, initialization) in subobjects {
+                let member = &layout.members[*index as usize];
+                add_bytes(
+                    initialization,
+                    &member.r#type,
+                    offset + member.offset,
+                    member.bit_field,
+                    scalar,
+                    bytes,
+                    addresses,
+                )?;
+            }
+        }
+        _ => match scalar(initialization, r#type)? {
+            Initializer::Integer(value) => match bit_field {
+                Some(BitField { width, bit_offset }) => {
+                    for bit in 0..width {
+                        if value >> bit & 1 != 0 {
+                            let position = bit_offset + bit;
+                            bytes[(offset + position / 8) as usize] |= 1 << (position % 8);
+                        }
+                    }
+                }
+                None => {
+                    let size = r#type.size() as usize;
+                    bytes[offset as usize..][..size].copy_from_slice(&value.to_le_bytes()[..size]);
+                }
+            },
+            Initializer::String(value) => {
+                bytes[offset as usize..][..value.len()].copy_from_slice(&value)
+            }
+            Initializer::Address {
+                identifier,
+                offset: addend,
+            } => addresses.push((offset, identifier, addend)),
+            Initializer::Aggregate { .. } => unreachable!(),
+        },
+    }
+    Ok(())
+}
+
+// 6.6 `&x`, `&x + n` and `&x - n` for an object `x` with static storage duration, where `&x` may
+// also be an array, a string literal or a function, and cast to another pointer type. Returns the
+// object with the type of the address and the offset in bytes.
+fn address_constant(
+    expression: &AdditiveExpression,
+    file_scope: &FileScope,
+    operand_type: OperandType,
+    location: &Location,
+) -> Result<(EcoString, CType, i64), ConstantError> {
+    match expression {
+        AdditiveExpression::PrimaryExpression(MultiplicativeExpression::CastExpression(
+            cast_expression,
+        )) => cast_address_constant(cast_expression, file_scope, location),
+        AdditiveExpression::Add { lhs, rhs, location }
+        | AdditiveExpression::Minus { lhs, rhs, location } => {
+            let (identifier, r#type, offset) =
+                address_constant(lhs, file_scope, operand_type, location)?;
+            let CType::Pointer { pointee, .. } = &r#type else {
+                unreachable!()
+            };
+            // The size of a function is unknown
+            if !pointee.is_complete() {
+                return Err(ConstantError::NotConstant(location.clone()));
+            }
+            let index = rhs.evaluate_with(operand_type)?.value;
+            let index = match expression {
+                AdditiveExpression::Minus { .. } => -index,
+                _ => index,
+            };
+            let offset = offset + (index * pointee.size() as i128) as i64;
+            Ok((identifier, r#type, offset))
+        }
+        _ => Err(ConstantError::NotConstant(location.clone())),
+    }
+}
+
+fn cast_address_constant(
+    expression: &CastExpression,
+    file_scope: &FileScope,
+    location: &Location,
+) -> Result<(EcoString, CType, i64), ConstantError> {
+    let not_constant = || ConstantError::NotConstant(location.clone());
+    let unary_expression = match expression {
+        CastExpression::UnaryExpression(unary_expression) => unary_expression,
+        CastExpression::Cast {
+            type_name,
+            expression,
+            ..
+        } => {
+            let r#type = type_name.r#type();
+            if !r#type.is_pointer() {
+                return Err(not_constant());
+            }
+            let (identifier, _, offset) = cast_address_constant(expression, file_scope, location)?;
+            return Ok((identifier, r#type, offset));
+        }
+    };
+    let (identifier, r#type, r#const) = match unary_expression {
+        UnaryExpression::Address { expression, .. } => {
+            let identifier = expression.as_identifier().ok_or_else(not_constant)?;
+            let symbol = file_scope.lookup(identifier).ok_or_else(not_constant)?;
+            (identifier.clone(), symbol.r#type.clone(), symbol.r#const)
+        }
+        UnaryExpression::PostfixExpression(PostfixExpression::PrimaryExpression(
+            PrimaryExpression::StringLiteral {
+                value,
+                r#type,
+                location,
+            },
+        )) => {
+            let mut value = value.clone();
+            value.push(0);
+            let identifier = file_scope.string_literal(&value, location);
+            return Ok((identifier, decay(r#type.clone(), false), 0));
+        }
+        unary_expression => {
+            let identifier = unary_expression.as_identifier().ok_or_else(not_constant)?;
+            let symbol = file_scope.lookup(identifier).ok_or_else(not_constant)?;
+            match symbol.r#type {
+                CType::Array { .. } => {
+                    return Ok((
+                        identifier.clone(),
+                        decay(symbol.r#type.clone(), symbol.r#const),
+                        0,
+                    ))
+                }
+                CType::Function { .. } => (identifier.clone(), symbol.r#type.clone(), false),
+                _ => return Err(not_constant()),
+            }
+        }
+    };
+    // `llvm.mlir.addressof` takes only an `llvm.func`
+    if r#type.is_function() && !file_scope.is_llvm_function(&identifier) {
+        return Err(not_constant());
+    }
+    let r#type = CType::Pointer {
+        pointee: Box::new(r#type),
+        r#const,
+    };
+    Ok((identifier, r#type, 0))
+}
+
+// 6.3.2.1 The type of an array converted to a pointer to its first element
+fn decay(r#type: CType, r#const: bool) -> CType {
+    match r#type {
+        CType::Array { element, .. } => CType::Pointer {
+            pointee: element,
+            r#const,
+        },
+        r#type => r#type,
+    }
+}
+
+// 6.7.9 Returns the contents of an array initialized with a string literal
+fn array_initializer(
+    r#type: &CType,
+    initializer: &AssignmentExpression,
+    location: &Location,
+) -> Result<Vec<u8>, CompileError> {
+    let CType::Array { element, length } = r#type else {
+        unreachable!()
+    };
+    let char = matches!(
+        **element,
+        CType::Integer {
+            kind: IntegerKind::Char | IntegerKind::PlainChar,
+            ..
+        }
+    );
+    let value = match string_literal(initializer) {
+        Some(value) if char => value,
+        _ if char => {
+            return Err(CompileError::new(
+                location.clone(),
+                "array initializer must be an initializer list or string literal".to_string(),
+            ))
+        }
+        _ => {
+            return Err(CompileError::new(
+                location.clone(),
+                "array initializer must be an initializer list".to_string(),
+            ))
+        }
+    };
+    // The terminating null character is dropped if there is no room for it
+    if value.len() as u64 > *length {
+        return Err(CompileError::new(
+            location.clone(),
+            "initializer-string for char array is too long".to_string(),
+        ));
+    }
+    let mut value = value.to_vec();
+    value.resize(*length as usize, 0);
+    Ok(value)
+}
+
+#[test]
+fn test_lower_init_declarator_list() {
+    use super::{lower, Options};
+
+    let src = "int a, b = 2; \
+               static int s = 1, t; \
+               extern int e1, e2; \
+               int f(void), g(void); \
+               int f(void) { \
+                 int x = 1, y = x + 1; \
+                 static int u, v = 3; \
+                 extern int e1, e2; \
+                 return a + b + s + t + x + y + u + v + e1 + e2 + g(); \
+               } \
+               int g(void) { return 0; }";
+    let module = lower(src, &Options::default()).unwrap();
+    for symbol in ["@a", "@b", "@s", "@t", "@e1", "@e2", "@u.", "@v.", "@g"] {
+        assert!(module.contains(symbol), "{} is not in {}", symbol, module);
+    }
+
+    // Each local is declared once
+    let src = "int f(void) { int x, x; return 0; }";
+    assert!(lower(src, &Options::default()).is_err());
+}
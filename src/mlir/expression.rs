@@ -0,0 +1,2184 @@
+use ecow::EcoString;
+use melior::{
+    dialect::{
+        arith::{self, CmpfPredicate, CmpiPredicate},
+        func, llvm,
+    },
+    ir::{
+        attribute::{
+            DenseI32ArrayAttribute, FlatSymbolRefAttribute, FloatAttribute, IntegerAttribute,
+            TypeAttribute,
+        },
+        operation::OperationBuilder,
+        r#type::IntegerType,
+        Attribute, Identifier, Location, Value,
+    },
+};
+
+use crate::{
+    lexer,
+    parser::{
+        expression::{
+            AdditiveExpression, AssignmentExpression, CastExpression, ConstantError,
+            EqualityExpression, Evaluate, Expression, GenericSelection, IntegerConstant,
+            MultiplicativeExpression, PostfixExpression, PrimaryExpression, RelationalExpression,
+            ShiftExpression, UnaryExpression, VaBuiltin,
+        },
+        Constant,
+    },
+    types::{CType, RecordKind},
+};
+
+use super::{
+    add_address, add_complex, add_condition, add_conversion, add_index, add_operation, add_zero,
+    addressof_operation,
+    builtin::{add_builtin_call, add_builtin_constant},
+    record_fields, AddLvalue, AddValue, CompileError, Field, FunctionBuilder, Lvalue, TypedValue,
+};
+
+// `operation_name` is the signed integer variant; the unsigned, floating or complex one is chosen
+// by the converted type. Signed + - * overflow is undefined in C, so they are marked `nsw`.
+fn add_arithmetic<'c, 'a>(
+    builder: &FunctionBuilder<'c, 'a>,
+    operation_name: &str,
+    lhs: TypedValue<'c, 'a>,
+    rhs: TypedValue<'c, 'a>,
+    location: Location<'c>,
+) -> TypedValue<'c, 'a> {
+    let context = builder.context;
+    let r#type = CType::usual_arithmetic_conversion(&lhs.r#type, &rhs.r#type);
+    let lhs = add_conversion(builder, lhs, &r#type, location);
+    let rhs = add_conversion(builder, rhs, &r#type, location);
+
+    let mut operation = OperationBuilder::new(
+        match (operation_name, &r#type) {
+            ("arith.addi", CType::Floating(_)) => "arith.addf",
+            ("arith.subi", CType::Floating(_)) => "arith.subf",
+            ("arith.muli", CType::Floating(_)) => "arith.mulf",
+            ("arith.divsi", CType::Floating(_)) => "arith.divf",
+            ("arith.addi", CType::Complex(_)) => "complex.add",
+            ("arith.subi", CType::Complex(_)) => "complex.sub",
+            ("arith.muli", CType::Complex(_)) => "complex.mul",
+            ("arith.divsi", CType::Complex(_)) => "complex.div",
+            ("arith.divsi", _) if !r#type.is_signed() => "arith.divui",
+            ("arith.remsi", _) if !r#type.is_signed() => "arith.remui",
+            (operation_name, _) => operation_name,
+        },
+        location,
+    )
+    .add_operands(&[lhs, rhs]);
+    if r#type.is_signed() && matches!(operation_name, "arith.addi" | "arith.subi" | "arith.muli") {
+        operation = operation.add_attributes(&[(
+            Identifier::new(context, "overflowFlags"),
+            Attribute::parse(context, "#arith.overflow<nsw>").unwrap(),
+        )]);
+    }
+
+    TypedValue {
+        value: add_operation(builder, operation),
+        r#type,
+    }
+}
+
+// 6.5.7 The integer promotions are performed on each of the operands separately
+fn add_shift<'c, 'a>(
+    builder: &FunctionBuilder<'c, 'a>,
+    operation_name: &str,
+    lhs: TypedValue<'c, 'a>,
+    rhs: TypedValue<'c, 'a>,
+    location: Location<'c>,
+) -> TypedValue<'c, 'a> {
+    let r#type = lhs.r#type.integer_promotion();
+    let lhs = add_conversion(builder, lhs, &r#type, location);
+    let rhs = add_conversion(builder, rhs, &r#type, location);
+
+    let operation_name = match operation_name {
+        "arith.shrsi" if !r#type.is_signed() => "arith.shrui",
+        operation_name => operation_name,
+    };
+
+    TypedValue {
+        value: add_operation(
+            builder,
+            OperationBuilder::new(operation_name, location).add_operands(&[lhs, rhs]),
+        ),
+        r#type,
+    }
+}
+
+// `predicate` is the signed integer variant; the unsigned or floating one is chosen by the converted
+// type. Complex values are only compared for equality.
+fn add_comparison<'c, 'a>(
+    builder: &FunctionBuilder<'c, 'a>,
+    predicate: CmpiPredicate,
+    lhs: TypedValue<'c, 'a>,
+    rhs: TypedValue<'c, 'a>,
+    location: Location<'c>,
+) -> TypedValue<'c, 'a> {
+    let context = builder.context;
+    let r#type = CType::usual_arithmetic_conversion(&lhs.r#type, &rhs.r#type);
+    let lhs = add_conversion(builder, lhs, &r#type, location);
+    let rhs = add_conversion(builder, rhs, &r#type, location);
+
+    let condition = match r#type {
+        // Ordered except for `!=`, which is true if either is a NaN
+        CType::Floating(_) => {
+            let predicate = match predicate {
+                CmpiPredicate::Eq => CmpfPredicate::Oeq,
+                CmpiPredicate::Ne => CmpfPredicate::Une,
+                CmpiPredicate::Slt => CmpfPredicate::Olt,
+                CmpiPredicate::Sgt => CmpfPredicate::Ogt,
+                CmpiPredicate::Sle => CmpfPredicate::Ole,
+                _ => CmpfPredicate::Oge,
+            };
+            builder.append_operation(arith::cmpf(context, predicate, lhs, rhs, location))
+        }
+        CType::Complex(_) => builder.append_operation(
+            OperationBuilder::new(
+                match predicate {
+                    CmpiPredicate::Eq => "complex.eq",
+                    _ => "complex.neq",
+                },
+                location,
+            )
+            .add_operands(&[lhs, rhs])
+            .add_results(&[IntegerType::new(context, 1).into()])
+            .build()
+            .unwrap(),
+        ),
+        _ => {
+            let predicate = match predicate {
+                CmpiPredicate::Slt if !r#type.is_signed() => CmpiPredicate::Ult,
+                CmpiPredicate::Sgt if !r#type.is_signed() => CmpiPredicate::Ugt,
+                CmpiPredicate::Sle if !r#type.is_signed() => CmpiPredicate::Ule,
+                CmpiPredicate::Sge if !r#type.is_signed() => CmpiPredicate::Uge,
+                predicate => predicate,
+            };
+            builder.append_operation(arith::cmpi(context, predicate, lhs, rhs, location))
+        }
+    };
+
+    // The result has type int
+    TypedValue {
+        value: builder
+            .append_operation(arith::extui(
+                condition.result(0).unwrap().into(),
+                CType::INT.mlir_type(context),
+                location,
+            ))
+            .result(0)
+            .unwrap()
+            .into(),
+        r#type: CType::INT,
+    }
+}
+
+fn invalid_operands(lhs: &CType, rhs: &CType, location: &lexer::Location) -> CompileError {
+    CompileError::new(
+        location.clone(),
+        format!(
+            "invalid operands to binary expression ('{}' and '{}')",
+            lhs, rhs
+        ),
+    )
+}
+
+// 6.5.5 `*` and `/` take arithmetic types
+fn check_arithmetic(
+    lhs: &TypedValue,
+    rhs: &TypedValue,
+    location: &lexer::Location,
+) -> Result<(), CompileError> {
+    if lhs.r#type.is_arithmetic() && rhs.r#type.is_arithmetic() {
+        Ok(())
+    } else {
+        Err(invalid_operands(&lhs.r#type, &rhs.r#type, location))
+    }
+}
+
+// 6.5.5, 6.5.7 `%` and shifts take integers only
+fn check_integers(
+    lhs: &TypedValue,
+    rhs: &TypedValue,
+    location: &lexer::Location,
+) -> Result<(), CompileError> {
+    if lhs.r#type.is_integer() && rhs.r#type.is_integer() {
+        Ok(())
+    } else {
+        Err(invalid_operands(&lhs.r#type, &rhs.r#type, location))
+    }
+}
+
+// 6.5.3.3 `+` and `-` take arithmetic types, `~` integers and `!` scalars, as told by `valid`
+fn check_unary(
+    operand: &TypedValue,
+    valid: bool,
+    location: &lexer::Location,
+) -> Result<(), CompileError> {
+    if valid {
+        Ok(())
+    } else {
+        Err(CompileError::new(
+            location.clone(),
+            format!(
+                "invalid argument type '{}' to unary expression",
+                operand.r#type
+            ),
+        ))
+    }
+}
+
+// 6.5.3.3 The integer promotions are performed on the operand of `+`, `-` and `~`
+fn add_promotion<'c, 'a>(
+    builder: &FunctionBuilder<'c, 'a>,
+    operand: TypedValue<'c, 'a>,
+    location: &lexer::Location,
+) -> TypedValue<'c, 'a> {
+    let r#type = operand.r#type.integer_promotion();
+    TypedValue {
+        value: add_conversion(
+            builder,
+            operand,
+            &r#type,
+            location.mlir_location(builder.context),
+        ),
+        r#type,
+    }
+}
+
+// 6.5.3.3 The negative of a promoted operand. An integer is subtracted from 0, while the sign of
+// a floating value is flipped so that `-0.0` keeps its sign.
+fn add_negation<'c, 'a>(
+    builder: &FunctionBuilder<'c, 'a>,
+    operand: TypedValue<'c, 'a>,
+    location: &lexer::Location,
+) -> TypedValue<'c, 'a> {
+    let location = location.mlir_location(builder.context);
+    let operation_name = match operand.r#type {
+        CType::Floating(_) => "arith.negf",
+        CType::Complex(_) => "complex.neg",
+        _ => {
+            let zero = TypedValue {
+                value: add_zero(builder, &operand.r#type, location),
+                r#type: operand.r#type.clone(),
+            };
+            return add_arithmetic(builder, "arith.subi", zero, operand, location);
+        }
+    };
+    TypedValue {
+        value: add_operation(
+            builder,
+            OperationBuilder::new(operation_name, location).add_operands(&[operand.value]),
+        ),
+        r#type: operand.r#type,
+    }
+}
+
+fn pointee<'t>(pointer: &'t CType, location: &lexer::Location) -> Result<&'t CType, CompileError> {
+    match pointer {
+        CType::Pointer { pointee, .. } if pointee.is_void() => Err(CompileError::new(
+            location.clone(),
+            "arithmetic on a pointer to void".to_string(),
+        )),
+        CType::Pointer { pointee, .. } if !pointee.is_complete() => Err(CompileError::new(
+            location.clone(),
+            format!(
+                "arithmetic on a pointer to an incomplete type '{}'",
+                pointee
+            ),
+        )),
+        CType::Pointer { pointee, .. } => Ok(pointee),
+        _ => unreachable!("{} is not a pointer", pointer),
+    }
+}
+
+// 6.5.6 Moves `pointer` by `index` elements, backwards if `negate`. The pointee must be
+// checked with `pointee` beforehand.
+fn add_pointer_offset<'c, 'a>(
+    builder: &FunctionBuilder<'c, 'a>,
+    pointer: TypedValue<'c, 'a>,
+    index: TypedValue<'c, 'a>,
+    negate: bool,
+    location: Location<'c>,
+) -> TypedValue<'c, 'a> {
+    let context = builder.context;
+    let CType::Pointer { pointee, .. } = &pointer.r#type else {
+        unreachable!("{} is not a pointer", pointer.r#type)
+    };
+    let mut index = TypedValue {
+        value: add_conversion(builder, index, &CType::LONG, location),
+        r#type: CType::LONG,
+    };
+    // A variable length array is moved over in bytes
+    let element_type = if pointee.is_variably_sized() {
+        let size = TypedValue {
+            value: builder.add_size(pointee, location),
+            r#type: CType::LONG,
+        };
+        index = add_arithmetic(builder, "arith.muli", index, size, location);
+        IntegerType::new(context, 8).into()
+    } else {
+        pointee.memory_type(context)
+    };
+    if negate {
+        let zero = TypedValue {
+            value: builder
+                .append_operation(arith::constant(
+                    context,
+                    IntegerAttribute::new(CType::LONG.mlir_type(context), 0).into(),
+                    location,
+                ))
+                .result(0)
+                .unwrap()
+                .into(),
+            r#type: CType::LONG,
+        };
+        index = add_arithmetic(builder, "arith.subi", zero, index, location);
+    }
+    TypedValue {
+        value: builder
+            .append_operation(llvm::get_element_ptr_dynamic(
+                context,
+                pointer.value,
+                &[index.value],
+                element_type,
+                llvm::r#type::pointer(context, 0),
+                location,
+            ))
+            .result(0)
+            .unwrap()
+            .into(),
+        r#type: pointer.r#type,
+    }
+}
+
+// 6.5.6 `+` and `-`, which also take a pointer and an integer, or two pointers to subtract
+fn add_additive<'c, 'a>(
+    builder: &FunctionBuilder<'c, 'a>,
+    operation_name: &str,
+    lhs: TypedValue<'c, 'a>,
+    rhs: TypedValue<'c, 'a>,
+    location: &lexer::Location,
+) -> Result<TypedValue<'c, 'a>, CompileError> {
+    let context = builder.context;
+    let subtract = operation_name == "arith.subi";
+    match (&lhs.r#type, &rhs.r#type) {
+        (lhs_type, rhs_type) if lhs_type.is_arithmetic() && rhs_type.is_arithmetic() => {
+            Ok(add_arithmetic(
+                builder,
+                operation_name,
+                lhs,
+                rhs,
+                location.mlir_location(context),
+            ))
+        }
+        (CType::Pointer { .. }, CType::Integer { .. }) => {
+            pointee(&lhs.r#type, location)?;
+            let location = location.mlir_location(context);
+            Ok(add_pointer_offset(builder, lhs, rhs, subtract, location))
+        }
+        (CType::Integer { .. }, CType::Pointer { .. }) if !subtract => {
+            pointee(&rhs.r#type, location)?;
+            let location = location.mlir_location(context);
+            Ok(add_pointer_offset(builder, rhs, lhs, false, location))
+        }
+        // The difference is in elements and has type ptrdiff_t
+        (
+            CType::Pointer {
+                pointee: lhs_pointee,
+                ..
+            },
+            CType::Pointer {
+                pointee: rhs_pointee,
+                ..
+            },
+        ) if subtract && lhs_pointee.is_compatible(rhs_pointee) => {
+            let pointee = pointee(&lhs.r#type, location)?;
+            let location = location.mlir_location(context);
+            let size = TypedValue {
+                value: builder.add_size(pointee, location),
+                r#type: CType::LONG,
+            };
+            let [lhs, rhs] = [lhs, rhs].map(|pointer| TypedValue {
+                value: add_address(builder, pointer.value, location),
+                r#type: CType::LONG,
+            });
+            let difference = add_arithmetic(builder, "arith.subi", lhs, rhs, location);
+            Ok(add_arithmetic(
+                builder,
+                "arith.divsi",
+                difference,
+                size,
+                location,
+            ))
+        }
+        _ => Err(invalid_operands(&lhs.r#type, &rhs.r#type, location)),
+    }
+}
+
+pub(super) fn is_null_pointer_constant(constant: Result<IntegerConstant, ConstantError>) -> bool {
+    // 6.3.2.3 An integer constant expression with the value 0
+    matches!(constant, Ok(IntegerConstant { value: 0, .. }))
+}
+
+// 6.5.8, 6.5.9 Pointers are compared by address. Equality operators also accept a pointer to
+// void and a null pointer constant, as told by `nulls`, on either side.
+fn add_relational<'c, 'a>(
+    builder: &FunctionBuilder<'c, 'a>,
+    predicate: CmpiPredicate,
+    lhs: TypedValue<'c, 'a>,
+    rhs: TypedValue<'c, 'a>,
+    nulls: (bool, bool),
+    location: &lexer::Location,
+) -> Result<TypedValue<'c, 'a>, CompileError> {
+    let equality = matches!(predicate, CmpiPredicate::Eq | CmpiPredicate::Ne);
+    let comparable = match (&lhs.r#type, &rhs.r#type) {
+        // 6.5.8 Relational operators take real types
+        (lhs_type, rhs_type) if lhs_type.is_arithmetic() && rhs_type.is_arithmetic() => {
+            equality || !lhs_type.is_complex() && !rhs_type.is_complex()
+        }
+        (
+            CType::Pointer {
+                pointee: lhs_pointee,
+                ..
+            },
+            CType::Pointer {
+                pointee: rhs_pointee,
+                ..
+            },
+        ) => {
+            lhs_pointee.is_compatible(rhs_pointee)
+                || equality && (lhs_pointee.is_void() || rhs_pointee.is_void())
+        }
+        (CType::Pointer { .. }, CType::Integer { .. }) => equality && nulls.1,
+        (CType::Integer { .. }, CType::Pointer { .. }) => equality && nulls.0,
+        _ => false,
+    };
+    if !comparable {
+        return Err(invalid_operands(&lhs.r#type, &rhs.r#type, location));
+    }
+    let location = location.mlir_location(builder.context);
+    let [lhs, rhs] = [lhs, rhs].map(|value| match value.r#type {
+        CType::Pointer { .. } => TypedValue {
+            value: add_address(builder, value.value, location),
+            r#type: CType::UNSIGNED_LONG,
+        },
+        _ => value,
+    });
+    Ok(add_comparison(builder, predicate, lhs, rhs, location))
+}
+
+// 6.5.16.1 Converts `expression` to `r#type` as if by assignment
+pub fn add_assignment<'c, 'a>(
+    builder: &mut FunctionBuilder<'c, 'a>,
+    expression: &AssignmentExpression,
+    r#type: &CType,
+    location: &lexer::Location,
+) -> Result<Value<'c, 'a>, CompileError> {
+    let context = builder.context;
+    if r#type.is_pointer() && is_null_pointer_constant(expression.evaluate()) {
+        return Ok(builder
+            .append_operation(llvm::zero(
+                r#type.mlir_type(context),
+                location.mlir_location(context),
+            ))
+            .result(0)
+            .unwrap()
+            .into());
+    }
+    let value = expression.add_value(builder)?;
+    // 6.5.16.1 A structure or union is copied as a whole
+    if r#type.is_record() && value.r#type == *r#type {
+        return Ok(value.value);
+    }
+    if !(value.r#type.is_arithmetic() && r#type.is_arithmetic()
+        || value.r#type.is_pointer() && *r#type == CType::BOOL
+        || value.r#type.is_pointer_convertible(r#type))
+    {
+        return Err(CompileError::new(
+            location.clone(),
+            format!(
+                "incompatible types assigning to '{}' from '{}'",
+                r#type, value.r#type
+            ),
+        ));
+    }
+    Ok(add_conversion(
+        builder,
+        value,
+        r#type,
+        location.mlir_location(context),
+    ))
+}
+
+// 6.5.2.4, 6.5.3.1 `operation_name` adds or subtracts 1 to the operand.
+// Returns the old and the new value.
+fn add_increment<'c, 'a, T: AddLvalue>(
+    builder: &mut FunctionBuilder<'c, 'a>,
+    operation_name: &str,
+    expression: &T,
+    location: &lexer::Location,
+) -> Result<(TypedValue<'c, 'a>, TypedValue<'c, 'a>), CompileError> {
+    let context = builder.context;
+    let lvalue = expression.add_lvalue(builder)?;
+    check_modifiable(&lvalue, location)?;
+    let r#type = lvalue.r#type().clone();
+    if r#type.is_pointer() {
+        pointee(&r#type, location)?;
+    }
+    let location = location.mlir_location(context);
+    let old = builder.load(&lvalue, location);
+    let one = TypedValue {
+        value: builder
+            .append_operation(arith::constant(
+                context,
+                IntegerAttribute::new(CType::INT.mlir_type(context), 1).into(),
+                location,
+            ))
+            .result(0)
+            .unwrap()
+            .into(),
+        r#type: CType::INT,
+    };
+    let new = if r#type.is_pointer() {
+        add_pointer_offset(
+            builder,
+            old.clone(),
+            one,
+            operation_name == "arith.subi",
+            location,
+        )
+    } else {
+        let new = add_arithmetic(builder, operation_name, old.clone(), one, location);
+        TypedValue {
+            value: add_conversion(builder, new, &r#type, location),
+            r#type,
+        }
+    };
+    builder.store(&lvalue, new.value, location);
+    if let Lvalue::BitField { .. } = lvalue {
+        return Ok((old, builder.load(&lvalue, location)));
+    }
+    Ok((old, new))
+}
+
+// How a function is called 6.5.2.2
+enum Callee<'c, 'a> {
+    // By the name of a function
+    Symbol(EcoString),
+    // Through a pointer to a function, which is any other expression
+    Pointer(Value<'c, 'a>),
+}
+
+// 6.5.2.2 Returns `None` for a function returning void
+pub fn add_call<'c, 'a>(
+    builder: &mut FunctionBuilder<'c, 'a>,
+    function: &PostfixExpression,
+    arguments: &[AssignmentExpression],
+    location: &lexer::Location,
+) -> Result<Option<TypedValue<'c, 'a>>, CompileError> {
+    let context = builder.context;
+    let (callee, function_type) = match function.as_identifier() {
+        Some(identifier) if builder.lookup(identifier).is_none() => {
+            if let Some(value) = add_builtin_call(builder, identifier, arguments, location)? {
+                return Ok(Some(value));
+            }
+            let Some(symbol) = builder.file_scope().lookup(identifier) else {
+                return Err(CompileError::new(
+                    location.clone(),
+                    format!("call to undeclared function '{}'", identifier),
+                ));
+            };
+            match &symbol.r#type {
+                r#type @ CType::Function { .. } => {
+                    (Callee::Symbol(identifier.clone()), r#type.clone())
+                }
+                _ => callee_pointer(builder, function, location)?,
+            }
+        }
+        _ => callee_pointer(builder, function, location)?,
+    };
+    let CType::Function {
+        return_type,
+        parameters,
+        variadic,
+    } = &function_type
+    else {
+        unreachable!()
+    };
+    let variadic = *variadic;
+    if arguments.len() < parameters.len() || arguments.len() > parameters.len() && !variadic {
+        return Err(CompileError::new(
+            location.clone(),
+            format!(
+                "too {} arguments to function call, expected {}{}, have {}",
+                if arguments.len() < parameters.len() {
+                    "few"
+                } else {
+                    "many"
+                },
+                if variadic { "at least " } else { "" },
+                parameters.len(),
+                arguments.len()
+            ),
+        ));
+    }
+
+    let mut values = Vec::new();
+    if let Callee::Pointer(pointer) = callee {
+        values.push(pointer);
+    }
+    for (i, argument) in arguments.iter().enumerate() {
+        let value = match parameters.get(i) {
+            Some(parameter @ CType::Array { .. }) => {
+                memref_argument(builder, argument, parameter, location)?
+            }
+            // Passed by reference, since it is an array
+            Some(CType::VaList) => va_list_pointer(builder, argument, location)?,
+            // Converted as if by assignment
+            Some(parameter) => add_assignment(builder, argument, parameter, location)?,
+            None => {
+                let v0 = argument.add_value(builder)?;
+                let r#type = v0.r#type.default_argument_promotion();
+                add_conversion(builder, v0, &r#type, location.mlir_location(context))
+            }
+        };
+        values.push(value);
+    }
+    let location = location.mlir_location(context);
+    let result_types = if return_type.is_void() {
+        vec![]
+    } else {
+        vec![return_type.mlir_type(context)]
+    };
+    let operation = match callee {
+        Callee::Symbol(identifier) if !builder.file_scope().is_llvm_function(&identifier) => {
+            builder.append_operation(func::call(
+                context,
+                FlatSymbolRefAttribute::new(context, &identifier),
+                &values,
+                &result_types,
+                location,
+            ))
+        }
+        // An `llvm.func`, or a pointer to a function passed before the arguments
+        callee => {
+            let mut attributes = Vec::new();
+            if let Callee::Symbol(identifier) = callee {
+                attributes.push((
+                    Identifier::new(context, "callee"),
+                    FlatSymbolRefAttribute::new(context, &identifier).into(),
+                ));
+            }
+            if variadic {
+                attributes.push((
+                    Identifier::new(context, "var_callee_type"),
+                    TypeAttribute::new(function_type.llvm_function_type(context)).into(),
+                ));
+            }
+            builder.append_operation(
+                OperationBuilder::new("llvm.call", location)
+                    .add_operands(&values)
+                    .add_attributes(&attributes)
+                    .add_results(&result_types)
+                    .build()
+                    .unwrap(),
+            )
+        }
+    };
+    Ok((!return_type.is_void()).then(|| TypedValue {
+        value: operation.result(0).unwrap().into(),
+        r#type: return_type.as_ref().clone(),
+    }))
+}
+
+// A callee that is not called by name must be a pointer to a function. Returns it with the type
+// of the function.
+fn callee_pointer<'c, 'a>(
+    builder: &mut FunctionBuilder<'c, 'a>,
+    function: &PostfixExpression,
+    location: &lexer::Location,
+) -> Result<(Callee<'c, 'a>, CType), CompileError> {
+    let pointer = function.add_value(builder)?;
+    match pointer.r#type {
+        CType::Pointer { pointee, .. } if pointee.is_function() => {
+            Ok((Callee::Pointer(pointer.value), *pointee))
+        }
+        r#type => Err(CompileError::new(
+            location.clone(),
+            format!(
+                "called object type '{}' is not a function or function pointer",
+                r#type
+            ),
+        )),
+    }
+}
+
+// A `memref` parameter takes a whole array kept as a `memref` of the same type
+// TODO Qualifiers of the elements are not part of the parameter type
+fn memref_argument<'c, 'a>(
+    builder: &mut FunctionBuilder<'c, 'a>,
+    argument: &AssignmentExpression,
+    parameter: &CType,
+    location: &lexer::Location,
+) -> Result<Value<'c, 'a>, CompileError> {
+    match argument.add_lvalue(builder) {
+        Ok(Lvalue::Memref {
+            memref,
+            memref_type,
+            indices,
+            ..
+        }) if indices.is_empty() && memref_type == *parameter => Ok(memref),
+        _ => Err(CompileError::new(
+            location.clone(),
+            format!(
+                "parameter of type '{}' takes an array of the same type kept as a memref",
+                parameter
+            ),
+        )),
+    }
+}
+
+fn va_list_pointer<'c, 'a>(
+    builder: &mut FunctionBuilder<'c, 'a>,
+    va_list: &AssignmentExpression,
+    location: &lexer::Location,
+) -> Result<Value<'c, 'a>, CompileError> {
+    match va_list.add_lvalue(builder)? {
+        Lvalue::Memory {
+            pointer,
+            r#type: CType::VaList,
+            ..
+        } => Ok(pointer),
+        _ => Err(CompileError::new(
+            location.clone(),
+            "expected a 'va_list' object".to_string(),
+        )),
+    }
+}
+
+// 7.16.1 Returns `None` except for `va_arg`
+pub fn add_va_builtin<'c, 'a>(
+    builder: &mut FunctionBuilder<'c, 'a>,
+    va_builtin: &VaBuiltin,
+) -> Result<Option<TypedValue<'c, 'a>>, CompileError> {
+    let context = builder.context;
+    let location = va_builtin.location().mlir_location(context);
+    let operation = match va_builtin {
+        VaBuiltin::Start {
+            location: va_location,
+            va_list,
+            ..
+        } => {
+            if !builder.is_variadic() {
+                return Err(CompileError::new(
+                    va_location.clone(),
+                    "'va_start' used in function with fixed parameters".to_string(),
+                ));
+            }
+            let pointer = va_list_pointer(builder, va_list, va_location)?;
+            OperationBuilder::new("llvm.intr.vastart", location).add_operands(&[pointer])
+        }
+        VaBuiltin::Arg {
+            location: va_location,
+            va_list,
+            r#type,
+        } => {
+            let type_location = r#type.location.clone();
+            let r#type = r#type.r#type();
+            if !r#type.is_scalar() {
+                return Err(CompileError::new(
+                    type_location,
+                    "second argument to 'va_arg' must be a scalar type".to_string(),
+                ));
+            }
+            let pointer = va_list_pointer(builder, va_list, va_location)?;
+            let va_arg = |r#type: &CType| -> Value<'c, 'a> {
+                builder
+                    .append_operation(
+                        OperationBuilder::new("llvm.va_arg", location)
+                            .add_operands(&[pointer])
+                            .add_results(&[r#type.mlir_type(context)])
+                            .build()
+                            .unwrap(),
+                    )
+                    .result(0)
+                    .unwrap()
+                    .into()
+            };
+            // `llvm.va_arg` does not take complex types. A complex argument is lowered to a
+            // structure of its parts, which LLVM passes as two arguments.
+            let value = match r#type {
+                CType::Complex(kind) => {
+                    let real = va_arg(&CType::Floating(kind));
+                    let imaginary = va_arg(&CType::Floating(kind));
+                    add_complex(builder, real, imaginary, &r#type, location)
+                }
+                _ => va_arg(&r#type),
+            };
+            return Ok(Some(TypedValue { value, r#type }));
+        }
+        VaBuiltin::End {
+            location: va_location,
+            va_list,
+        } => {
+            let pointer = va_list_pointer(builder, va_list, va_location)?;
+            OperationBuilder::new("llvm.intr.vaend", location).add_operands(&[pointer])
+        }
+        VaBuiltin::Copy {
+            location: va_location,
+            destination,
+            source,
+        } => {
+            let destination = va_list_pointer(builder, destination, va_location)?;
+            let source = va_list_pointer(builder, source, va_location)?;
+            OperationBuilder::new("llvm.intr.vacopy", location).add_operands(&[destination, source])
+        }
+    };
+    builder.append_operation(operation.build().unwrap());
+    Ok(None)
+}
+
+/// 6.8.3 Evaluates an expression only for its side effects, so it may have type void.
+pub fn add_discarded_value<'c, 'a>(
+    builder: &mut FunctionBuilder<'c, 'a>,
+    expression: &AssignmentExpression,
+) -> Result<(), CompileError> {
+    match expression.as_cast_expression() {
+        Some(cast_expression) => add_discarded_cast(builder, cast_expression),
+        None => {
+            expression.add_value(builder)?;
+            Ok(())
+        }
+    }
+}
+
+// 6.5.4 The operand of a cast to void is discarded as well
+fn add_discarded_cast<'c, 'a>(
+    builder: &mut FunctionBuilder<'c, 'a>,
+    expression: &CastExpression,
+) -> Result<(), CompileError> {
+    match expression {
+        CastExpression::Cast {
+            type_name,
+            expression,
+            ..
+        } if type_name.r#type().is_void() => {
+            add_discarded_cast(builder, expression)?;
+        }
+        CastExpression::UnaryExpression(UnaryExpression::PostfixExpression(
+            PostfixExpression::FunctionCall {
+                function,
+                arguments,
+                location,
+            },
+        )) => {
+            add_call(builder, function, arguments, location)?;
+        }
+        CastExpression::UnaryExpression(UnaryExpression::PostfixExpression(
+            PostfixExpression::PrimaryExpression(PrimaryExpression::Parenthesized(expression)),
+        )) => match expression.as_ref() {
+            Expression::AssignmentExpression(expression) => {
+                add_discarded_value(builder, expression)?
+            }
+        },
+        CastExpression::UnaryExpression(UnaryExpression::PostfixExpression(
+            PostfixExpression::PrimaryExpression(PrimaryExpression::VaBuiltin(va_builtin)),
+        )) => {
+            add_va_builtin(builder, va_builtin)?;
+        }
+        _ => {
+            expression.add_value(builder)?;
+        }
+    }
+    Ok(())
+}
+
+// 6.3.2.1 Reads the value of the object
+fn add_lvalue_conversion<'c, 'a>(
+    builder: &FunctionBuilder<'c, 'a>,
+    lvalue: Lvalue<'c, 'a>,
+    location: &lexer::Location,
+) -> Result<TypedValue<'c, 'a>, CompileError> {
+    // A function designator is converted to a pointer to the function
+    if let r#type @ CType::Function { .. } = lvalue.r#type() {
+        return Ok(TypedValue {
+            value: builder.address(&lvalue, location.mlir_location(builder.context)),
+            r#type: CType::Pointer {
+                pointee: Box::new(r#type.clone()),
+                r#const: false,
+            },
+        });
+    }
+    if *lvalue.r#type() == CType::VaList {
+        return Err(CompileError::new(
+            location.clone(),
+            "'va_list' object can only be used with the va_ builtins".to_string(),
+        ));
+    }
+    if !lvalue.r#type().is_complete() && !lvalue.r#type().is_array() {
+        return Err(CompileError::new(
+            location.clone(),
+            format!(
+                "incomplete type '{}' where a complete type is required",
+                lvalue.r#type()
+            ),
+        ));
+    }
+    let location = location.mlir_location(builder.context);
+    // An array is converted to a pointer to its first element, which has the same address
+    if let CType::Array { element, .. } | CType::VariableArray { element, .. } = lvalue.r#type() {
+        return Ok(TypedValue {
+            value: builder.address(&lvalue, location),
+            r#type: CType::Pointer {
+                pointee: element.clone(),
+                r#const: lvalue.is_const(),
+            },
+        });
+    }
+    Ok(builder.load(&lvalue, location))
+}
+
+// 6.5.3.4 The size of a complete object type as a size_t, which is computed at run time for a
+// variable length array
+fn add_sizeof<'c, 'a>(
+    builder: &FunctionBuilder<'c, 'a>,
+    r#type: &CType,
+    location: &lexer::Location,
+) -> Result<TypedValue<'c, 'a>, CompileError> {
+    let context = builder.context;
+    let size = match r#type {
+        CType::Void => {
+            return Err(CompileError::new(
+                location.clone(),
+                "invalid application of 'sizeof' to an incomplete type 'void'".to_string(),
+            ))
+        }
+        CType::Function { .. } => {
+            return Err(CompileError::new(
+                location.clone(),
+                "invalid application of 'sizeof' to a function type".to_string(),
+            ))
+        }
+        r#type if !r#type.is_complete() => {
+            return Err(CompileError::new(
+                location.clone(),
+                format!(
+                    "invalid application of 'sizeof' to an incomplete type '{}'",
+                    r#type
+                ),
+            ))
+        }
+        r#type if r#type.is_variably_sized() => {
+            return Ok(TypedValue {
+                value: builder.add_size(r#type, location.mlir_location(context)),
+                r#type: CType::UNSIGNED_LONG,
+            })
+        }
+        r#type => r#type.size(),
+    };
+    Ok(TypedValue {
+        value: builder
+            .append_operation(arith::constant(
+                context,
+                IntegerAttribute::new(CType::UNSIGNED_LONG.mlir_type(context), size as i64).into(),
+                location.mlir_location(context),
+            ))
+            .result(0)
+            .unwrap()
+            .into(),
+        r#type: CType::UNSIGNED_LONG,
+    })
+}
+
+// 6.5.3.4 The operand of `sizeof` is not evaluated, so it is lowered into a region that is
+// dropped. An lvalue is not converted, so that an array keeps its type.
+pub(super) fn expression_type(
+    builder: &FunctionBuilder,
+    expression: &UnaryExpression,
+    location: &lexer::Location,
+) -> Result<CType, CompileError> {
+    let mut r#type = CType::Void;
+    builder.add_region(&[], location.mlir_location(builder.context), |builder| {
+        r#type = match expression.add_lvalue(builder) {
+            Ok(lvalue) => lvalue.r#type().clone(),
+            Err(_) => expression.add_value(builder)?.r#type,
+        };
+        Ok(())
+    })?;
+    Ok(r#type)
+}
+
+// The type of the value of an expression that is not evaluated, after lvalue conversion
+pub(super) fn value_type(
+    builder: &FunctionBuilder,
+    expression: &AssignmentExpression,
+    location: &lexer::Location,
+) -> Result<CType, CompileError> {
+    let mut r#type = CType::Void;
+    builder.add_region(&[], location.mlir_location(builder.context), |builder| {
+        r#type = expression.add_value(builder)?.r#type;
+        Ok(())
+    })?;
+    Ok(r#type)
+}
+
+// 6.5.1.1 The expression of the association for the type of the controlling expression, which is
+// not evaluated. The associations must have distinct complete object types.
+fn select_association<'s>(
+    builder: &FunctionBuilder,
+    generic_selection: &'s GenericSelection,
+) -> Result<&'s AssignmentExpression, CompileError> {
+    let associations = &generic_selection.associations;
+    for (i, association) in associations.iter().enumerate() {
+        let Some(type_name) = &association.type_name else {
+            if associations[..i]
+                .iter()
+                .any(|previous| previous.type_name.is_none())
+            {
+                return Err(CompileError::new(
+                    association.location.clone(),
+                    "duplicate default generic association".to_string(),
+                ));
+            }
+            continue;
+        };
+        let r#type = type_name.r#type();
+        if !r#type.is_complete() {
+            return Err(CompileError::new(
+                association.location.clone(),
+                format!("type '{}' in generic association incomplete", r#type),
+            ));
+        }
+        if associations[..i].iter().any(|previous| {
+            previous
+                .type_name
+                .as_ref()
+                .is_some_and(|previous| previous.r#type() == r#type)
+        }) {
+            return Err(CompileError::new(
+                association.location.clone(),
+                format!(
+                    "type '{}' in generic association compatible with previously specified type",
+                    r#type
+                ),
+            ));
+        }
+    }
+    let location = &generic_selection.location;
+    let r#type = value_type(builder, &generic_selection.controlling_expression, location)?;
+    generic_selection.select(&r#type).ok_or_else(|| {
+        CompileError::new(
+            location.clone(),
+            format!(
+                "controlling expression type '{}' not compatible with any generic association type",
+                r#type
+            ),
+        )
+    })
+}
+
+// 6.5.3.2 The lvalue designating the pointee
+fn indirection<'c, 'a>(
+    pointer: TypedValue<'c, 'a>,
+    location: &lexer::Location,
+) -> Result<Lvalue<'c, 'a>, CompileError> {
+    match pointer.r#type {
+        CType::Pointer { pointee, .. } if pointee.is_void() => Err(CompileError::new(
+            location.clone(),
+            "indirection through a pointer to void".to_string(),
+        )),
+        CType::Pointer { pointee, r#const } => Ok(Lvalue::Memory {
+            pointer: pointer.value,
+            r#type: *pointee,
+            r#const,
+            alignment: None,
+        }),
+        r#type => Err(CompileError::new(
+            location.clone(),
+            format!(
+                "indirection requires pointer operand ('{}' invalid)",
+                r#type
+            ),
+        )),
+    }
+}
+
+impl AddValue for Expression {
+    fn add_value<'c, 'a>(
+        &self,
+        builder: &mut FunctionBuilder<'c, 'a>,
+    ) -> Result<TypedValue<'c, 'a>, CompileError> {
+        match self {
+            Expression::AssignmentExpression(assignment_expression) => {
+                assignment_expression.add_value(builder)
+            }
+        }
+    }
+}
+
+impl AddValue for PrimaryExpression {
+    fn add_value<'c, 'a>(
+        &self,
+        builder: &mut FunctionBuilder<'c, 'a>,
+    ) -> Result<TypedValue<'c, 'a>, CompileError> {
+        let context = builder.context;
+        match self {
+            PrimaryExpression::Identifier {
+                identifier,
+                location,
+            } => {
+                if builder.lookup(identifier).is_none()
+                    && builder.file_scope().lookup(identifier).is_none()
+                {
+                    if let Some(value) = add_builtin_constant(builder, identifier, location) {
+                        return Ok(value);
+                    }
+                }
+                let lvalue = self.add_lvalue(builder)?;
+                add_lvalue_conversion(builder, lvalue, location)
+            }
+            PrimaryExpression::StringLiteral { location, .. } => {
+                let lvalue = self.add_lvalue(builder)?;
+                add_lvalue_conversion(builder, lvalue, location)
+            }
+            PrimaryExpression::Parenthesized(expression) => expression.add_value(builder),
+            PrimaryExpression::GenericSelection(generic_selection) => {
+                select_association(builder, generic_selection)?.add_value(builder)
+            }
+            PrimaryExpression::VaBuiltin(va_builtin) => add_va_builtin(builder, va_builtin)?
+                .ok_or_else(|| void_value(va_builtin.location())),
+            PrimaryExpression::Constant {
+                value: Constant::Integer { value, r#type },
+                location,
+            } => Ok(TypedValue {
+                value: builder
+                    .append_operation(arith::constant(
+                        context,
+                        IntegerAttribute::new(r#type.mlir_type(context), *value as i64).into(),
+                        location.mlir_location(context),
+                    ))
+                    .result(0)
+                    .unwrap()
+                    .into(),
+                r#type: r#type.clone(),
+            }),
+            PrimaryExpression::Constant {
+                value: Constant::Floating { value, r#type },
+                location,
+            } => Ok(TypedValue {
+                value: builder
+                    .append_operation(arith::constant(
+                        context,
+                        FloatAttribute::new(context, r#type.mlir_type(context), *value).into(),
+                        location.mlir_location(context),
+                    ))
+                    .result(0)
+                    .unwrap()
+                    .into(),
+                r#type: r#type.clone(),
+            }),
+        }
+    }
+}
+
+impl AddValue for PostfixExpression {
+    fn add_value<'c, 'a>(
+        &self,
+        builder: &mut FunctionBuilder<'c, 'a>,
+    ) -> Result<TypedValue<'c, 'a>, CompileError> {
+        match self {
+            PostfixExpression::PrimaryExpression(primary_expression) => {
+                primary_expression.add_value(builder)
+            }
+            PostfixExpression::FunctionCall {
+                function,
+                arguments,
+                location,
+            } => add_call(builder, function, arguments, location)?
+                .ok_or_else(|| void_value(location)),
+            PostfixExpression::Increment {
+                expression,
+                location,
+            } => Ok(add_increment(builder, "arith.addi", expression.as_ref(), location)?.0),
+            PostfixExpression::Decrement {
+                expression,
+                location,
+            } => Ok(add_increment(builder, "arith.subi", expression.as_ref(), location)?.0),
+            PostfixExpression::Subscript { location, .. }
+            | PostfixExpression::Member { location, .. }
+            | PostfixExpression::Arrow { location, .. } => {
+                let lvalue = self.add_lvalue(builder)?;
+                add_lvalue_conversion(builder, lvalue, location)
+            }
+        }
+    }
+}
+
+impl AddValue for UnaryExpression {
+    fn add_value<'c, 'a>(
+        &self,
+        builder: &mut FunctionBuilder<'c, 'a>,
+    ) -> Result<TypedValue<'c, 'a>, CompileError> {
+        match self {
+            UnaryExpression::PostfixExpression(postfix_expression) => {
+                postfix_expression.add_value(builder)
+            }
+            UnaryExpression::Increment {
+                expression,
+                location,
+            } => Ok(add_increment(builder, "arith.addi", expression.as_ref(), location)?.1),
+            UnaryExpression::Decrement {
+                expression,
+                location,
+            } => Ok(add_increment(builder, "arith.subi", expression.as_ref(), location)?.1),
+            UnaryExpression::Address {
+                expression,
+                location,
+            } => match expression.add_lvalue(builder)? {
+                // `scf::is_structured` keeps such functions in memory
+                Lvalue::Variable { identifier, .. } => Err(CompileError::new(
+                    location.clone(),
+                    format!(
+                        "cannot take the address of '{}' kept as a value",
+                        identifier
+                    ),
+                )),
+                Lvalue::BitField { .. } => Err(CompileError::new(
+                    location.clone(),
+                    "address of bit-field requested".to_string(),
+                )),
+                lvalue => Ok(TypedValue {
+                    value: builder.address(&lvalue, location.mlir_location(builder.context)),
+                    r#type: CType::Pointer {
+                        pointee: Box::new(lvalue.r#type().clone()),
+                        r#const: lvalue.is_const(),
+                    },
+                }),
+            },
+            UnaryExpression::Indirection { location, .. } => {
+                let lvalue = self.add_lvalue(builder)?;
+                add_lvalue_conversion(builder, lvalue, location)
+            }
+            UnaryExpression::Plus {
+                expression,
+                location,
+            } => {
+                let operand = expression.add_value(builder)?;
+                check_unary(&operand, operand.r#type.is_arithmetic(), location)?;
+                Ok(add_promotion(builder, operand, location))
+            }
+            UnaryExpression::Minus {
+                expression,
+                location,
+            } => {
+                let operand = expression.add_value(builder)?;
+                check_unary(&operand, operand.r#type.is_arithmetic(), location)?;
+                let operand = add_promotion(builder, operand, location);
+                Ok(add_negation(builder, operand, location))
+            }
+            UnaryExpression::BitwiseNot {
+                expression,
+                location,
+            } => {
+                let operand = expression.add_value(builder)?;
+                check_unary(&operand, operand.r#type.is_integer(), location)?;
+                let operand = add_promotion(builder, operand, location);
+                let location = location.mlir_location(builder.context);
+                // `~x` is `x ^ -1`
+                let ones = builder.add_constant(operand.r#type.width() as u64, u64::MAX, location);
+                Ok(TypedValue {
+                    value: add_operation(
+                        builder,
+                        OperationBuilder::new("arith.xori", location)
+                            .add_operands(&[operand.value, ones]),
+                    ),
+                    r#type: operand.r#type,
+                })
+            }
+            // 6.5.3.3 `!x` is `0 == x`, which is an int
+            UnaryExpression::LogicalNot {
+                expression,
+                location,
+            } => {
+                let operand = expression.add_value(builder)?;
+                check_unary(&operand, operand.r#type.is_scalar(), location)?;
+                let context = builder.context;
+                let location = location.mlir_location(context);
+                let condition = add_condition(builder, operand, location);
+                let truth = builder.add_constant(1, 1, location);
+                let condition = add_operation(
+                    builder,
+                    OperationBuilder::new("arith.xori", location).add_operands(&[condition, truth]),
+                );
+                Ok(TypedValue {
+                    value: builder
+                        .append_operation(arith::extui(
+                            condition,
+                            CType::INT.mlir_type(context),
+                            location,
+                        ))
+                        .result(0)
+                        .unwrap()
+                        .into(),
+                    r#type: CType::INT,
+                })
+            }
+            UnaryExpression::SizeofExpression {
+                expression,
+                location,
+            } => {
+                let r#type = expression_type(builder, expression, location)?;
+                // 6.5.3.4 The operand is evaluated if it is a variable length array
+                if r#type.is_variably_sized() {
+                    expression.add_lvalue(builder)?;
+                }
+                add_sizeof(builder, &r#type, location)
+            }
+            UnaryExpression::SizeofType {
+                type_name,
+                location,
+            } => add_sizeof(builder, &type_name.r#type(), location),
+        }
+    }
+}
+
+impl AddValue for CastExpression {
+    fn add_value<'c, 'a>(
+        &self,
+        builder: &mut FunctionBuilder<'c, 'a>,
+    ) -> Result<TypedValue<'c, 'a>, CompileError> {
+        let (type_name, expression, location) = match self {
+            CastExpression::UnaryExpression(unary_expression) => {
+                return unary_expression.add_value(builder)
+            }
+            CastExpression::Cast {
+                type_name,
+                expression,
+                location,
+            } => (type_name, expression, location),
+        };
+        let r#type = type_name.r#type();
+        if r#type.is_void() {
+            return Err(void_value(location));
+        }
+        let operand = expression.add_value(builder)?;
+        let error = |message: String| Err(CompileError::new(location.clone(), message));
+        // 6.5.4 Both are scalars, and pointers are converted only from and to integers
+        if !r#type.is_scalar() {
+            return error(format!(
+                "used type '{}' where arithmetic or pointer type is required",
+                r#type
+            ));
+        }
+        if !operand.r#type.is_scalar() {
+            return error(format!(
+                "operand of type '{}' where arithmetic or pointer type is required",
+                operand.r#type
+            ));
+        }
+        if r#type.is_pointer() && !operand.r#type.is_pointer() && !operand.r#type.is_integer() {
+            return error(format!(
+                "operand of type '{}' cannot be cast to a pointer type",
+                operand.r#type
+            ));
+        }
+        if operand.r#type.is_pointer() && !r#type.is_pointer() && !r#type.is_integer() {
+            return error(format!("pointer cannot be cast to type '{}'", r#type));
+        }
+        Ok(TypedValue {
+            value: add_conversion(
+                builder,
+                operand,
+                &r#type,
+                location.mlir_location(builder.context),
+            ),
+            r#type,
+        })
+    }
+}
+
+impl AddValue for MultiplicativeExpression {
+    fn add_value<'c, 'a>(
+        &self,
+        builder: &mut FunctionBuilder<'c, 'a>,
+    ) -> Result<TypedValue<'c, 'a>, CompileError> {
+        let context = builder.context;
+        match self {
+            MultiplicativeExpression::CastExpression(cast_expression) => {
+                cast_expression.add_value(builder)
+            }
+            MultiplicativeExpression::Mul { lhs, rhs, location } => {
+                let v0 = lhs.add_value(builder)?;
+                let v1 = rhs.add_value(builder)?;
+                check_arithmetic(&v0, &v1, location)?;
+                Ok(add_arithmetic(
+                    builder,
+                    "arith.muli",
+                    v0,
+                    v1,
+                    location.mlir_location(context),
+                ))
+            }
+            MultiplicativeExpression::Div { lhs, rhs, location } => {
+                let v0 = lhs.add_value(builder)?;
+                let v1 = rhs.add_value(builder)?;
+                check_arithmetic(&v0, &v1, location)?;
+                Ok(add_arithmetic(
+                    builder,
+                    "arith.divsi",
+                    v0,
+                    v1,
+                    location.mlir_location(context),
+                ))
+            }
+            MultiplicativeExpression::Rem { lhs, rhs, location } => {
+                let v0 = lhs.add_value(builder)?;
+                let v1 = rhs.add_value(builder)?;
+                check_integers(&v0, &v1, location)?;
+                Ok(add_arithmetic(
+                    builder,
+                    "arith.remsi",
+                    v0,
+                    v1,
+                    location.mlir_location(context),
+                ))
+            }
+        }
+    }
+}
+
+impl AddValue for AdditiveExpression {
+    fn add_value<'c, 'a>(
+        &self,
+        builder: &mut FunctionBuilder<'c, 'a>,
+    ) -> Result<TypedValue<'c, 'a>, CompileError> {
+        match self {
+            AdditiveExpression::PrimaryExpression(primary_expression) => {
+                primary_expression.add_value(builder)
+            }
+            AdditiveExpression::Add { lhs, rhs, location } => {
+                let v0 = lhs.add_value(builder)?;
+                let v1 = rhs.add_value(builder)?;
+                add_additive(builder, "arith.addi", v0, v1, location)
+            }
+            AdditiveExpression::Minus { lhs, rhs, location } => {
+                let v0 = lhs.add_value(builder)?;
+                let v1 = rhs.add_value(builder)?;
+                add_additive(builder, "arith.subi", v0, v1, location)
+            }
+        }
+    }
+}
+
+impl AddValue for ShiftExpression {
+    fn add_value<'c, 'a>(
+        &self,
+        builder: &mut FunctionBuilder<'c, 'a>,
+    ) -> Result<TypedValue<'c, 'a>, CompileError> {
+        let context = builder.context;
+        match self {
+            ShiftExpression::AdditiveExpression(additive_expression) => {
+                additive_expression.add_value(builder)
+            }
+            ShiftExpression::Left { lhs, rhs, location } => {
+                let v0 = lhs.add_value(builder)?;
+                let v1 = rhs.add_value(builder)?;
+                check_integers(&v0, &v1, location)?;
+                Ok(add_shift(
+                    builder,
+                    "arith.shli",
+                    v0,
+                    v1,
+                    location.mlir_location(context),
+                ))
+            }
+            ShiftExpression::Right { lhs, rhs, location } => {
+                let v0 = lhs.add_value(builder)?;
+                let v1 = rhs.add_value(builder)?;
+                check_integers(&v0, &v1, location)?;
+                Ok(add_shift(
+                    builder,
+                    "arith.shrsi",
+                    v0,
+                    v1,
+                    location.mlir_location(context),
+                ))
+            }
+        }
+    }
+}
+
+impl AddValue for RelationalExpression {
+    fn add_value<'c, 'a>(
+        &self,
+        builder: &mut FunctionBuilder<'c, 'a>,
+    ) -> Result<TypedValue<'c, 'a>, CompileError> {
+        match self {
+            RelationalExpression::ShiftExpression(shift_expression) => {
+                shift_expression.add_value(builder)
+            }
+            RelationalExpression::Less { lhs, rhs, location } => {
+                let v0 = lhs.add_value(builder)?;
+                let v1 = rhs.add_value(builder)?;
+                add_relational(
+                    builder,
+                    CmpiPredicate::Slt,
+                    v0,
+                    v1,
+                    (false, false),
+                    location,
+                )
+            }
+            RelationalExpression::Greater { lhs, rhs, location } => {
+                let v0 = lhs.add_value(builder)?;
+                let v1 = rhs.add_value(builder)?;
+                add_relational(
+                    builder,
+                    CmpiPredicate::Sgt,
+                    v0,
+                    v1,
+                    (false, false),
+                    location,
+                )
+            }
+            RelationalExpression::LessEqual { lhs, rhs, location } => {
+                let v0 = lhs.add_value(builder)?;
+                let v1 = rhs.add_value(builder)?;
+                add_relational(
+                    builder,
+                    CmpiPredicate::Sle,
+                    v0,
+                    v1,
+                    (false, false),
+                    location,
+                )
+            }
+            RelationalExpression::GreaterEqual { lhs, rhs, location } => {
+                let v0 = lhs.add_value(builder)?;
+                let v1 = rhs.add_value(builder)?;
+                add_relational(
+                    builder,
+                    CmpiPredicate::Sge,
+                    v0,
+                    v1,
+                    (false, false),
+                    location,
+                )
+            }
+        }
+    }
+}
+
+impl AddValue for EqualityExpression {
+    fn add_value<'c, 'a>(
+        &self,
+        builder: &mut FunctionBuilder<'c, 'a>,
+    ) -> Result<TypedValue<'c, 'a>, CompileError> {
+        match self {
+            EqualityExpression::RelationalExpression(relational_expression) => {
+                relational_expression.add_value(builder)
+            }
+            EqualityExpression::Equal { lhs, rhs, location } => {
+                let v0 = lhs.add_value(builder)?;
+                let v1 = rhs.add_value(builder)?;
+                let nulls = (
+                    is_null_pointer_constant(lhs.evaluate()),
+                    is_null_pointer_constant(rhs.evaluate()),
+                );
+                add_relational(builder, CmpiPredicate::Eq, v0, v1, nulls, location)
+            }
+            EqualityExpression::NotEqual { lhs, rhs, location } => {
+                let v0 = lhs.add_value(builder)?;
+                let v1 = rhs.add_value(builder)?;
+                let nulls = (
+                    is_null_pointer_constant(lhs.evaluate()),
+                    is_null_pointer_constant(rhs.evaluate()),
+                );
+                add_relational(builder, CmpiPredicate::Ne, v0, v1, nulls, location)
+            }
+        }
+    }
+}
+
+impl AddValue for AssignmentExpression {
+    fn add_value<'c, 'a>(
+        &self,
+        builder: &mut FunctionBuilder<'c, 'a>,
+    ) -> Result<TypedValue<'c, 'a>, CompileError> {
+        let context = builder.context;
+        match self {
+            AssignmentExpression::EqualityExpression(equality_expression) => {
+                equality_expression.add_value(builder)
+            }
+            AssignmentExpression::Assign { lhs, rhs, location } => {
+                let lvalue = lhs.add_lvalue(builder)?;
+                check_modifiable(&lvalue, location)?;
+                // 6.5.16.1 The value is converted to the type of the assignment expression
+                let r#type = lvalue.r#type().clone();
+                let v0 = add_assignment(builder, rhs, &r#type, location)?;
+                let location = location.mlir_location(context);
+                builder.store(&lvalue, v0, location);
+                // The value of a bit-field is truncated to its width
+                if let Lvalue::BitField { .. } = lvalue {
+                    return Ok(builder.load(&lvalue, location));
+                }
+                Ok(TypedValue { value: v0, r#type })
+            }
+        }
+    }
+}
+
+fn not_record(r#type: &CType, location: &lexer::Location) -> CompileError {
+    CompileError::new(
+        location.clone(),
+        format!(
+            "member reference base type '{}' is not a structure or union",
+            r#type
+        ),
+    )
+}
+
+// 6.5.2.3 The member `identifier` of the structure or union at `pointer`, looked up through
+// anonymous members. `alignment` is that of a structure which is a member of a packed one.
+fn member<'c, 'a>(
+    builder: &FunctionBuilder<'c, 'a>,
+    pointer: Value<'c, 'a>,
+    r#type: &CType,
+    r#const: bool,
+    alignment: Option<u64>,
+    identifier: &str,
+    location: &lexer::Location,
+) -> Result<Lvalue<'c, 'a>, CompileError> {
+    let CType::Record(record) = r#type else {
+        unreachable!()
+    };
+    if record.layout().is_none() {
+        return Err(CompileError::new(
+            location.clone(),
+            format!("incomplete definition of type '{}'", r#type),
+        ));
+    }
+    let Some(path) = record.find_member(identifier) else {
+        return Err(CompileError::new(
+            location.clone(),
+            format!("no member named '{}' in '{}'", identifier, r#type),
+        ));
+    };
+    let mlir_location = location.mlir_location(builder.context);
+    let mut lvalue = Lvalue::Memory {
+        pointer,
+        r#type: r#type.clone(),
+        r#const,
+        alignment,
+    };
+    for index in path {
+        lvalue = member_at(
+            builder,
+            builder.address(&lvalue, mlir_location),
+            lvalue.r#type(),
+            lvalue.is_const(),
+            lvalue.alignment(),
+            index,
+            mlir_location,
+        );
+    }
+    Ok(lvalue)
+}
+
+/// The member at `index` of the structure or union at `pointer`. Members of a union are all at
+/// its address. `alignment` is that of a structure which is a member of a packed one.
+pub(super) fn member_at<'c, 'a>(
+    builder: &FunctionBuilder<'c, 'a>,
+    mut pointer: Value<'c, 'a>,
+    r#type: &CType,
+    r#const: bool,
+    alignment: Option<u64>,
+    index: usize,
+    location: Location<'c>,
+) -> Lvalue<'c, 'a> {
+    let context = builder.context;
+    let CType::Record(record) = r#type else {
+        unreachable!()
+    };
+    let layout = record.layout().unwrap();
+    let member = &layout.members[index];
+    let r#const = r#const || member.r#const;
+    match member.bit_field {
+        // A bit-field is not a field of the `!llvm.struct`, so its bytes are addressed directly
+        Some(bit_field) => {
+            if member.offset > 0 {
+                pointer = builder
+                    .append_operation(llvm::get_element_ptr(
+                        context,
+                        pointer,
+                        DenseI32ArrayAttribute::new(context, &[member.offset as i32]),
+                        IntegerType::new(context, 8).into(),
+                        llvm::r#type::pointer(context, 0),
+                        location,
+                    ))
+                    .result(0)
+                    .unwrap()
+                    .into();
+            }
+            Lvalue::BitField {
+                pointer,
+                r#type: member.r#type.clone(),
+                bit_field,
+                r#const,
+                alignment: layout.offset_alignment(member.offset, alignment),
+            }
+        }
+        None => {
+            if record.kind == RecordKind::Struct {
+                let field = record_fields(record.kind, &layout)
+                    .iter()
+                    .position(|field| matches!(field, Field::Member(i) if *i == index))
+                    .unwrap();
+                pointer = builder
+                    .append_operation(llvm::get_element_ptr(
+                        context,
+                        pointer,
+                        DenseI32ArrayAttribute::new(context, &[0, field as i32]),
+                        r#type.mlir_type(context),
+                        llvm::r#type::pointer(context, 0),
+                        location,
+                    ))
+                    .result(0)
+                    .unwrap()
+                    .into();
+            }
+            Lvalue::Memory {
+                pointer,
+                r#type: member.r#type.clone(),
+                r#const,
+                alignment: member.access_alignment(alignment),
+            }
+        }
+    }
+}
+
+fn void_value(location: &lexer::Location) -> CompileError {
+    CompileError::new(
+        location.clone(),
+        "void value not ignored as it ought to be".to_string(),
+    )
+}
+
+fn not_assignable(location: &lexer::Location) -> CompileError {
+    CompileError::new(location.clone(), "expression is not assignable".to_string())
+}
+
+// 6.5.16, 6.5.2.4 The operand must be a modifiable lvalue
+fn check_modifiable(lvalue: &Lvalue, location: &lexer::Location) -> Result<(), CompileError> {
+    if lvalue.r#type().is_function() {
+        return Err(CompileError::new(
+            location.clone(),
+            format!("non-object type '{}' is not assignable", lvalue.r#type()),
+        ));
+    }
+    if lvalue.r#type().is_array() {
+        return Err(CompileError::new(
+            location.clone(),
+            format!("array type '{}' is not assignable", lvalue.r#type()),
+        ));
+    }
+    if lvalue.is_const() {
+        return Err(CompileError::new(
+            location.clone(),
+            "cannot assign to a const-qualified object".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+impl AddLvalue for PrimaryExpression {
+    fn add_lvalue<'c, 'a>(
+        &self,
+        builder: &mut FunctionBuilder<'c, 'a>,
+    ) -> Result<Lvalue<'c, 'a>, CompileError> {
+        match self {
+            PrimaryExpression::Identifier {
+                identifier,
+                location,
+            } => {
+                if let Some(variable) = builder.lookup(identifier) {
+                    return Ok(variable.lvalue(identifier));
+                }
+                let Some(symbol) = builder.file_scope().lookup(identifier) else {
+                    return Err(CompileError::new(
+                        location.clone(),
+                        format!("use of undeclared identifier '{}'", identifier),
+                    ));
+                };
+                // A function designator is at the address of the function
+                if symbol.r#type.is_function() && !builder.file_scope().is_llvm_function(identifier)
+                {
+                    return Err(CompileError::new(
+                        location.clone(),
+                        format!(
+                            "cannot take the address of function '{}' whose type has no LLVM form",
+                            identifier
+                        ),
+                    ));
+                }
+                let r#type = symbol.r#type.clone();
+                let r#const = symbol.r#const;
+                let context = builder.context;
+                let pointer = builder
+                    .append_operation(addressof_operation(
+                        context,
+                        identifier,
+                        location.mlir_location(context),
+                    ))
+                    .result(0)
+                    .unwrap()
+                    .into();
+                Ok(Lvalue::Memory {
+                    pointer,
+                    r#type,
+                    r#const,
+                    alignment: None,
+                })
+            }
+            // 6.4.5 An array of static storage duration
+            PrimaryExpression::StringLiteral {
+                value,
+                r#type,
+                location,
+            } => {
+                let mut value = value.clone();
+                value.push(0);
+                let context = builder.context;
+                let identifier = builder.file_scope().string_literal(&value, location);
+                let pointer = builder
+                    .append_operation(addressof_operation(
+                        context,
+                        &identifier,
+                        location.mlir_location(context),
+                    ))
+                    .result(0)
+                    .unwrap()
+                    .into();
+                Ok(Lvalue::Memory {
+                    pointer,
+                    r#type: r#type.clone(),
+                    r#const: false,
+                    alignment: None,
+                })
+            }
+            PrimaryExpression::Parenthesized(expression) => expression.add_lvalue(builder),
+            // 6.5.1.1 It is an lvalue if the selected expression is
+            PrimaryExpression::GenericSelection(generic_selection) => {
+                select_association(builder, generic_selection)?.add_lvalue(builder)
+            }
+            PrimaryExpression::Constant { location, .. } => Err(not_assignable(location)),
+            PrimaryExpression::VaBuiltin(va_builtin) => Err(not_assignable(va_builtin.location())),
+        }
+    }
+}
+
+impl AddLvalue for PostfixExpression {
+    fn add_lvalue<'c, 'a>(
+        &self,
+        builder: &mut FunctionBuilder<'c, 'a>,
+    ) -> Result<Lvalue<'c, 'a>, CompileError> {
+        match self {
+            PostfixExpression::PrimaryExpression(primary_expression) => {
+                primary_expression.add_lvalue(builder)
+            }
+            PostfixExpression::Subscript {
+                array,
+                index,
+                location,
+            } => {
+                let array = match array.as_ref() {
+                    // The same as `add_value`, except that an array kept as a `memref` is
+                    // indexed instead of decaying to a pointer
+                    PostfixExpression::PrimaryExpression(PrimaryExpression::Identifier {
+                        ..
+                    })
+                    | PostfixExpression::Subscript { .. } => {
+                        let mut lvalue = array.add_lvalue(builder)?;
+                        let is_array = lvalue.r#type().is_array();
+                        if let (Lvalue::Memref { indices, .. }, true) = (&mut lvalue, is_array) {
+                            let index = index.add_value(builder)?;
+                            if !index.r#type.is_integer() {
+                                return Err(CompileError::new(
+                                    location.clone(),
+                                    "array subscript is not an integer".to_string(),
+                                ));
+                            }
+                            indices.push(add_index(
+                                builder,
+                                index,
+                                location.mlir_location(builder.context),
+                            ));
+                            return Ok(lvalue);
+                        }
+                        add_lvalue_conversion(builder, lvalue, location)?
+                    }
+                    array => array.add_value(builder)?,
+                };
+                let index = index.add_value(builder)?;
+                // Either operand may be the pointer
+                if !array.r#type.is_pointer() && !index.r#type.is_pointer() {
+                    return Err(CompileError::new(
+                        location.clone(),
+                        "subscripted value is not an array or pointer".to_string(),
+                    ));
+                }
+                if !array.r#type.is_integer() && !index.r#type.is_integer() {
+                    return Err(CompileError::new(
+                        location.clone(),
+                        "array subscript is not an integer".to_string(),
+                    ));
+                }
+                let pointer = add_additive(builder, "arith.addi", array, index, location)?;
+                indirection(pointer, location)
+            }
+            PostfixExpression::Member {
+                structure,
+                identifier,
+                location,
+            } => {
+                let mlir_location = location.mlir_location(builder.context);
+                let (pointer, r#type, r#const, alignment) = match structure.as_ref() {
+                    // The result of a call is not an lvalue but its members can be read, so it
+                    // is stored to a temporary
+                    PostfixExpression::FunctionCall { .. } => {
+                        let value = structure.add_value(builder)?;
+                        if !value.r#type.is_record() {
+                            return Err(not_record(&value.r#type, location));
+                        }
+                        let pointer = builder.add_alloca(
+                            &value.r#type,
+                            value.r#type.alignment(),
+                            mlir_location,
+                        );
+                        let lvalue = Lvalue::Memory {
+                            pointer,
+                            r#type: value.r#type.clone(),
+                            r#const: true,
+                            alignment: None,
+                        };
+                        builder.store(&lvalue, value.value, mlir_location);
+                        (pointer, value.r#type, true, None)
+                    }
+                    structure => {
+                        let lvalue = structure.add_lvalue(builder)?;
+                        if !lvalue.r#type().is_record() {
+                            return Err(not_record(lvalue.r#type(), location));
+                        }
+                        (
+                            builder.address(&lvalue, mlir_location),
+                            lvalue.r#type().clone(),
+                            lvalue.is_const(),
+                            lvalue.alignment(),
+                        )
+                    }
+                };
+                member(
+                    builder, pointer, &r#type, r#const, alignment, identifier, location,
+                )
+            }
+            PostfixExpression::Arrow {
+                pointer,
+                identifier,
+                location,
+            } => {
+                let pointer = pointer.add_value(builder)?;
+                match &pointer.r#type {
+                    CType::Pointer { pointee, r#const } if pointee.is_record() => member(
+                        builder,
+                        pointer.value,
+                        pointee,
+                        *r#const,
+                        None,
+                        identifier,
+                        location,
+                    ),
+                    r#type => Err(CompileError::new(
+                        location.clone(),
+                        format!(
+                            "member reference type '{}' is not a pointer to a structure or union",
+                            r#type
+                        ),
+                    )),
+                }
+            }
+            PostfixExpression::FunctionCall { location, .. }
+            | PostfixExpression::Increment { location, .. }
+            | PostfixExpression::Decrement { location, .. } => Err(not_assignable(location)),
+        }
+    }
+}
+
+impl AddLvalue for UnaryExpression {
+    fn add_lvalue<'c, 'a>(
+        &self,
+        builder: &mut FunctionBuilder<'c, 'a>,
+    ) -> Result<Lvalue<'c, 'a>, CompileError> {
+        match self {
+            UnaryExpression::PostfixExpression(postfix_expression) => {
+                postfix_expression.add_lvalue(builder)
+            }
+            UnaryExpression::Indirection {
+                expression,
+                location,
+            } => {
+                let pointer = expression.add_value(builder)?;
+                indirection(pointer, location)
+            }
+            UnaryExpression::Increment { location, .. }
+            | UnaryExpression::Decrement { location, .. }
+            | UnaryExpression::Address { location, .. }
+            | UnaryExpression::Plus { location, .. }
+            | UnaryExpression::Minus { location, .. }
+            | UnaryExpression::BitwiseNot { location, .. }
+            | UnaryExpression::LogicalNot { location, .. }
+            | UnaryExpression::SizeofExpression { location, .. }
+            | UnaryExpression::SizeofType { location, .. } => Err(not_assignable(location)),
+        }
+    }
+}
+
+impl AddLvalue for CastExpression {
+    fn add_lvalue<'c, 'a>(
+        &self,
+        builder: &mut FunctionBuilder<'c, 'a>,
+    ) -> Result<Lvalue<'c, 'a>, CompileError> {
+        match self {
+            CastExpression::UnaryExpression(unary_expression) => {
+                unary_expression.add_lvalue(builder)
+            }
+            CastExpression::Cast { location, .. } => Err(not_assignable(location)),
+        }
+    }
+}
+
+impl AddLvalue for MultiplicativeExpression {
+    fn add_lvalue<'c, 'a>(
+        &self,
+        builder: &mut FunctionBuilder<'c, 'a>,
+    ) -> Result<Lvalue<'c, 'a>, CompileError> {
+        match self {
+            MultiplicativeExpression::CastExpression(cast_expression) => {
+                cast_expression.add_lvalue(builder)
+            }
+            MultiplicativeExpression::Mul { location, .. }
+            | MultiplicativeExpression::Div { location, .. }
+            | MultiplicativeExpression::Rem { location, .. } => Err(not_assignable(location)),
+        }
+    }
+}
+
+impl AddLvalue for AdditiveExpression {
+    fn add_lvalue<'c, 'a>(
+        &self,
+        builder: &mut FunctionBuilder<'c, 'a>,
+    ) -> Result<Lvalue<'c, 'a>, CompileError> {
+        match self {
+            AdditiveExpression::PrimaryExpression(multiplicative_expression) => {
+                multiplicative_expression.add_lvalue(builder)
+            }
+            AdditiveExpression::Add { location, .. }
+            | AdditiveExpression::Minus { location, .. } => Err(not_assignable(location)),
+        }
+    }
+}
+
+impl AddLvalue for ShiftExpression {
+    fn add_lvalue<'c, 'a>(
+        &self,
+        builder: &mut FunctionBuilder<'c, 'a>,
+    ) -> Result<Lvalue<'c, 'a>, CompileError> {
+        match self {
+            ShiftExpression::AdditiveExpression(additive_expression) => {
+                additive_expression.add_lvalue(builder)
+            }
+            ShiftExpression::Left { location, .. } | ShiftExpression::Right { location, .. } => {
+                Err(not_assignable(location))
+            }
+        }
+    }
+}
+
+impl AddLvalue for RelationalExpression {
+    fn add_lvalue<'c, 'a>(
+        &self,
+        builder: &mut FunctionBuilder<'c, 'a>,
+    ) -> Result<Lvalue<'c, 'a>, CompileError> {
+        match self {
+            RelationalExpression::ShiftExpression(shift_expression) => {
+                shift_expression.add_lvalue(builder)
+            }
+            RelationalExpression::Less { location, .. }
+            | RelationalExpression::Greater { location, .. }
+            | RelationalExpression::LessEqual { location, .. }
+            | RelationalExpression::GreaterEqual { location, .. } => Err(not_assignable(location)),
+        }
+    }
+}
+
+impl AddLvalue for EqualityExpression {
+    fn add_lvalue<'c, 'a>(
+        &self,
+        builder: &mut FunctionBuilder<'c, 'a>,
+    ) -> Result<Lvalue<'c, 'a>, CompileError> {
+        match self {
+            EqualityExpression::RelationalExpression(relational_expression) => {
+                relational_expression.add_lvalue(builder)
+            }
+            EqualityExpression::Equal { location, .. }
+            | EqualityExpression::NotEqual { location, .. } => Err(not_assignable(location)),
+        }
+    }
+}
+
+impl AddLvalue for Expression {
+    fn add_lvalue<'c, 'a>(
+        &self,
+        builder: &mut FunctionBuilder<'c, 'a>,
+    ) -> Result<Lvalue<'c, 'a>, CompileError> {
+        match self {
+            Expression::AssignmentExpression(assignment_expression) => {
+                assignment_expression.add_lvalue(builder)
+            }
+        }
+    }
+}
+
+impl AddLvalue for AssignmentExpression {
+    fn add_lvalue<'c, 'a>(
+        &self,
+        builder: &mut FunctionBuilder<'c, 'a>,
+    ) -> Result<Lvalue<'c, 'a>, CompileError> {
+        match self {
+            AssignmentExpression::EqualityExpression(equality_expression) => {
+                equality_expression.add_lvalue(builder)
+            }
+            // 6.5.16 An assignment expression is not an lvalue
+            AssignmentExpression::Assign { location, .. } => Err(not_assignable(location)),
+        }
+    }
+}
+
+#[test]
+fn test_lower_va() {
+    use super::{lower, Options};
+
+    let src = "double second(int n, ...) { \
+                   va_list ap; va_start(ap, n); va_arg(ap, int); \
+                   double d = va_arg(ap, double); va_end(ap); return d; } \
+               double call(float f, char c, short s) { return second(3, c, f, s); }";
+    let module = lower(src, &Options::default()).unwrap();
+    assert!(module.contains("llvm.intr.vastart"));
+    assert_eq!(module.matches("llvm.va_arg").count(), 2);
+    assert!(module.contains("llvm.intr.vaend"));
+    // 6.5.2.2 The default argument promotions are performed on the variadic arguments
+    assert!(module.contains("i8 to i32"));
+    assert!(module.contains("f32 to f64"));
+    assert!(module.contains("i16 to i32"));
+    assert!(module.contains("(i32, i32, f64, i32) -> f64"));
+}
@@ -0,0 +1,355 @@
+// 6.7.9 Initializers resolved against the type of the object
+//
+// Designators and brace elision are applied first, which gives each subobject at most one
+// initializer. Later initializers of the same subobject override earlier ones.
+
+use std::collections::BTreeMap;
+
+use crate::{
+    lexer::Location,
+    parser::{
+        declaration::{DesignatedInitializer, Designator, Initializer},
+        expression::{
+            AssignmentExpression, Evaluate, PostfixExpression, PrimaryExpression, UnaryExpression,
+        },
+    },
+    types::{CType, IntegerKind, RecordKind},
+};
+
+use super::{constant_error, expression::value_type, CompileError, FunctionBuilder};
+
+pub enum Initialization<'s> {
+    Expression {
+        location: &'s Location,
+        expression: &'s AssignmentExpression,
+    },
+    /// The initializers of the elements or members by index. The subobjects without one are
+    /// initialized to 0, and so is a scalar with an empty list.
+    List(BTreeMap<u64, Initialization<'s>>),
+}
+
+impl<'s> Initialization<'s> {
+    /// Without a builder the type of an expression is not known, so an expression is never taken
+    /// to initialize a whole structure or union in a list.
+    pub fn new(
+        builder: Option<&FunctionBuilder>,
+        initializer: &'s Initializer,
+        r#type: &CType,
+    ) -> Result<Self, CompileError> {
+        match initializer {
+            Initializer::Expression {
+                location,
+                expression,
+            } => Ok(Initialization::Expression {
+                location,
+                expression,
+            }),
+            Initializer::List { initializers, .. } => list(builder, initializers, r#type),
+        }
+    }
+
+    /// The length of an array of unknown size initialized with it, which is one past the largest
+    /// index initialized
+    pub fn length(&self) -> u64 {
+        match self {
+            Initialization::Expression { expression, .. } => {
+                string_literal(expression).map_or(1, |value| value.len() as u64 + 1)
+            }
+            Initialization::List(subobjects) => subobjects
+                .last_key_value()
+                .map_or(0, |(index, _)| index + 1),
+        }
+    }
+}
+
+fn list<'s>(
+    builder: Option<&FunctionBuilder>,
+    initializers: &'s [DesignatedInitializer],
+    r#type: &CType,
+) -> Result<Initialization<'s>, CompileError> {
+    match (r#type, initializers) {
+        // A character array may be initialized with a string literal in braces
+        (
+            CType::Array { element, .. },
+            [DesignatedInitializer {
+                designators,
+                initializer:
+                    Initializer::Expression {
+                        location,
+                        expression,
+                    },
+            }],
+        ) if designators.is_empty() && is_char(element) && string_literal(expression).is_some() => {
+            return Ok(Initialization::Expression {
+                location,
+                expression,
+            })
+        }
+        (CType::Array { .. } | CType::Record(_), _) => {}
+        (_, []) => return Ok(Initialization::List(BTreeMap::new())),
+        (
+            _,
+            [DesignatedInitializer {
+                designators,
+                initializer,
+            }],
+        ) => {
+            if let Some(designator) = designators.first() {
+                return Err(CompileError::new(
+                    designator.location().clone(),
+                    format!("designator in initializer for scalar type '{}'", r#type),
+                ));
+            }
+            return Initialization::new(builder, initializer, r#type);
+        }
+        (_, [_, initializer, ..]) => {
+            return Err(CompileError::new(
+                initializer.initializer.location().clone(),
+                "excess elements in scalar initializer".to_string(),
+            ))
+        }
+    }
+    let mut designation = Designation {
+        builder,
+        initializers,
+        position: 0,
+        designators: 0,
+        path: Vec::new(),
+    };
+    let mut subobjects = BTreeMap::new();
+    designation.fill(r#type, &mut subobjects, false)?;
+    Ok(Initialization::List(subobjects))
+}
+
+// The position in a brace-enclosed list of the object being initialized
+struct Designation<'s, 'b, 'c, 'a> {
+    builder: Option<&'b FunctionBuilder<'c, 'a>>,
+    initializers: &'s [DesignatedInitializer],
+    // Of the next initializer
+    position: usize,
+    // The designators of the next initializer that are applied
+    designators: usize,
+    // The anonymous members left by a member designator, innermost first
+    path: Vec<usize>,
+}
+
+impl<'s> Designation<'s, '_, '_, '_> {
+    // Initializes the subobjects of an object until the list ends. If its braces are `elided`,
+    // it also stops when the object is full, or at a designator, which starts again from the
+    // object of the braces.
+    fn fill(
+        &mut self,
+        r#type: &CType,
+        subobjects: &mut BTreeMap<u64, Initialization<'s>>,
+        elided: bool,
+    ) -> Result<(), CompileError> {
+        let mut next = 0;
+        while let Some(initializer) = self.initializers.get(self.position) {
+            if elided && self.designators == 0 && !initializer.designators.is_empty() {
+                return Ok(());
+            }
+            let index = if self.designated() {
+                self.designate(r#type)?
+            } else {
+                match next_subobject(r#type, next) {
+                    Some(index) => index,
+                    None if elided => return Ok(()),
+                    None => {
+                        return Err(CompileError::new(
+                            initializer.initializer.location().clone(),
+                            format!(
+                                "excess elements in {} initializer",
+                                match r#type {
+                                    CType::Array { .. } => "array",
+                                    CType::Record(record) if record.kind == RecordKind::Union => {
+                                        "union"
+                                    }
+                                    _ => "struct",
+                                }
+                            ),
+                        ))
+                    }
+                }
+            };
+            let subobject_type = match r#type {
+                CType::Array { element, .. } => (**element).clone(),
+                CType::Record(record) => {
+                    // Only one member of a union is initialized
+                    if record.kind == RecordKind::Union {
+                        subobjects.retain(|member, _| *member == index);
+                    }
+                    record.layout().unwrap().members[index as usize]
+                        .r#type
+                        .clone()
+                }
+                _ => unreachable!(),
+            };
+            let elision = match &initializer.initializer {
+                _ if self.designated() => true,
+                Initializer::Expression {
+                    location,
+                    expression,
+                } => !self.initializes(&subobject_type, expression, location)?,
+                Initializer::List { .. } => false,
+            };
+            if elision {
+                // Further designators or the next initializers are for the subobject, which
+                // keeps its earlier initializers
+                let subobject = subobjects
+                    .entry(index)
+                    .or_insert_with(|| Initialization::List(BTreeMap::new()));
+                if let Initialization::Expression { .. } = subobject {
+                    *subobject = Initialization::List(BTreeMap::new());
+                }
+                let Initialization::List(subobjects) = subobject else {
+                    unreachable!()
+                };
+                self.fill(&subobject_type, subobjects, true)?;
+            } else {
+                subobjects.insert(
+                    index,
+                    Initialization::new(self.builder, &initializer.initializer, &subobject_type)?,
+                );
+                self.position += 1;
+                self.designators = 0;
+            }
+            next = match r#type {
+                CType::Record(record) if record.kind == RecordKind::Union => u64::MAX,
+                _ => index + 1,
+            };
+        }
+        Ok(())
+    }
+
+    fn designated(&self) -> bool {
+        !self.path.is_empty()
+            || self.designators < self.initializers[self.position].designators.len()
+    }
+
+    // Applies the next designator to an object, which gives the index of the subobject
+    fn designate(&mut self, r#type: &CType) -> Result<u64, CompileError> {
+        if let Some(index) = self.path.pop() {
+            return Ok(index as u64);
+        }
+        let designator = &self.initializers[self.position].designators[self.designators];
+        self.designators += 1;
+        match (designator, r#type) {
+            (Designator::Index(expression), CType::Array { length, .. }) => {
+                let index = expression
+                    .evaluate()
+                    .map_err(|error| {
+                        constant_error(error, "expression is not an integer constant expression")
+                    })?
+                    .value;
+                if index < 0 {
+                    return Err(CompileError::new(
+                        expression.location.clone(),
+                        format!("array designator value '{}' is negative", index),
+                    ));
+                }
+                if index >= *length as i128 {
+                    return Err(CompileError::new(
+                        expression.location.clone(),
+                        format!(
+                            "array designator index ({}) exceeds array bounds ({})",
+                            index, length
+                        ),
+                    ));
+                }
+                Ok(index as u64)
+            }
+            (Designator::Index(expression), _) => Err(CompileError::new(
+                expression.location.clone(),
+                format!(
+                    "array designator cannot initialize non-array type '{}'",
+                    r#type
+                ),
+            )),
+            (
+                Designator::Member {
+                    location,
+                    identifier,
+                },
+                CType::Record(record),
+            ) => {
+                let Some(mut path) = record.find_member(identifier) else {
+                    return Err(CompileError::new(
+                        location.clone(),
+                        format!(
+                            "field designator '{}' does not refer to any field in type '{}'",
+                            identifier, r#type
+                        ),
+                    ));
+                };
+                path.reverse();
+                let index = path.pop().unwrap();
+                self.path = path;
+                Ok(index as u64)
+            }
+            (Designator::Member { location, .. }, _) => Err(CompileError::new(
+                location.clone(),
+                format!(
+                    "field designator cannot initialize a non-struct, non-union type '{}'",
+                    r#type
+                ),
+            )),
+        }
+    }
+
+    // Whether an expression initializes a whole subobject, or only its first scalar with the
+    // braces of the subobject elided
+    fn initializes(
+        &self,
+        r#type: &CType,
+        expression: &AssignmentExpression,
+        location: &Location,
+    ) -> Result<bool, CompileError> {
+        match r#type {
+            CType::Array { element, .. } => {
+                Ok(is_char(element) && string_literal(expression).is_some())
+            }
+            CType::Record(_) => match self.builder {
+                Some(builder) => Ok(value_type(builder, expression, location)? == *r#type),
+                None => Ok(false),
+            },
+            _ => Ok(true),
+        }
+    }
+}
+
+// The index of the first subobject from `index` that is initialized in order. Unnamed bit-fields
+// are skipped, and a union has only its first member.
+fn next_subobject(r#type: &CType, index: u64) -> Option<u64> {
+    match r#type {
+        CType::Array { length, .. } => (index < *length).then_some(index),
+        CType::Record(record) => record
+            .layout()
+            .unwrap()
+            .members
+            .iter()
+            .enumerate()
+            .skip(index as usize)
+            .find(|(_, member)| member.identifier.is_some() || member.bit_field.is_none())
+            .map(|(index, _)| index as u64),
+        _ => unreachable!(),
+    }
+}
+
+fn is_char(r#type: &CType) -> bool {
+    matches!(
+        r#type,
+        CType::Integer {
+            kind: IntegerKind::Char | IntegerKind::PlainChar,
+            ..
+        }
+    )
+}
+
+pub(super) fn string_literal(initializer: &AssignmentExpression) -> Option<&[u8]> {
+    match initializer.as_unary_expression()? {
+        UnaryExpression::PostfixExpression(PostfixExpression::PrimaryExpression(
+            PrimaryExpression::StringLiteral { value, .. },
+        )) => Some(value),
+        _ => None,
+    }
+}
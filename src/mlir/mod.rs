@@ -0,0 +1,3002 @@
+use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    rc::Rc,
+};
+
+use ecow::{eco_format, EcoString};
+
+use melior::{
+    dialect::{
+        arith::{self, CmpfPredicate, CmpiPredicate},
+        cf, func,
+        llvm::{self, AllocaOptions, LoadStoreOptions},
+        memref,
+    },
+    ir::{
+        attribute::{
+            DenseI32ArrayAttribute, DenseI64ArrayAttribute, FlatSymbolRefAttribute, FloatAttribute,
+            IntegerAttribute, StringAttribute, TypeAttribute,
+        },
+        operation::OperationBuilder,
+        r#type::{FunctionType, IntegerType, MemRefType},
+        Attribute, Block, Identifier, Location, Module, Operation, OperationRef, Region, Type,
+        Value, ValueLike,
+    },
+    Context,
+};
+use typed_arena::Arena;
+
+use crate::{
+    lexer,
+    parser::{
+        declaration::{
+            check_return_type, ArrayDeclarator, DeclarationSpecifiers, Derivation,
+            ParameterDeclaration, ParameterTypeList, StorageClassSpecifier,
+        },
+        expression::{ConstantError, Evaluate, IntegerConstant, OperandType, UnaryExpression},
+        ExternalDeclaration, FunctionDeclaration, FunctionDefinition, TranslationUnit,
+    },
+    types::{BitField, CType, FloatingKind, Layout, RecordKind, VariableLength},
+};
+
+pub mod builtin;
+pub mod declaration;
+pub mod expression;
+pub mod initializer;
+pub mod scf;
+pub mod statement;
+
+use scf::Analyze;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ControlFlow {
+    /// Unstructured branches between blocks
+    #[default]
+    Cf,
+    /// `scf.if`, `scf.for` and `scf.while` with locals kept as SSA values.
+    /// Functions using `break`, `continue`, `switch`, an early `return`, `va_list` or `&` fall back
+    /// to `cf`.
+    Scf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum MemoryModel {
+    /// Locals are `llvm.alloca` accessed with `llvm.load` and `llvm.store`
+    #[default]
+    Llvm,
+    /// Integer locals and arrays of them are `memref.alloca` accessed with `memref.load` and
+    /// `memref.store`, unless the address of the integer is taken, and array parameters with
+    /// constant lengths are `memref`s.
+    /// Such a parameter is not adjusted to a pointer, so `sizeof` gives the size of the array.
+    Memref,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    pub control_flow: ControlFlow,
+    pub memory_model: MemoryModel,
+    /// Functions of the standard library are called by name instead of lowered as builtins,
+    /// like `-fno-builtin` of GCC. Those named with `__builtin_` are still lowered.
+    pub no_builtin: bool,
+}
+
+#[derive(Debug)]
+pub struct CompileError {
+    pub location: lexer::Location,
+    pub message: String,
+}
+
+impl CompileError {
+    pub fn new(location: lexer::Location, message: String) -> Self {
+        Self { location, message }
+    }
+}
+
+impl Display for CompileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{}:{}:{}:",
+            self.location.filename,
+            // To 1-indexed
+            self.location.line + 1,
+            self.location.column + 1,
+        )?;
+        writeln!(f, "{}", self.message)?;
+
+        Ok(())
+    }
+}
+
+// 6.6 `message` reports an operand that is not allowed in the constant expression
+fn constant_error(error: ConstantError, message: &str) -> CompileError {
+    match error {
+        ConstantError::NotConstant(location) => CompileError::new(location, message.to_string()),
+        ConstantError::Undefined { location, message } => CompileError::new(location, message),
+    }
+}
+
+pub trait AddModule {
+    fn add_module(
+        &self,
+        context: &Context,
+        module: &Module,
+        options: &Options,
+    ) -> Result<(), CompileError>;
+}
+
+pub trait AddBlock {
+    fn add_block<'c, 'a>(&self, builder: &mut FunctionBuilder<'c, 'a>) -> Result<(), CompileError>;
+}
+
+#[derive(Clone)]
+pub struct TypedValue<'c, 'a> {
+    pub value: Value<'c, 'a>,
+    pub r#type: CType,
+}
+
+pub trait AddValue {
+    fn add_value<'c, 'a>(
+        &self,
+        builder: &mut FunctionBuilder<'c, 'a>,
+    ) -> Result<TypedValue<'c, 'a>, CompileError>;
+}
+
+/// An object designated by an lvalue 6.3.2.1
+pub enum Lvalue<'c, 'a> {
+    Memory {
+        pointer: Value<'c, 'a>,
+        r#type: CType,
+        r#const: bool,
+        /// Less than that of the type for a member of a packed structure, which is accessed
+        /// with it
+        alignment: Option<u64>,
+    },
+    /// A local kept as an SSA value
+    Variable {
+        identifier: EcoString,
+        r#type: CType,
+        r#const: bool,
+    },
+    /// A local kept as a `memref` of `memref_type`, indexed from its outermost array. Fewer
+    /// indices than the rank designate a subarray.
+    Memref {
+        memref: Value<'c, 'a>,
+        memref_type: CType,
+        indices: Vec<Value<'c, 'a>>,
+        r#const: bool,
+    },
+    /// A bit-field in the bytes from `pointer`, which has no address. The bytes are accessed
+    /// with `alignment`, since they may be at any offset of the record.
+    BitField {
+        pointer: Value<'c, 'a>,
+        r#type: CType,
+        bit_field: BitField,
+        r#const: bool,
+        alignment: u64,
+    },
+}
+
+impl Lvalue<'_, '_> {
+    pub fn r#type(&self) -> &CType {
+        match self {
+            Lvalue::Memory { r#type, .. }
+            | Lvalue::Variable { r#type, .. }
+            | Lvalue::BitField { r#type, .. } => r#type,
+            Lvalue::Memref {
+                memref_type,
+                indices,
+                ..
+            } => {
+                let mut r#type = memref_type;
+                for _ in indices {
+                    let (CType::Array { element, .. } | CType::VariableArray { element, .. }) =
+                        r#type
+                    else {
+                        unreachable!("{} is indexed beyond its rank", memref_type)
+                    };
+                    r#type = element;
+                }
+                r#type
+            }
+        }
+    }
+
+    // 6.3.2.1 A const-qualified lvalue is not modifiable
+    pub fn is_const(&self) -> bool {
+        match self {
+            Lvalue::Memory { r#const, .. }
+            | Lvalue::Variable { r#const, .. }
+            | Lvalue::Memref { r#const, .. }
+            | Lvalue::BitField { r#const, .. } => *r#const,
+        }
+    }
+
+    // Of an object in memory accessed with less than the alignment of its type
+    pub fn alignment(&self) -> Option<u64> {
+        match self {
+            Lvalue::Memory { alignment, .. } => *alignment,
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub enum Variable<'c, 'a> {
+    Memory {
+        pointer: Value<'c, 'a>,
+        r#type: CType,
+        r#const: bool,
+    },
+    Value {
+        value: TypedValue<'c, 'a>,
+        r#const: bool,
+    },
+    Memref {
+        memref: Value<'c, 'a>,
+        r#type: CType,
+        r#const: bool,
+    },
+}
+
+impl<'c, 'a> Variable<'c, 'a> {
+    pub fn lvalue(&self, identifier: &EcoString) -> Lvalue<'c, 'a> {
+        match self {
+            Variable::Memory {
+                pointer,
+                r#type,
+                r#const,
+            } => Lvalue::Memory {
+                pointer: *pointer,
+                r#type: r#type.clone(),
+                r#const: *r#const,
+                alignment: None,
+            },
+            Variable::Value { value, r#const } => Lvalue::Variable {
+                identifier: identifier.clone(),
+                r#type: value.r#type.clone(),
+                r#const: *r#const,
+            },
+            Variable::Memref {
+                memref,
+                r#type,
+                r#const,
+            } => Lvalue::Memref {
+                memref: *memref,
+                memref_type: r#type.clone(),
+                indices: Vec::new(),
+                r#const: *r#const,
+            },
+        }
+    }
+}
+
+pub trait AddLvalue {
+    fn add_lvalue<'c, 'a>(
+        &self,
+        builder: &mut FunctionBuilder<'c, 'a>,
+    ) -> Result<Lvalue<'c, 'a>, CompileError>;
+}
+
+// 6.9.2
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Definition {
+    Declaration,
+    /// An object declared without an initializer or `extern`, which is initialized to 0 unless
+    /// it is defined elsewhere in the translation unit
+    Tentative,
+    Definition,
+}
+
+#[derive(Clone)]
+pub struct FileSymbol {
+    pub location: lexer::Location,
+    pub r#type: CType,
+    pub r#const: bool,
+    /// 6.2.2 Internal linkage
+    pub internal: bool,
+    pub definition: Definition,
+    pub initializer: Option<Initializer>,
+    // 6.7.5 Requested by `_Alignas` in any of the declarations
+    pub alignment: Option<u64>,
+}
+
+#[derive(Clone)]
+pub enum Initializer {
+    Integer(i128),
+    // 6.7.9 The bytes of a character array initialized with a string literal padded to its
+    // length, or of an object initialized with a list
+    String(Vec<u8>),
+    /// 6.6 An address constant, which is the address of an object offset by bytes
+    Address {
+        identifier: EcoString,
+        offset: i64,
+    },
+    /// An object initialized with a list having address constants at byte offsets
+    Aggregate {
+        value: Vec<u8>,
+        addresses: Vec<(u64, EcoString, i64)>,
+    },
+}
+
+/// Identifiers with file scope 6.2.1
+#[derive(Clone, Default)]
+pub struct FileScope {
+    symbols: HashMap<EcoString, FileSymbol>,
+    // Declaration order of `symbols` for a deterministic output
+    order: Vec<EcoString>,
+    // 6.4.5 Shared by the clones in function builders, since literals are found while lowering
+    // function bodies
+    string_literals: Rc<RefCell<Vec<StringLiteral>>>,
+    // Functions whose address may be taken, found before lowering since it decides how calls to
+    // them are lowered
+    designated: Rc<HashSet<EcoString>>,
+    // Identifiers with linkage and objects with static storage duration declared at block scope,
+    // which are declared here once the whole translation unit is seen
+    block_symbols: Rc<RefCell<Vec<BlockSymbol>>>,
+}
+
+struct BlockSymbol {
+    identifier: EcoString,
+    storage_class: Option<StorageClassSpecifier>,
+    symbol: FileSymbol,
+}
+
+// The contents of a string literal including the terminating null character, or of a
+// character array initializer
+#[derive(Clone)]
+struct StringLiteral {
+    value: Vec<u8>,
+    // Of the first occurrence
+    location: lexer::Location,
+}
+
+// Not a valid C identifier
+fn string_literal_symbol(index: usize) -> EcoString {
+    eco_format!(".str.{}", index)
+}
+
+impl FileScope {
+    /// The type of the operand of `sizeof` outside of functions, where it may only refer to
+    /// identifiers with file scope. It is lowered in a function that is dropped.
+    fn operand_type(
+        &self,
+        context: &Context,
+        operand: &UnaryExpression,
+        location: &lexer::Location,
+    ) -> Option<CType> {
+        let blocks = Arena::new();
+        let builder = FunctionBuilder::new(
+            context,
+            &blocks,
+            ControlFlow::Cf,
+            MemoryModel::Llvm,
+            CType::Void,
+            false,
+            self.clone(),
+        );
+        builder.operand_type(operand, location)
+    }
+
+    /// 6.7 All declarations of an identifier must have the same type and linkage, and at most one
+    /// may be a definition.
+    fn declare(
+        &mut self,
+        identifier: &EcoString,
+        storage_class: Option<StorageClassSpecifier>,
+        symbol: FileSymbol,
+    ) -> Result<(), CompileError> {
+        let Some(previous) = self.symbols.get_mut(identifier) else {
+            self.symbols.insert(identifier.clone(), symbol);
+            self.order.push(identifier.clone());
+            return Ok(());
+        };
+        let error = |message: String| Err(CompileError::new(symbol.location.clone(), message));
+        if previous.r#type != symbol.r#type || previous.r#const != symbol.r#const {
+            return error(format!("conflicting types for '{}'", identifier));
+        }
+        // 6.2.2 `extern` and functions without a storage class take the previous linkage
+        let inherited = match storage_class {
+            Some(StorageClassSpecifier::Extern) => true,
+            Some(StorageClassSpecifier::Static) => false,
+            Some(StorageClassSpecifier::Typedef) => unreachable!(),
+            None => matches!(symbol.r#type, CType::Function { .. }),
+        };
+        if !inherited && previous.internal != symbol.internal {
+            return error(if symbol.internal {
+                format!(
+                    "static declaration of '{}' follows non-static declaration",
+                    identifier
+                )
+            } else {
+                format!(
+                    "non-static declaration of '{}' follows static declaration",
+                    identifier
+                )
+            });
+        }
+        if previous.definition == Definition::Definition
+            && symbol.definition == Definition::Definition
+        {
+            return error(format!("redefinition of '{}'", identifier));
+        }
+        previous.alignment = previous.alignment.max(symbol.alignment);
+        if symbol.definition > previous.definition {
+            previous.definition = symbol.definition;
+            previous.initializer = symbol.initializer;
+        }
+        Ok(())
+    }
+
+    pub fn lookup(&self, identifier: &str) -> Option<&FileSymbol> {
+        self.symbols.get(identifier)
+    }
+
+    /// Whether a function is an `llvm.func`, since `func.func` cannot be variadic and
+    /// `llvm.mlir.addressof` takes only an `llvm.func`. One that takes memrefs or complex values,
+    /// which have no LLVM type, stays a `func.func`, so its address cannot be taken.
+    pub fn is_llvm_function(&self, identifier: &str) -> bool {
+        match self.lookup(identifier).map(|symbol| &symbol.r#type) {
+            Some(CType::Function {
+                return_type,
+                parameters,
+                variadic,
+            }) => {
+                *variadic
+                    || self.designated.contains(identifier)
+                        && !return_type.is_complex()
+                        && !parameters
+                            .iter()
+                            .any(|parameter| parameter.is_array() || parameter.is_complex())
+            }
+            _ => false,
+        }
+    }
+
+    /// 6.2.2 Declares an identifier with linkage or a `static` object at block scope, and returns
+    /// its symbol. That of a `static` object is not a valid C identifier, so that it has no
+    /// linkage.
+    fn declare_block_symbol(
+        &mut self,
+        identifier: &EcoString,
+        storage_class: Option<StorageClassSpecifier>,
+        symbol: FileSymbol,
+    ) -> Result<EcoString, CompileError> {
+        let identifier = match storage_class {
+            Some(StorageClassSpecifier::Static) => {
+                eco_format!("{}.{}", identifier, self.block_symbols.borrow().len())
+            }
+            _ => identifier.clone(),
+        };
+        // Visible until the end of the function, since calls are looked up here
+        if symbol.r#type.is_function() {
+            self.declare(&identifier, storage_class, symbol.clone())?;
+        }
+        self.block_symbols.borrow_mut().push(BlockSymbol {
+            identifier: identifier.clone(),
+            storage_class,
+            symbol,
+        });
+        Ok(identifier)
+    }
+
+    /// Returns the symbol of an array of static storage duration holding `value`. Identical
+    /// literals share it.
+    pub fn string_literal(&self, value: &[u8], location: &lexer::Location) -> EcoString {
+        let mut string_literals = self.string_literals.borrow_mut();
+        let index = match string_literals
+            .iter()
+            .position(|literal| literal.value == value)
+        {
+            Some(index) => index,
+            None => {
+                string_literals.push(StringLiteral {
+                    value: value.to_vec(),
+                    location: location.clone(),
+                });
+                string_literals.len() - 1
+            }
+        };
+        string_literal_symbol(index)
+    }
+}
+
+struct Switch<'c, 'a> {
+    r#type: CType,
+    cases: Vec<(i64, &'a Block<'c>)>,
+    default: Option<&'a Block<'c>>,
+}
+
+struct LabelTarget<'c, 'a> {
+    block: &'a Block<'c>,
+    // The scopes enclosing the label and the number of stack saves of the innermost one there
+    definition: Option<(Vec<usize>, usize)>,
+    // The `goto`s seen before the label is defined, in order
+    forward_gotos: Vec<ForwardGoto<'c, 'a>>,
+}
+
+// A `goto` branches to `block`, which restores the stack and branches to the label once the
+// scopes between them are known
+struct ForwardGoto<'c, 'a> {
+    block: &'a Block<'c>,
+    stack_saves: Vec<StackSaves<'c, 'a>>,
+    location: lexer::Location,
+}
+
+// The stack pointers saved in a scope before allocating variable length arrays, which are
+// restored when leaving it since their lifetimes end 6.2.4. A label starts another save, since a
+// jump back to it leaves the scopes of the arrays following it.
+#[derive(Clone)]
+struct StackSaves<'c, 'a> {
+    // Identifies the scope, which tells the scopes left by a jump
+    scope: usize,
+    saves: Vec<(Value<'c, 'a>, Location<'c>)>,
+    // Whether the arrays allocated next are freed by restoring the last save
+    saved: bool,
+}
+
+impl StackSaves<'_, '_> {
+    fn new(scope: usize) -> Self {
+        Self {
+            scope,
+            saves: Vec::new(),
+            saved: false,
+        }
+    }
+}
+
+// The outermost stack pointer saved by a jump from `from` to a label defined in `scopes` after
+// `saves` saves of the innermost one
+fn jump_stack_save<'c, 'a>(
+    from: &[StackSaves<'c, 'a>],
+    scopes: &[usize],
+    saves: usize,
+) -> Option<Value<'c, 'a>> {
+    let common = from
+        .iter()
+        .zip(scopes)
+        .take_while(|(stack_saves, scope)| stack_saves.scope == **scope)
+        .count();
+    let mut left = from[common..]
+        .iter()
+        .flat_map(|stack_saves| &stack_saves.saves);
+    if common == scopes.len() {
+        // The label encloses the jump, so only the arrays following it are left. A forward jump
+        // into the scope of an array is not allowed 6.8.6.1, so it passes none of them.
+        let following = from[common - 1].saves.get(saves..).unwrap_or_default();
+        following.iter().chain(left).next()
+    } else {
+        left.next()
+    }
+    .map(|(pointer, _)| *pointer)
+}
+
+/// Builds the region of a function.
+///
+/// Blocks are kept in an arena until the whole body is lowered so that every block can be
+/// referenced as a successor before it is moved into the region.
+pub struct FunctionBuilder<'c, 'a> {
+    pub context: &'c Context,
+    blocks: &'a Arena<Block<'c>>,
+    entry: &'a Block<'c>,
+    // The entry block of the function, which differs from `entry` in regions
+    alloca_block: &'a Block<'c>,
+    block: &'a Block<'c>,
+    control_flow: ControlFlow,
+    memory_model: MemoryModel,
+    return_type: CType,
+    variadic: bool,
+    file_scope: FileScope,
+    // With the number of scopes enclosing the statement, whose stack is restored by a jump
+    break_targets: Vec<(&'a Block<'c>, usize)>,
+    continue_targets: Vec<(&'a Block<'c>, usize)>,
+    switches: Vec<Switch<'c, 'a>>,
+    // 6.2.1 Labels have function scope
+    labels: HashMap<EcoString, LabelTarget<'c, 'a>>,
+    // 6.2.1 Block scopes, innermost last
+    scopes: Vec<HashMap<EcoString, Variable<'c, 'a>>>,
+    // Of each block scope
+    stack_saves: Vec<StackSaves<'c, 'a>>,
+    // The number of block scopes entered so far, which identifies the next one
+    scope_count: usize,
+    // Whether functions of the standard library are lowered as builtins
+    builtin: bool,
+    // Whether the function is an `llvm.func`
+    llvm: bool,
+    // Locals whose address is taken with `&`, which are kept out of `memref`s
+    addressed: Rc<HashSet<EcoString>>,
+}
+
+impl<'c, 'a> FunctionBuilder<'c, 'a> {
+    pub fn new(
+        context: &'c Context,
+        blocks: &'a Arena<Block<'c>>,
+        control_flow: ControlFlow,
+        memory_model: MemoryModel,
+        return_type: CType,
+        variadic: bool,
+        file_scope: FileScope,
+    ) -> Self {
+        // The entry block is allocated first so that it becomes the first block of the region
+        let entry = blocks.alloc(Block::new(&[]));
+        Self {
+            context,
+            blocks,
+            entry,
+            alloca_block: entry,
+            block: entry,
+            control_flow,
+            memory_model,
+            return_type,
+            variadic,
+            file_scope,
+            break_targets: Vec::new(),
+            continue_targets: Vec::new(),
+            switches: Vec::new(),
+            labels: HashMap::new(),
+            scopes: vec![HashMap::new()],
+            stack_saves: vec![StackSaves::new(0)],
+            scope_count: 1,
+            builtin: true,
+            llvm: variadic,
+            addressed: Rc::new(HashSet::new()),
+        }
+    }
+
+    pub fn new_block(&self) -> &'a Block<'c> {
+        self.blocks.alloc(Block::new(&[]))
+    }
+
+    pub fn block(&self) -> &'a Block<'c> {
+        self.block
+    }
+
+    pub fn position_at_end(&mut self, block: &'a Block<'c>) {
+        self.block = block;
+    }
+
+    pub fn append_operation(&self, operation: Operation<'c>) -> OperationRef<'c, 'a> {
+        self.block.append_operation(operation)
+    }
+
+    /// Allocates a stack slot at the top of the entry block so that it dominates every use and
+    /// is allocated once even in a loop.
+    pub fn add_alloca(
+        &self,
+        r#type: &CType,
+        alignment: u64,
+        location: Location<'c>,
+    ) -> Value<'c, 'a> {
+        let context = self.context;
+        let i64_type = IntegerType::new(context, 64).into();
+        let one = self.alloca_block.insert_operation(
+            0,
+            arith::constant(context, IntegerAttribute::new(i64_type, 1).into(), location),
+        );
+        self.alloca_block
+            .insert_operation(
+                1,
+                llvm::alloca(
+                    context,
+                    one.result(0).unwrap().into(),
+                    llvm::r#type::pointer(context, 0),
+                    location,
+                    AllocaOptions::default()
+                        .elem_type(Some(TypeAttribute::new(r#type.memory_type(context))))
+                        .align(Some(IntegerAttribute::new(i64_type, alignment as i64))),
+                ),
+            )
+            .result(0)
+            .unwrap()
+            .into()
+    }
+
+    /// Allocates a local in the memory model of the function. Other than integers and arrays of
+    /// them, locals are `llvm.alloca` since they are not `memref` element types. So are integers
+    /// whose address is taken, so that the pointer is derived from the allocation.
+    pub fn add_local(
+        &self,
+        identifier: &str,
+        r#type: CType,
+        r#const: bool,
+        alignment: u64,
+        location: Location<'c>,
+    ) -> Variable<'c, 'a> {
+        let context = self.context;
+        match r#type.memref_type(context) {
+            Some(memref_type)
+                if self.memory_model == MemoryModel::Memref
+                    && (r#type.is_array() || !self.addressed.contains(identifier)) =>
+            {
+                let alignment =
+                    IntegerAttribute::new(IntegerType::new(context, 64).into(), alignment as i64);
+                let memref = self
+                    .alloca_block
+                    .insert_operation(
+                        0,
+                        memref::alloca(context, memref_type, &[], &[], Some(alignment), location),
+                    )
+                    .result(0)
+                    .unwrap()
+                    .into();
+                Variable::Memref {
+                    memref,
+                    r#type,
+                    r#const,
+                }
+            }
+            _ => Variable::Memory {
+                pointer: self.add_alloca(&r#type, alignment, location),
+                r#type,
+                r#const,
+            },
+        }
+    }
+
+    /// Allocates a variable length array where its declaration is reached, since its size is
+    /// only known there. Unlike `add_local`, it is allocated again in each iteration of a loop,
+    /// so the stack is saved before the first one of a scope or following a label, and restored
+    /// when leaving it.
+    pub fn add_variable_array(
+        &mut self,
+        r#type: CType,
+        r#const: bool,
+        alignment: u64,
+        location: Location<'c>,
+    ) -> Variable<'c, 'a> {
+        let context = self.context;
+        let pointer_type = llvm::r#type::pointer(context, 0);
+        let stack_saves = self.stack_saves.last_mut().unwrap();
+        if !stack_saves.saved {
+            let pointer = self
+                .block
+                .append_operation(
+                    OperationBuilder::new("llvm.intr.stacksave", location)
+                        .add_results(&[pointer_type])
+                        .build()
+                        .unwrap(),
+                )
+                .result(0)
+                .unwrap()
+                .into();
+            stack_saves.saves.push((pointer, location));
+            stack_saves.saved = true;
+        }
+        let alignment =
+            IntegerAttribute::new(IntegerType::new(context, 64).into(), alignment as i64);
+        match r#type.memref_type(context) {
+            Some(memref_type) if self.memory_model == MemoryModel::Memref => {
+                let mut sizes = Vec::new();
+                let mut element = &r#type;
+                while let CType::Array { element: inner, .. }
+                | CType::VariableArray { element: inner, .. } = element
+                {
+                    if let CType::VariableArray { length, .. } = element {
+                        let length = TypedValue {
+                            value: self.variable_length(length),
+                            r#type: CType::UNSIGNED_LONG,
+                        };
+                        sizes.push(add_index(self, length, location));
+                    }
+                    element = inner;
+                }
+                let memref = self
+                    .append_operation(memref::alloca(
+                        context,
+                        memref_type,
+                        &sizes,
+                        &[],
+                        Some(alignment),
+                        location,
+                    ))
+                    .result(0)
+                    .unwrap()
+                    .into();
+                Variable::Memref {
+                    memref,
+                    r#type,
+                    r#const,
+                }
+            }
+            _ => {
+                let size = self.add_size(&r#type, location);
+                let pointer = self
+                    .append_operation(llvm::alloca(
+                        context,
+                        size,
+                        pointer_type,
+                        location,
+                        AllocaOptions::default()
+                            .elem_type(Some(TypeAttribute::new(
+                                IntegerType::new(context, 8).into(),
+                            )))
+                            .align(Some(alignment)),
+                    ))
+                    .result(0)
+                    .unwrap()
+                    .into();
+                Variable::Memory {
+                    pointer,
+                    r#type,
+                    r#const,
+                }
+            }
+        }
+    }
+
+    /// The size of a type in bytes as an i64, which is computed from the lengths of variable
+    /// length arrays
+    pub fn add_size(&self, r#type: &CType, location: Location<'c>) -> Value<'c, 'a> {
+        let (element, length) = match r#type {
+            CType::VariableArray { element, length } => (element, self.variable_length(length)),
+            CType::Array { element, length } if element.is_variably_sized() => {
+                (element, self.add_constant(64, *length, location))
+            }
+            r#type => return self.add_constant(64, r#type.size(), location),
+        };
+        let size = self.add_size(element, location);
+        add_operation(
+            self,
+            OperationBuilder::new("arith.muli", location).add_operands(&[length, size]),
+        )
+    }
+
+    // The value of the hidden local declared by `add_variable_lengths`
+    fn variable_length(&self, length: &VariableLength) -> Value<'c, 'a> {
+        match self.lookup(&length.0) {
+            Some(Variable::Value { value, .. }) => value.value,
+            _ => unreachable!("the length '{}' is not evaluated", length.0),
+        }
+    }
+
+    /// Appends a terminator. Statements following it are unreachable, so they go to a new block.
+    pub fn terminate(&mut self, operation: Operation<'c>) {
+        self.append_operation(operation);
+        self.block = self.new_block();
+    }
+
+    /// Branches to `block` unless the current block is already terminated.
+    pub fn branch(&self, block: &'a Block<'c>, location: Location<'c>) {
+        if self.block.terminator().is_none() {
+            self.append_operation(cf::br(block, &[], location));
+        }
+    }
+
+    pub fn break_target(&self) -> Option<(&'a Block<'c>, usize)> {
+        self.break_targets.last().copied()
+    }
+
+    pub fn continue_target(&self) -> Option<(&'a Block<'c>, usize)> {
+        self.continue_targets.last().copied()
+    }
+
+    pub fn return_type(&self) -> &CType {
+        &self.return_type
+    }
+
+    pub fn is_builtin(&self) -> bool {
+        self.builtin
+    }
+
+    pub fn is_variadic(&self) -> bool {
+        self.variadic
+    }
+
+    /// An `llvm.func` returns with `llvm.return`.
+    pub fn r#return(&self, value: Option<Value<'c, 'a>>, location: Location<'c>) -> Operation<'c> {
+        if self.llvm {
+            llvm::r#return(value, location)
+        } else {
+            func::r#return(value.as_slice(), location)
+        }
+    }
+
+    pub fn file_scope(&self) -> &FileScope {
+        &self.file_scope
+    }
+
+    /// 6.6 Evaluates an integer constant expression, where the operands of `sizeof` are typed
+    /// against the declarations in scope
+    pub fn evaluate(&self, expression: &impl Evaluate) -> Result<IntegerConstant, ConstantError> {
+        expression.evaluate_with(&|operand, location| self.operand_type(operand, location))
+    }
+
+    /// The type of the operand of `sizeof`, or `None` if it is invalid
+    fn operand_type(&self, operand: &UnaryExpression, location: &lexer::Location) -> Option<CType> {
+        expression::expression_type(self, operand, location).ok()
+    }
+
+    /// Returns the block of a label, which may be defined later.
+    fn label_target(&mut self, identifier: &EcoString) -> &mut LabelTarget<'c, 'a> {
+        let blocks = self.blocks;
+        self.labels
+            .entry(identifier.clone())
+            .or_insert_with(|| LabelTarget {
+                block: blocks.alloc(Block::new(&[])),
+                definition: None,
+                forward_gotos: Vec::new(),
+            })
+    }
+
+    /// Defines a label at the current position. Returns `false` if it is already defined.
+    pub fn define_label(&mut self, identifier: &EcoString, location: Location<'c>) -> bool {
+        let scopes = self
+            .stack_saves
+            .iter()
+            .map(|stack_saves| stack_saves.scope)
+            .collect::<Vec<_>>();
+        let context = self.context;
+        let stack_saves = self.stack_saves.last_mut().unwrap();
+        stack_saves.saved = false;
+        let saves = stack_saves.saves.len();
+        let target = self.label_target(identifier);
+        if target.definition.is_some() {
+            return false;
+        }
+        let block = target.block;
+        for forward_goto in std::mem::take(&mut target.forward_gotos) {
+            let location = forward_goto.location.mlir_location(context);
+            if let Some(pointer) = jump_stack_save(&forward_goto.stack_saves, &scopes, saves) {
+                forward_goto
+                    .block
+                    .append_operation(stack_restore(pointer, location));
+            }
+            forward_goto
+                .block
+                .append_operation(cf::br(block, &[], location));
+        }
+        target.definition = Some((scopes, saves));
+        self.branch(block, location);
+        self.position_at_end(block);
+        true
+    }
+
+    /// 6.8.6.1 Jumps to a label, freeing the variable length arrays of the scopes left
+    pub fn add_goto(&mut self, identifier: &EcoString, location: &lexer::Location) {
+        let mlir_location = location.mlir_location(self.context);
+        let blocks = self.blocks;
+        let stack_saves = self.stack_saves.clone();
+        let target = self.label_target(identifier);
+        let (block, pointer) = match &target.definition {
+            Some((scopes, saves)) => (target.block, jump_stack_save(&stack_saves, scopes, *saves)),
+            None => {
+                let block = blocks.alloc(Block::new(&[]));
+                target.forward_gotos.push(ForwardGoto {
+                    block,
+                    stack_saves,
+                    location: location.clone(),
+                });
+                (&*block, None)
+            }
+        };
+        if let Some(pointer) = pointer {
+            self.append_operation(stack_restore(pointer, mlir_location));
+        }
+        self.terminate(cf::br(block, &[], mlir_location));
+    }
+
+    /// Returns the label first used without a definition, if any.
+    fn undefined_label(&self) -> Option<(&EcoString, &lexer::Location)> {
+        self.labels
+            .iter()
+            .filter(|(_, target)| target.definition.is_none())
+            .filter_map(|(identifier, target)| {
+                Some((identifier, &target.forward_gotos.first()?.location))
+            })
+            .min_by_key(|(_, location)| (location.line, location.column))
+    }
+
+    pub fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+        self.stack_saves.push(StackSaves::new(self.scope_count));
+        self.scope_count += 1;
+    }
+
+    pub fn pop_scope(&mut self) {
+        self.scopes.pop();
+        let stack_saves = self.stack_saves.pop().unwrap();
+        if let Some((pointer, location)) = stack_saves.saves.first() {
+            if self.block.terminator().is_none() {
+                self.append_operation(stack_restore(*pointer, *location));
+            }
+        }
+    }
+
+    /// Frees the variable length arrays of the scopes left by a jump to a statement enclosed by
+    /// `depth` scopes, by restoring the outermost stack pointer saved in them.
+    pub fn restore_stack(&self, depth: usize, location: Location<'c>) {
+        if let Some((pointer, _)) = self.stack_saves[depth..]
+            .iter()
+            .flat_map(|stack_saves| &stack_saves.saves)
+            .next()
+        {
+            self.append_operation(stack_restore(*pointer, location));
+        }
+    }
+
+    /// Returns `false` if the identifier is already declared in the innermost scope.
+    pub fn declare(&mut self, identifier: EcoString, variable: Variable<'c, 'a>) -> bool {
+        let scope = self.scopes.last_mut().unwrap();
+        if scope.contains_key(&identifier) {
+            return false;
+        }
+        scope.insert(identifier, variable);
+        true
+    }
+
+    pub fn lookup(&self, identifier: &str) -> Option<&Variable<'c, 'a>> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(identifier))
+    }
+
+    /// Rebinds a local kept as an SSA value.
+    pub fn assign(&mut self, identifier: &str, value: TypedValue<'c, 'a>) {
+        if let Some(Variable::Value { value: old, .. }) = self
+            .scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(identifier))
+        {
+            *old = value;
+        }
+    }
+
+    /// Locals kept as SSA values that are visible from the current scope
+    pub fn value_variables(&self) -> Vec<(EcoString, TypedValue<'c, 'a>)> {
+        let mut variables = HashMap::new();
+        for scope in &self.scopes {
+            for (identifier, variable) in scope {
+                variables.insert(identifier.clone(), variable);
+            }
+        }
+        let mut variables = variables
+            .into_iter()
+            .filter_map(|(identifier, variable)| match variable {
+                Variable::Value { value, .. } => Some((identifier, value.clone())),
+                Variable::Memory { .. } | Variable::Memref { .. } => None,
+            })
+            .collect::<Vec<_>>();
+        // For a deterministic output
+        variables.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+        variables
+    }
+
+    pub fn load(&self, lvalue: &Lvalue<'c, 'a>, location: Location<'c>) -> TypedValue<'c, 'a> {
+        let context = self.context;
+        match lvalue {
+            Lvalue::Memory {
+                pointer,
+                r#type,
+                alignment,
+                ..
+            } => {
+                let value = self
+                    .append_operation(llvm::load(
+                        context,
+                        *pointer,
+                        r#type.memory_type(context),
+                        location,
+                        self.load_store_options(*alignment),
+                    ))
+                    .result(0)
+                    .unwrap()
+                    .into();
+                TypedValue {
+                    value: self.value_from_memory(value, r#type, location),
+                    r#type: r#type.clone(),
+                }
+            }
+            Lvalue::Variable { identifier, .. } => match self.lookup(identifier) {
+                Some(Variable::Value { value, .. }) => value.clone(),
+                _ => unreachable!("SSA variable '{}' is not in scope", identifier),
+            },
+            // A subarray is accessed through its address, since it has no `memref` form
+            Lvalue::Memref { r#const, .. } if lvalue.r#type().is_array() => self.load(
+                &Lvalue::Memory {
+                    pointer: self.address(lvalue, location),
+                    r#type: lvalue.r#type().clone(),
+                    r#const: *r#const,
+                    alignment: None,
+                },
+                location,
+            ),
+            Lvalue::Memref {
+                memref, indices, ..
+            } => TypedValue {
+                value: self
+                    .append_operation(memref::load(*memref, indices, location))
+                    .result(0)
+                    .unwrap()
+                    .into(),
+                r#type: lvalue.r#type().clone(),
+            },
+            // The bits are shifted to the top of the loaded bytes, and then back to the bottom
+            // with the sign or zeros
+            Lvalue::BitField {
+                pointer,
+                r#type,
+                bit_field,
+                alignment,
+                ..
+            } => {
+                let unit = bit_field_unit(bit_field);
+                let (left, right) = bit_field_read_shifts(bit_field);
+                let bytes = self.load_bytes(*pointer, unit, *alignment, location);
+                let value = add_operation(
+                    self,
+                    OperationBuilder::new("arith.shli", location)
+                        .add_operands(&[bytes, self.add_constant(unit, left, location)]),
+                );
+                let value = add_operation(
+                    self,
+                    OperationBuilder::new(
+                        if r#type.is_signed() {
+                            "arith.shrsi"
+                        } else {
+                            "arith.shrui"
+                        },
+                        location,
+                    )
+                    .add_operands(&[value, self.add_constant(unit, right, location)]),
+                );
+                TypedValue {
+                    value: self.resize(value, unit, r#type, location),
+                    r#type: r#type.clone(),
+                }
+            }
+        }
+    }
+
+    // Converts a value loaded with the `memory_type` to the `mlir_type`
+    fn value_from_memory(
+        &self,
+        value: Value<'c, 'a>,
+        r#type: &CType,
+        location: Location<'c>,
+    ) -> Value<'c, 'a> {
+        let context = self.context;
+        let CType::Complex(kind) = r#type else {
+            return value;
+        };
+        let part_type = CType::Floating(*kind).mlir_type(context);
+        let [real, imaginary] = [0, 1].map(|index| {
+            self.append_operation(llvm::extract_value(
+                context,
+                value,
+                DenseI64ArrayAttribute::new(context, &[index]),
+                part_type,
+                location,
+            ))
+            .result(0)
+            .unwrap()
+            .into()
+        });
+        add_complex(self, real, imaginary, r#type, location)
+    }
+
+    // Converts a value to the `memory_type` to store it
+    fn value_to_memory(
+        &self,
+        value: Value<'c, 'a>,
+        r#type: &CType,
+        location: Location<'c>,
+    ) -> Value<'c, 'a> {
+        let context = self.context;
+        let CType::Complex(kind) = r#type else {
+            return value;
+        };
+        let mut parts = self
+            .append_operation(llvm::undef(r#type.memory_type(context), location))
+            .result(0)
+            .unwrap()
+            .into();
+        for (index, imaginary) in [false, true].into_iter().enumerate() {
+            let part = add_complex_part(self, value, *kind, imaginary, location);
+            parts = self
+                .append_operation(llvm::insert_value(
+                    context,
+                    parts,
+                    DenseI64ArrayAttribute::new(context, &[index as i64]),
+                    part,
+                    location,
+                ))
+                .result(0)
+                .unwrap()
+                .into();
+        }
+        parts
+    }
+
+    // The options of `llvm.load` and `llvm.store` with an explicit alignment if any
+    fn load_store_options(&self, alignment: Option<u64>) -> LoadStoreOptions<'c> {
+        LoadStoreOptions::default().align(alignment.map(|alignment| {
+            IntegerAttribute::new(IntegerType::new(self.context, 64).into(), alignment as i64)
+        }))
+    }
+
+    /// Loads the bytes holding a bit-field as an integer of `width` bits
+    fn load_bytes(
+        &self,
+        pointer: Value<'c, 'a>,
+        width: u64,
+        alignment: u64,
+        location: Location<'c>,
+    ) -> Value<'c, 'a> {
+        self.append_operation(llvm::load(
+            self.context,
+            pointer,
+            IntegerType::new(self.context, width as u32).into(),
+            location,
+            self.load_store_options(Some(alignment)),
+        ))
+        .result(0)
+        .unwrap()
+        .into()
+    }
+
+    fn add_constant(&self, width: u64, value: u64, location: Location<'c>) -> Value<'c, 'a> {
+        let r#type = IntegerType::new(self.context, width as u32).into();
+        self.append_operation(arith::constant(
+            self.context,
+            IntegerAttribute::new(r#type, value as i64).into(),
+            location,
+        ))
+        .result(0)
+        .unwrap()
+        .into()
+    }
+
+    // Converts an integer of `width` bits to `r#type`, extending it by the signedness of the type
+    fn resize(
+        &self,
+        value: Value<'c, 'a>,
+        width: u64,
+        r#type: &CType,
+        location: Location<'c>,
+    ) -> Value<'c, 'a> {
+        let mlir_type = r#type.mlir_type(self.context);
+        let operation = match width.cmp(&(r#type.width() as u64)) {
+            Ordering::Equal => return value,
+            Ordering::Less if r#type.is_signed() => arith::extsi(value, mlir_type, location),
+            Ordering::Less => arith::extui(value, mlir_type, location),
+            Ordering::Greater => arith::trunci(value, mlir_type, location),
+        };
+        self.append_operation(operation).result(0).unwrap().into()
+    }
+
+    /// `value` must already be converted to the type of the lvalue.
+    pub fn store(&mut self, lvalue: &Lvalue<'c, 'a>, value: Value<'c, 'a>, location: Location<'c>) {
+        match lvalue {
+            Lvalue::Memory {
+                pointer,
+                r#type,
+                alignment,
+                ..
+            } => {
+                self.append_operation(llvm::store(
+                    self.context,
+                    self.value_to_memory(value, r#type, location),
+                    *pointer,
+                    location,
+                    self.load_store_options(*alignment),
+                ));
+            }
+            Lvalue::Variable {
+                identifier, r#type, ..
+            } => self.assign(
+                identifier,
+                TypedValue {
+                    value,
+                    r#type: r#type.clone(),
+                },
+            ),
+            Lvalue::Memref { r#const, .. } if lvalue.r#type().is_array() => {
+                let pointer = self.address(lvalue, location);
+                self.store(
+                    &Lvalue::Memory {
+                        pointer,
+                        r#type: lvalue.r#type().clone(),
+                        r#const: *r#const,
+                        alignment: None,
+                    },
+                    value,
+                    location,
+                );
+            }
+            Lvalue::Memref {
+                memref, indices, ..
+            } => {
+                self.append_operation(memref::store(value, *memref, indices, location));
+            }
+            // The bits below and above the bit-field are kept. Every shift is less than the
+            // width of the bytes, since shifting by it is poison.
+            Lvalue::BitField {
+                pointer,
+                r#type,
+                bit_field,
+                alignment,
+                ..
+            } => {
+                let context = self.context;
+                let unit = bit_field_unit(bit_field);
+                let BitField { width, bit_offset } = *bit_field;
+                let bytes = self.load_bytes(*pointer, unit, *alignment, location);
+                let value = match (r#type.width() as u64).cmp(&unit) {
+                    Ordering::Equal => value,
+                    Ordering::Less => self
+                        .append_operation(arith::extui(
+                            value,
+                            IntegerType::new(context, unit as u32).into(),
+                            location,
+                        ))
+                        .result(0)
+                        .unwrap()
+                        .into(),
+                    Ordering::Greater => self
+                        .append_operation(arith::trunci(
+                            value,
+                            IntegerType::new(context, unit as u32).into(),
+                            location,
+                        ))
+                        .result(0)
+                        .unwrap()
+                        .into(),
+                };
+                let (left, right) = bit_field_write_shifts(bit_field);
+                let value = add_operation(
+                    self,
+                    OperationBuilder::new("arith.shli", location)
+                        .add_operands(&[value, self.add_constant(unit, left, location)]),
+                );
+                let mut value = add_operation(
+                    self,
+                    OperationBuilder::new("arith.shrui", location)
+                        .add_operands(&[value, self.add_constant(unit, right, location)]),
+                );
+                if bit_offset > 0 {
+                    let low = add_operation(
+                        self,
+                        OperationBuilder::new("arith.andi", location).add_operands(&[
+                            bytes,
+                            self.add_constant(unit, (1 << bit_offset) - 1, location),
+                        ]),
+                    );
+                    value = add_operation(
+                        self,
+                        OperationBuilder::new("arith.ori", location).add_operands(&[value, low]),
+                    );
+                }
+                if bit_offset + width < unit {
+                    let shift = self.add_constant(unit, bit_offset + width, location);
+                    let high = add_operation(
+                        self,
+                        OperationBuilder::new("arith.shrui", location)
+                            .add_operands(&[bytes, shift]),
+                    );
+                    let high = add_operation(
+                        self,
+                        OperationBuilder::new("arith.shli", location).add_operands(&[high, shift]),
+                    );
+                    value = add_operation(
+                        self,
+                        OperationBuilder::new("arith.ori", location).add_operands(&[value, high]),
+                    );
+                }
+                self.append_operation(llvm::store(
+                    context,
+                    value,
+                    *pointer,
+                    location,
+                    self.load_store_options(Some(*alignment)),
+                ));
+            }
+        }
+    }
+
+    /// Returns the address of an object in memory as an `!llvm.ptr`.
+    ///
+    /// The aligned pointer of a `memref` is offset by the indices. This relies on every `memref`
+    /// having the type of `CType::memref_type`, whose layout is the identity with offset 0: those
+    /// are allocated with it, and a `memref` parameter only takes a whole array of the same type.
+    /// The pointer is made with `llvm.inttoptr`, which loses the provenance of the allocation, so
+    /// integers whose address is taken are not kept as `memref`s.
+    pub fn address(&self, lvalue: &Lvalue<'c, 'a>, location: Location<'c>) -> Value<'c, 'a> {
+        let context = self.context;
+        match lvalue {
+            Lvalue::Memory { pointer, .. } => *pointer,
+            Lvalue::Variable { identifier, .. } => {
+                unreachable!("SSA variable '{}' has no address", identifier)
+            }
+            Lvalue::BitField { .. } => unreachable!("a bit-field has no address"),
+            Lvalue::Memref {
+                memref,
+                memref_type,
+                indices,
+                ..
+            } => {
+                debug_assert_eq!(
+                    memref.r#type(),
+                    memref_type.memref_type(context).unwrap().into(),
+                    "a memref of '{}' must have the identity layout",
+                    memref_type
+                );
+                let index_type = Type::index(context);
+                let mut address = self
+                    .append_operation(
+                        OperationBuilder::new("memref.extract_aligned_pointer_as_index", location)
+                            .add_operands(&[*memref])
+                            .add_results(&[index_type])
+                            .build()
+                            .unwrap(),
+                    )
+                    .result(0)
+                    .unwrap()
+                    .into();
+                let mut r#type = memref_type;
+                for index in indices {
+                    let (CType::Array { element, .. } | CType::VariableArray { element, .. }) =
+                        r#type
+                    else {
+                        unreachable!("{} is indexed beyond its rank", memref_type)
+                    };
+                    let size = if element.is_variably_sized() {
+                        let size = TypedValue {
+                            value: self.add_size(element, location),
+                            r#type: CType::UNSIGNED_LONG,
+                        };
+                        add_index(self, size, location)
+                    } else {
+                        self.append_operation(arith::constant(
+                            context,
+                            IntegerAttribute::new(index_type, element.size() as i64).into(),
+                            location,
+                        ))
+                        .result(0)
+                        .unwrap()
+                        .into()
+                    };
+                    let offset = add_operation(
+                        self,
+                        OperationBuilder::new("arith.muli", location).add_operands(&[*index, size]),
+                    );
+                    address = add_operation(
+                        self,
+                        OperationBuilder::new("arith.addi", location)
+                            .add_operands(&[address, offset]),
+                    );
+                    r#type = element;
+                }
+                let address = self
+                    .append_operation(
+                        OperationBuilder::new("arith.index_cast", location)
+                            .add_operands(&[address])
+                            .add_results(&[IntegerType::new(context, 64).into()])
+                            .build()
+                            .unwrap(),
+                    )
+                    .result(0)
+                    .unwrap()
+                    .into();
+                self.append_operation(
+                    OperationBuilder::new("llvm.inttoptr", location)
+                        .add_operands(&[address])
+                        .add_results(&[llvm::r#type::pointer(context, 0)])
+                        .build()
+                        .unwrap(),
+                )
+                .result(0)
+                .unwrap()
+                .into()
+            }
+        }
+    }
+
+    /// Lowers the body of a single-block region such as that of `scf.if`.
+    ///
+    /// The body gets its own arena and builder so that the region can be moved into the operation
+    /// while the enclosing function is still being built. Locals are visible from the region, but
+    /// SSA values defined inside it must be passed out through its terminator.
+    pub fn add_region(
+        &self,
+        arguments: &[(Type<'c>, Location<'c>)],
+        location: Location<'c>,
+        add_body: impl for<'b> FnOnce(&mut FunctionBuilder<'c, 'b>) -> Result<(), CompileError>,
+    ) -> Result<Region<'c>, CompileError> {
+        let blocks = Arena::new();
+        {
+            let mut builder = FunctionBuilder::new(
+                self.context,
+                &blocks,
+                self.control_flow,
+                self.memory_model,
+                self.return_type.clone(),
+                self.variadic,
+                self.file_scope.clone(),
+            );
+            for (r#type, location) in arguments {
+                builder.entry.add_argument(*r#type, *location);
+            }
+            builder.alloca_block = self.alloca_block;
+            builder.scopes = self.scopes.clone();
+            builder.stack_saves = self
+                .stack_saves
+                .iter()
+                .map(|stack_saves| StackSaves::new(stack_saves.scope))
+                .collect();
+            builder.scope_count = self.scope_count;
+            builder.builtin = self.builtin;
+            builder.llvm = self.llvm;
+            builder.addressed = self.addressed.clone();
+            add_body(&mut builder)?;
+        }
+        Ok(FunctionBuilder::finish(blocks, location))
+    }
+
+    fn finish(blocks: Arena<Block<'c>>, location: Location<'c>) -> Region<'c> {
+        let region = Region::new();
+        for block in blocks.into_vec() {
+            if block.terminator().is_none() {
+                block.append_operation(llvm::unreachable(location));
+            }
+            region.append_block(block);
+        }
+        region
+    }
+}
+
+fn parameter_mlir_types<'c>(context: &'c Context, parameters: &[CType]) -> Vec<Type<'c>> {
+    parameters
+        .iter()
+        .map(|parameter| match parameter {
+            // Only in the memref memory model, since arrays are adjusted otherwise
+            CType::Array { .. } => parameter.memref_type(context).unwrap().into(),
+            CType::VaList => llvm::r#type::pointer(context, 0),
+            parameter => parameter.mlir_type(context),
+        })
+        .collect()
+}
+
+impl CType {
+    pub fn mlir_type<'c>(&self, context: &'c Context) -> Type<'c> {
+        match self {
+            CType::Void => unreachable!("void is not an object type"),
+            CType::Integer { kind, .. } => IntegerType::new(context, kind.width()).into(),
+            CType::Floating(FloatingKind::Float) => Type::float32(context),
+            CType::Floating(FloatingKind::Double) => Type::float64(context),
+            CType::Complex(kind) => Type::parse(
+                context,
+                &format!("complex<{}>", CType::Floating(*kind).mlir_type(context)),
+            )
+            .unwrap(),
+            // `func.func` cannot be variadic
+            CType::Function { variadic: true, .. } => self.llvm_function_type(context),
+            CType::Function {
+                return_type,
+                parameters,
+                ..
+            } => {
+                let parameters = parameter_mlir_types(context, parameters);
+                if return_type.is_void() {
+                    FunctionType::new(context, &parameters, &[]).into()
+                } else {
+                    FunctionType::new(context, &parameters, &[return_type.mlir_type(context)])
+                        .into()
+                }
+            }
+            // Pointers are opaque
+            CType::Pointer { .. } => llvm::r#type::pointer(context, 0),
+            // The length is checked by `array_type`
+            CType::Array { element, length } => {
+                llvm::r#type::array(element.memory_type(context), *length as u32)
+            }
+            // Only accessed through pointers to bytes
+            CType::VariableArray { .. } => unreachable!("{} has no static type", self),
+            // struct __va_list_tag { unsigned gp_offset, fp_offset; void *overflow_arg_area,
+            // *reg_save_area; } va_list[1];
+            CType::VaList => {
+                let int = IntegerType::new(context, 32).into();
+                let pointer = llvm::r#type::pointer(context, 0);
+                llvm::r#type::array(
+                    llvm::r#type::r#struct(context, &[int, int, pointer, pointer], false),
+                    1,
+                )
+            }
+            // Only in declarations of functions that are never called with it
+            CType::Record(record) if record.layout().is_none() => {
+                llvm::r#type::r#struct(context, &[], true)
+            }
+            CType::Record(record) => {
+                let layout = record.layout().unwrap();
+                let fields = record_fields(record.kind, &layout)
+                    .into_iter()
+                    .map(|field| match field {
+                        Field::Padding(size) => {
+                            llvm::r#type::array(IntegerType::new(context, 8).into(), size as u32)
+                        }
+                        Field::Member(index) => layout.members[index].r#type.memory_type(context),
+                    })
+                    .collect::<Vec<_>>();
+                llvm::r#type::r#struct(context, &fields, true)
+            }
+        }
+    }
+
+    /// The type of an `llvm.func`, and of calls through pointers to functions
+    pub fn llvm_function_type<'c>(&self, context: &'c Context) -> Type<'c> {
+        let CType::Function {
+            return_type,
+            parameters,
+            variadic,
+        } = self
+        else {
+            unreachable!("{} is not a function type", self)
+        };
+        let return_type = if return_type.is_void() {
+            llvm::r#type::void(context)
+        } else {
+            return_type.mlir_type(context)
+        };
+        llvm::r#type::function(
+            return_type,
+            &parameter_mlir_types(context, parameters),
+            *variadic,
+        )
+    }
+
+    /// The type of an object in memory, which is the `mlir_type` except for a complex type. A
+    /// `complex` has no LLVM form, so it is stored as an array of its real and imaginary parts.
+    pub fn memory_type<'c>(&self, context: &'c Context) -> Type<'c> {
+        match self {
+            CType::Complex(kind) => {
+                llvm::r#type::array(CType::Floating(*kind).mlir_type(context), 2)
+            }
+            _ => self.mlir_type(context),
+        }
+    }
+
+    /// The `memref` of an integer or an array of integers, shaped by the lengths of the arrays.
+    /// Those of variable length arrays are dynamic.
+    pub fn memref_type<'c>(&self, context: &'c Context) -> Option<MemRefType<'c>> {
+        let mut shape = Vec::new();
+        let mut r#type = self;
+        loop {
+            r#type = match r#type {
+                CType::Array { element, length } => {
+                    shape.push(*length as i64);
+                    element
+                }
+                CType::VariableArray { element, .. } => {
+                    // `ShapedType::kDynamic`
+                    shape.push(i64::MIN);
+                    element
+                }
+                _ => break,
+            };
+        }
+        r#type
+            .is_integer()
+            .then(|| MemRefType::new(r#type.mlir_type(context), &shape, None, None))
+    }
+}
+
+// 6.3.1.3
+fn add_conversion<'c, 'a>(
+    builder: &FunctionBuilder<'c, 'a>,
+    value: TypedValue<'c, 'a>,
+    r#type: &CType,
+    location: Location<'c>,
+) -> Value<'c, 'a> {
+    let context = builder.context;
+    // Pointers are opaque, so converting between pointer types is a no-op
+    if value.r#type.is_pointer() && r#type.is_pointer() {
+        return value.value;
+    }
+    // 6.3.2.3 Between pointers and integers through the address as an i64
+    if value.r#type.is_pointer() && *r#type != CType::BOOL {
+        let address = TypedValue {
+            value: add_address(builder, value.value, location),
+            r#type: CType::UNSIGNED_LONG,
+        };
+        return add_conversion(builder, address, r#type, location);
+    }
+    if r#type.is_pointer() {
+        let address = add_conversion(builder, value, &CType::UNSIGNED_LONG, location);
+        return builder
+            .append_operation(
+                OperationBuilder::new("llvm.inttoptr", location)
+                    .add_operands(&[address])
+                    .add_results(&[llvm::r#type::pointer(context, 0)])
+                    .build()
+                    .unwrap(),
+            )
+            .result(0)
+            .unwrap()
+            .into();
+    }
+    // 6.3.1.2 A scalar is 1 as a `_Bool` unless it compares equal to 0
+    if *r#type == CType::BOOL {
+        let condition = add_condition(builder, value, location);
+        return builder
+            .append_operation(arith::extui(condition, r#type.mlir_type(context), location))
+            .result(0)
+            .unwrap()
+            .into();
+    }
+    match (&value.r#type, r#type) {
+        (CType::Complex(from), CType::Complex(to)) if from == to => return value.value,
+        // 6.3.1.6 Each part is converted
+        (CType::Complex(from), CType::Complex(to)) => {
+            let [real, imaginary] = [false, true].map(|imaginary| {
+                let part = TypedValue {
+                    value: add_complex_part(builder, value.value, *from, imaginary, location),
+                    r#type: CType::Floating(*from),
+                };
+                add_conversion(builder, part, &CType::Floating(*to), location)
+            });
+            return add_complex(builder, real, imaginary, r#type, location);
+        }
+        // 6.3.1.7 The imaginary part is discarded
+        (CType::Complex(kind), _) => {
+            let real = TypedValue {
+                value: add_complex_part(builder, value.value, *kind, false, location),
+                r#type: CType::Floating(*kind),
+            };
+            return add_conversion(builder, real, r#type, location);
+        }
+        // 6.3.1.7 The imaginary part is zero
+        (_, CType::Complex(kind)) => {
+            let real = add_conversion(builder, value, &CType::Floating(*kind), location);
+            let imaginary = add_zero(builder, &CType::Floating(*kind), location);
+            return add_complex(builder, real, imaginary, r#type, location);
+        }
+        _ => {}
+    }
+    let mlir_type = r#type.mlir_type(context);
+    let operation = match (&value.r#type, r#type) {
+        // 6.3.1.5
+        (CType::Floating(from), CType::Floating(to)) => match from.cmp(to) {
+            Ordering::Equal => return value.value,
+            Ordering::Less => arith::extf(value.value, mlir_type, location),
+            Ordering::Greater => OperationBuilder::new("arith.truncf", location)
+                .add_operands(&[value.value])
+                .add_results(&[mlir_type])
+                .build()
+                .unwrap(),
+        },
+        // 6.3.1.4 Truncated toward zero
+        (CType::Floating(_), _) if r#type.is_signed() => {
+            arith::fptosi(value.value, mlir_type, location)
+        }
+        (CType::Floating(_), _) => arith::fptoui(value.value, mlir_type, location),
+        (_, CType::Floating(_)) if value.r#type.is_signed() => {
+            arith::sitofp(value.value, mlir_type, location)
+        }
+        (_, CType::Floating(_)) => arith::uitofp(value.value, mlir_type, location),
+        _ => match value.r#type.width().cmp(&r#type.width()) {
+            Ordering::Equal => return value.value,
+            Ordering::Less if value.r#type.is_signed() => {
+                arith::extsi(value.value, mlir_type, location)
+            }
+            Ordering::Less => arith::extui(value.value, mlir_type, location),
+            Ordering::Greater => arith::trunci(value.value, mlir_type, location),
+        },
+    };
+    builder
+        .append_operation(operation)
+        .result(0)
+        .unwrap()
+        .into()
+}
+
+// A field of the `!llvm.struct` of a record
+enum Field {
+    Padding(u64),
+    // The index of the member
+    Member(usize),
+}
+
+// 6.7.2.1 Members of a structure at their offsets with padding between them in a packed struct,
+// so that the layout is kept under `_Alignas` and `#pragma pack`. A union is a byte array as
+// large as it, where all members are at 0.
+fn record_fields(kind: RecordKind, layout: &Layout) -> Vec<Field> {
+    if kind == RecordKind::Union {
+        return vec![Field::Padding(layout.size)];
+    }
+    let mut fields = Vec::new();
+    let mut end = 0;
+    // Bit-fields are accessed by their bytes, which are left as padding
+    for (index, member) in layout.members.iter().enumerate() {
+        if member.bit_field.is_some() {
+            continue;
+        }
+        if member.offset > end {
+            fields.push(Field::Padding(member.offset - end));
+        }
+        fields.push(Field::Member(index));
+        end = member.offset + member.r#type.size();
+    }
+    if layout.size > end {
+        fields.push(Field::Padding(layout.size - end));
+    }
+    fields
+}
+
+// The width of the whole bytes holding a bit-field, which are loaded and stored together
+fn bit_field_unit(bit_field: &BitField) -> u64 {
+    (bit_field.bit_offset + bit_field.width).div_ceil(8) * 8
+}
+
+// The left shift moving a bit-field to the top of its unit, and the right one moving it back to
+// the bottom when it is read
+fn bit_field_read_shifts(bit_field: &BitField) -> (u64, u64) {
+    let unit = bit_field_unit(bit_field);
+    (
+        unit - bit_field.bit_offset - bit_field.width,
+        unit - bit_field.width,
+    )
+}
+
+// The left shift dropping the bits of a value above the width of a bit-field, and the right one
+// moving it to the bit-field when it is written
+fn bit_field_write_shifts(bit_field: &BitField) -> (u64, u64) {
+    let unit = bit_field_unit(bit_field);
+    (
+        unit - bit_field.width,
+        unit - bit_field.width - bit_field.bit_offset,
+    )
+}
+
+// An integer converted to an `index` for `memref` subscripts
+fn add_index<'c, 'a>(
+    builder: &FunctionBuilder<'c, 'a>,
+    value: TypedValue<'c, 'a>,
+    location: Location<'c>,
+) -> Value<'c, 'a> {
+    let value = add_conversion(builder, value, &CType::LONG, location);
+    builder
+        .append_operation(
+            OperationBuilder::new("arith.index_cast", location)
+                .add_operands(&[value])
+                .add_results(&[Type::index(builder.context)])
+                .build()
+                .unwrap(),
+        )
+        .result(0)
+        .unwrap()
+        .into()
+}
+
+// The address of a pointer as an i64 for comparisons and differences
+fn add_address<'c, 'a>(
+    builder: &FunctionBuilder<'c, 'a>,
+    pointer: Value<'c, 'a>,
+    location: Location<'c>,
+) -> Value<'c, 'a> {
+    builder
+        .append_operation(
+            OperationBuilder::new("llvm.ptrtoint", location)
+                .add_operands(&[pointer])
+                .add_results(&[IntegerType::new(builder.context, 64).into()])
+                .build()
+                .unwrap(),
+        )
+        .result(0)
+        .unwrap()
+        .into()
+}
+
+// 6.8.4.1 Compares the scalar against 0
+fn add_condition<'c, 'a>(
+    builder: &FunctionBuilder<'c, 'a>,
+    value: TypedValue<'c, 'a>,
+    location: Location<'c>,
+) -> Value<'c, 'a> {
+    let context = builder.context;
+    // 6.3.2.3 A null pointer compares equal to 0
+    let value = if value.r#type.is_pointer() {
+        TypedValue {
+            value: add_address(builder, value.value, location),
+            r#type: CType::UNSIGNED_LONG,
+        }
+    } else {
+        value
+    };
+    let zero = add_zero(builder, &value.r#type, location);
+    let operation = match value.r#type {
+        // A NaN is unequal to 0
+        CType::Floating(_) => arith::cmpf(context, CmpfPredicate::Une, value.value, zero, location),
+        CType::Complex(_) => OperationBuilder::new("complex.neq", location)
+            .add_operands(&[value.value, zero])
+            .add_results(&[IntegerType::new(context, 1).into()])
+            .build()
+            .unwrap(),
+        _ => arith::cmpi(context, CmpiPredicate::Ne, value.value, zero, location),
+    };
+    builder
+        .append_operation(operation)
+        .result(0)
+        .unwrap()
+        .into()
+}
+
+// Restores the stack pointer saved by `llvm.intr.stacksave`
+fn stack_restore<'c>(pointer: Value<'c, '_>, location: Location<'c>) -> Operation<'c> {
+    OperationBuilder::new("llvm.intr.stackrestore", location)
+        .add_operands(&[pointer])
+        .build()
+        .unwrap()
+}
+
+// The value 0 of an arithmetic or pointer type
+fn add_zero<'c, 'a>(
+    builder: &FunctionBuilder<'c, 'a>,
+    r#type: &CType,
+    location: Location<'c>,
+) -> Value<'c, 'a> {
+    let context = builder.context;
+    let mlir_type = r#type.mlir_type(context);
+    let operation = match r#type {
+        CType::Floating(_) => arith::constant(
+            context,
+            FloatAttribute::new(context, mlir_type, 0.0).into(),
+            location,
+        ),
+        CType::Complex(kind) => {
+            let zero = add_zero(builder, &CType::Floating(*kind), location);
+            return add_complex(builder, zero, zero, r#type, location);
+        }
+        CType::Pointer { .. } => llvm::zero(mlir_type, location),
+        _ => arith::constant(
+            context,
+            IntegerAttribute::new(mlir_type, 0).into(),
+            location,
+        ),
+    };
+    builder
+        .append_operation(operation)
+        .result(0)
+        .unwrap()
+        .into()
+}
+
+// 6.2.5 A value of the complex type `r#type` from its parts
+fn add_complex<'c, 'a>(
+    builder: &FunctionBuilder<'c, 'a>,
+    real: Value<'c, 'a>,
+    imaginary: Value<'c, 'a>,
+    r#type: &CType,
+    location: Location<'c>,
+) -> Value<'c, 'a> {
+    builder
+        .append_operation(
+            OperationBuilder::new("complex.create", location)
+                .add_operands(&[real, imaginary])
+                .add_results(&[r#type.mlir_type(builder.context)])
+                .build()
+                .unwrap(),
+        )
+        .result(0)
+        .unwrap()
+        .into()
+}
+
+// 7.3.9 The real or imaginary part of a complex value whose parts are of `kind`
+fn add_complex_part<'c, 'a>(
+    builder: &FunctionBuilder<'c, 'a>,
+    value: Value<'c, 'a>,
+    kind: FloatingKind,
+    imaginary: bool,
+    location: Location<'c>,
+) -> Value<'c, 'a> {
+    builder
+        .append_operation(
+            OperationBuilder::new(
+                if imaginary {
+                    "complex.im"
+                } else {
+                    "complex.re"
+                },
+                location,
+            )
+            .add_operands(&[value])
+            .add_results(&[CType::Floating(kind).mlir_type(builder.context)])
+            .build()
+            .unwrap(),
+        )
+        .result(0)
+        .unwrap()
+        .into()
+}
+
+fn add_operation<'c, 'a>(
+    builder: &FunctionBuilder<'c, 'a>,
+    operation: OperationBuilder<'c>,
+) -> Value<'c, 'a> {
+    builder
+        .append_operation(operation.enable_result_type_inference().build().unwrap())
+        .result(0)
+        .unwrap()
+        .into()
+}
+
+impl AddModule for TranslationUnit {
+    fn add_module(
+        &self,
+        context: &Context,
+        module: &Module,
+        options: &Options,
+    ) -> Result<(), CompileError> {
+        // Whether a function is an `llvm.func` is known before any call to it is lowered
+        let mut analysis = scf::Analysis::default();
+        for external_declaration in &self.0 {
+            match external_declaration {
+                ExternalDeclaration::FunctionDefinition(function_definition) => {
+                    function_definition.body.analyze(&mut analysis)
+                }
+                ExternalDeclaration::FunctionDeclaration(_) => {}
+                ExternalDeclaration::Declaration(declaration) => declaration.analyze(&mut analysis),
+            }
+        }
+        let mut file_scope = FileScope {
+            designated: Rc::new(analysis.designated),
+            ..FileScope::default()
+        };
+        for external_declaration in &self.0 {
+            match external_declaration {
+                ExternalDeclaration::FunctionDefinition(function_definition) => {
+                    function_definition.add_function(context, module, options, &mut file_scope)?
+                }
+                ExternalDeclaration::FunctionDeclaration(function_declaration) => {
+                    let r#type = function_type(
+                        function_declaration,
+                        options.memory_model,
+                        &|operand, location| file_scope.operand_type(context, operand, location),
+                    )?;
+                    function_declaration.declare(
+                        &mut file_scope,
+                        r#type,
+                        Definition::Declaration,
+                    )?;
+                }
+                ExternalDeclaration::Declaration(declaration) => {
+                    declaration.declare(context, &mut file_scope)?
+                }
+            }
+        }
+
+        for BlockSymbol {
+            identifier,
+            storage_class,
+            symbol,
+        } in file_scope.block_symbols.take()
+        {
+            file_scope.declare(&identifier, storage_class, symbol)?;
+        }
+
+        // Objects are emitted once all of their declarations are seen. Functions only declared
+        // here are resolved by the linker.
+        for identifier in &file_scope.order {
+            let symbol = &file_scope.symbols[identifier];
+            let location = symbol.location.mlir_location(context);
+            let operation = match symbol.r#type {
+                CType::Function { .. } if symbol.definition == Definition::Declaration => {
+                    function_operation(context, identifier, &file_scope, None, location)
+                }
+                CType::Function { .. } => continue,
+                // 6.9.2 A tentative definition of a structure completed later is fine
+                ref r#type
+                    if symbol.definition != Definition::Declaration && !r#type.is_complete() =>
+                {
+                    return Err(CompileError::new(
+                        symbol.location.clone(),
+                        format!("variable '{}' has incomplete type '{}'", identifier, r#type),
+                    ));
+                }
+                _ => global_operation(context, identifier, symbol, location),
+            };
+            module.body().append_operation(operation);
+        }
+        for (index, StringLiteral { value, location }) in
+            file_scope.string_literals.borrow().iter().enumerate()
+        {
+            let symbol = FileSymbol {
+                location: location.clone(),
+                r#type: CType::Array {
+                    element: Box::new(CType::CHAR),
+                    length: value.len() as u64,
+                },
+                // Modifying it is undefined
+                r#const: true,
+                internal: true,
+                definition: Definition::Definition,
+                initializer: Some(Initializer::String(value.clone())),
+                alignment: None,
+            };
+            module.body().append_operation(global_operation(
+                context,
+                &string_literal_symbol(index),
+                &symbol,
+                location.mlir_location(context),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A `func.func`, or an `llvm.func` if `FileScope::is_llvm_function`. Without a body it is an
+/// external declaration.
+fn function_operation<'c>(
+    context: &'c Context,
+    identifier: &str,
+    file_scope: &FileScope,
+    body: Option<Region<'c>>,
+    location: Location<'c>,
+) -> Operation<'c> {
+    let symbol = &file_scope.symbols[identifier];
+    let name = StringAttribute::new(context, identifier);
+    let declaration = body.is_none();
+    let body = body.unwrap_or_default();
+    if file_scope.is_llvm_function(identifier) {
+        return llvm::func(
+            context,
+            name,
+            TypeAttribute::new(symbol.r#type.llvm_function_type(context)),
+            body,
+            &[(
+                Identifier::new(context, "linkage"),
+                linkage(context, symbol),
+            )],
+            location,
+        );
+    }
+    let type_attribute = TypeAttribute::new(symbol.r#type.mlir_type(context));
+    // Declarations must be private in `func`, which also gives internal linkage
+    if declaration || symbol.internal {
+        func::func(
+            context,
+            name,
+            type_attribute,
+            body,
+            &[(
+                Identifier::new(context, "sym_visibility"),
+                StringAttribute::new(context, "private").into(),
+            )],
+            location,
+        )
+    } else {
+        func::func(context, name, type_attribute, body, &[], location)
+    }
+}
+
+// A field of a global initialized with address constants among its bytes
+enum GlobalField<'v> {
+    Bytes(&'v [u8]),
+    Address(&'v EcoString, i64),
+}
+
+// Splits the bytes at the address constants, which are in order
+fn global_fields<'v>(
+    value: &'v [u8],
+    addresses: &'v [(u64, EcoString, i64)],
+) -> Vec<GlobalField<'v>> {
+    let mut fields = Vec::new();
+    let mut start = 0;
+    for (offset, identifier, addend) in addresses {
+        let offset = *offset as usize;
+        if offset > start {
+            fields.push(GlobalField::Bytes(&value[start..offset]));
+        }
+        fields.push(GlobalField::Address(identifier, *addend));
+        start = offset + 8;
+    }
+    if start < value.len() {
+        fields.push(GlobalField::Bytes(&value[start..]));
+    }
+    fields
+}
+
+/// An `llvm.mlir.global`. Without a value it refers to a definition in another translation unit.
+///
+/// An object initialized with bytes is an array of them, or a packed structure of them and
+/// pointers if it has address constants.
+fn global_operation<'c>(
+    context: &'c Context,
+    identifier: &str,
+    symbol: &FileSymbol,
+    location: Location<'c>,
+) -> Operation<'c> {
+    let i8_type = IntegerType::new(context, 8).into();
+    let r#type = match &symbol.initializer {
+        Some(Initializer::String(value)) => llvm::r#type::array(i8_type, value.len() as u32),
+        Some(Initializer::Aggregate { value, addresses }) => llvm::r#type::r#struct(
+            context,
+            &global_fields(value, addresses)
+                .iter()
+                .map(|field| match field {
+                    GlobalField::Bytes(bytes) => llvm::r#type::array(i8_type, bytes.len() as u32),
+                    GlobalField::Address(..) => llvm::r#type::pointer(context, 0),
+                })
+                .collect::<Vec<_>>(),
+            true,
+        ),
+        _ => symbol.r#type.memory_type(context),
+    };
+    let mut attributes = vec![
+        (
+            Identifier::new(context, "sym_name"),
+            StringAttribute::new(context, identifier).into(),
+        ),
+        (
+            Identifier::new(context, "global_type"),
+            TypeAttribute::new(r#type).into(),
+        ),
+        (
+            Identifier::new(context, "linkage"),
+            linkage(context, symbol),
+        ),
+    ];
+    if symbol.r#type.is_complete() {
+        let alignment = symbol.alignment.unwrap_or(0).max(symbol.r#type.alignment());
+        attributes.push((
+            Identifier::new(context, "alignment"),
+            IntegerAttribute::new(IntegerType::new(context, 64).into(), alignment as i64).into(),
+        ));
+    }
+    if symbol.r#const {
+        attributes.push((
+            Identifier::new(context, "constant"),
+            Attribute::unit(context),
+        ));
+    }
+    let region = Region::new();
+    match &symbol.initializer {
+        _ if symbol.definition == Definition::Declaration => {}
+        Some(Initializer::String(value)) => attributes.push((
+            Identifier::new(context, "value"),
+            string_attribute(context, value),
+        )),
+        initializer if symbol.r#type.is_integer() => {
+            let value = match initializer {
+                Some(Initializer::Integer(value)) => *value,
+                _ => 0,
+            };
+            // `IntegerAttribute` takes 64 bits, which `__int128` may exceed
+            attributes.push((
+                Identifier::new(context, "value"),
+                Attribute::parse(context, &format!("{} : {}", value, r#type)).unwrap(),
+            ));
+        }
+        // Pointers and arrays are initialized in the region, since they have no attribute form
+        initializer => {
+            let block = region.append_block(Block::new(&[]));
+            let value = match initializer {
+                Some(Initializer::Address { identifier, offset }) => {
+                    address_constant(context, &block, identifier, *offset, location)
+                }
+                // The fields of bytes that are all 0 are left as they are
+                Some(Initializer::Aggregate { value, addresses }) => {
+                    let mut aggregate = block
+                        .append_operation(llvm::zero(r#type, location))
+                        .result(0)
+                        .unwrap()
+                        .into();
+                    for (index, field) in global_fields(value, addresses).iter().enumerate() {
+                        let value = match field {
+                            GlobalField::Bytes(bytes) if bytes.iter().all(|&byte| byte == 0) => {
+                                continue
+                            }
+                            GlobalField::Bytes(bytes) => block
+                                .append_operation(
+                                    OperationBuilder::new("llvm.mlir.constant", location)
+                                        .add_attributes(&[(
+                                            Identifier::new(context, "value"),
+                                            string_attribute(context, bytes),
+                                        )])
+                                        .add_results(&[llvm::r#type::array(
+                                            i8_type,
+                                            bytes.len() as u32,
+                                        )])
+                                        .build()
+                                        .unwrap(),
+                                )
+                                .result(0)
+                                .unwrap()
+                                .into(),
+                            GlobalField::Address(identifier, offset) => {
+                                address_constant(context, &block, identifier, *offset, location)
+                            }
+                        };
+                        aggregate = block
+                            .append_operation(llvm::insert_value(
+                                context,
+                                aggregate,
+                                DenseI64ArrayAttribute::new(context, &[index as i64]),
+                                value,
+                                location,
+                            ))
+                            .result(0)
+                            .unwrap()
+                            .into();
+                    }
+                    aggregate
+                }
+                _ => block
+                    .append_operation(llvm::zero(r#type, location))
+                    .result(0)
+                    .unwrap()
+                    .into(),
+            };
+            block.append_operation(llvm::r#return(Some(value), location));
+        }
+    }
+    OperationBuilder::new("llvm.mlir.global", location)
+        .add_attributes(&attributes)
+        .add_regions([region])
+        .build()
+        .unwrap()
+}
+
+// The address of a global offset by bytes in the initializer region of another global
+fn address_constant<'c, 'b>(
+    context: &'c Context,
+    block: &'b Block<'c>,
+    identifier: &str,
+    offset: i64,
+    location: Location<'c>,
+) -> Value<'c, 'b> {
+    let address = block
+        .append_operation(addressof_operation(context, identifier, location))
+        .result(0)
+        .unwrap()
+        .into();
+    block
+        .append_operation(llvm::get_element_ptr(
+            context,
+            address,
+            DenseI32ArrayAttribute::new(context, &[offset as i32]),
+            IntegerType::new(context, 8).into(),
+            llvm::r#type::pointer(context, 0),
+            location,
+        ))
+        .result(0)
+        .unwrap()
+        .into()
+}
+
+/// The address of a global
+fn addressof_operation<'c>(
+    context: &'c Context,
+    identifier: &str,
+    location: Location<'c>,
+) -> Operation<'c> {
+    OperationBuilder::new("llvm.mlir.addressof", location)
+        .add_attributes(&[(
+            Identifier::new(context, "global_name"),
+            FlatSymbolRefAttribute::new(context, identifier).into(),
+        )])
+        .add_results(&[llvm::r#type::pointer(context, 0)])
+        .build()
+        .unwrap()
+}
+
+// The bytes of an `!llvm.array<N x i8>` global as a string attribute with hexadecimal escapes
+fn string_attribute<'c>(context: &'c Context, value: &[u8]) -> Attribute<'c> {
+    let mut source = String::from("\"");
+    for &c in value {
+        if (c.is_ascii_graphic() || c == b' ') && c != b'"' && c != b'\\' {
+            source.push(c as char);
+        } else {
+            source.push_str(&format!("\\{:02X}", c));
+        }
+    }
+    source.push('"');
+    Attribute::parse(context, &source).unwrap()
+}
+
+fn linkage<'c>(context: &'c Context, symbol: &FileSymbol) -> Attribute<'c> {
+    let linkage = if symbol.internal {
+        "internal"
+    } else {
+        "external"
+    };
+    Attribute::parse(context, &format!("#llvm.linkage<{}>", linkage)).unwrap()
+}
+
+impl FunctionDeclaration {
+    fn declare(
+        &self,
+        file_scope: &mut FileScope,
+        r#type: CType,
+        definition: Definition,
+    ) -> Result<(), CompileError> {
+        let storage_class = self.declaration_specifiers.storage_class;
+        file_scope.declare(
+            &self.identifier,
+            storage_class,
+            FileSymbol {
+                location: self.location.clone(),
+                r#type,
+                r#const: false,
+                internal: storage_class == Some(StorageClassSpecifier::Static),
+                definition,
+                initializer: None,
+                alignment: None,
+            },
+        )
+    }
+}
+
+/// 6.7.6 Derives the type of a declarator with `derivations`, and returns it with whether the
+/// declared identifier itself is const. An omitted length of the outermost array is taken from
+/// `length`, that of the initializer.
+fn declarator_type(
+    declaration_specifiers: &DeclarationSpecifiers,
+    identifier: &str,
+    derivations: &[Derivation],
+    length: Option<u64>,
+    operand_type: OperandType,
+) -> Result<(CType, bool), CompileError> {
+    declaration_specifiers.derived_type(derivations, |index, derivation, r#type| {
+        if let Some(message) = derivation.check(identifier, &r#type) {
+            return Err(CompileError::new(derivation.location().clone(), message));
+        }
+        match derivation {
+            Derivation::Array(array) => {
+                let outermost = index + 1 == derivations.len();
+                let length = length.filter(|_| outermost);
+                array_type(
+                    r#type,
+                    identifier,
+                    index,
+                    array,
+                    length,
+                    outermost,
+                    operand_type,
+                )
+            }
+            // Only a function called by its name takes `memref`s
+            Derivation::Function(parameter_type_list) => Ok(CType::Function {
+                return_type: Box::new(r#type),
+                parameters: parameter_types(parameter_type_list, MemoryModel::Llvm, operand_type)?,
+                variadic: parameter_type_list.variadic,
+            }),
+            Derivation::Pointer(_) => unreachable!(),
+        }
+    })
+}
+
+/// 6.7.6.2 Derives an array of `r#type` with the array declarator at `index` of `identifier`. A
+/// length that is not an integer constant expression makes a variable length array, whose
+/// length is held by a hidden local named by `variable_length`.
+fn array_type(
+    r#type: CType,
+    identifier: &str,
+    index: usize,
+    array: &ArrayDeclarator,
+    length: Option<u64>,
+    outermost: bool,
+    operand_type: OperandType,
+) -> Result<CType, CompileError> {
+    let length =
+        match (&array.length, length) {
+            (Some(expression), _) => {
+                let constant = match expression.evaluate_with(operand_type) {
+                    Ok(constant) => constant,
+                    Err(ConstantError::NotConstant(_)) => {
+                        return Ok(CType::VariableArray {
+                            element: Box::new(r#type),
+                            length: variable_length(identifier, index),
+                        })
+                    }
+                    Err(error) => return Err(constant_error(error, "")),
+                };
+                match u64::try_from(constant.value) {
+                    Ok(0) => {
+                        return Err(CompileError::new(
+                            array.location.clone(),
+                            format!("'{}' declared as an array with a zero size", identifier),
+                        ))
+                    }
+                    // The length of `!llvm.array` is 32 bits
+                    Ok(length) if length <= u32::MAX as u64 => length,
+                    Ok(_) => {
+                        return Err(CompileError::new(
+                            array.location.clone(),
+                            format!("array '{}' is too large", identifier),
+                        ))
+                    }
+                    Err(_) => {
+                        return Err(CompileError::new(
+                            array.location.clone(),
+                            format!("'{}' declared as an array with a negative size", identifier),
+                        ))
+                    }
+                }
+            }
+            (None, Some(length)) => length,
+            (None, _) if outermost => return Err(CompileError::new(
+                array.location.clone(),
+                "definition of variable with array type needs an explicit size or an initializer"
+                    .to_string(),
+            )),
+            (None, _) => {
+                return Err(CompileError::new(
+                    array.location.clone(),
+                    "array has incomplete element type".to_string(),
+                ))
+            }
+        };
+    Ok(CType::Array {
+        element: Box::new(r#type),
+        length,
+    })
+}
+
+// The hidden local holding the length of the array declarator at `index` of `identifier`, which
+// cannot clash with an identifier
+fn variable_length(identifier: &str, index: usize) -> VariableLength {
+    VariableLength(eco_format!("{}[{}]", identifier, index))
+}
+
+/// 6.7.6.2 Evaluates the lengths of the variable length arrays of `r#type`, derived by a
+/// declarator with `derivations`, where the declaration is reached, and declares their hidden
+/// locals.
+fn add_variable_lengths(
+    builder: &mut FunctionBuilder,
+    identifier: &str,
+    derivations: &[Derivation],
+    mut r#type: &CType,
+) -> Result<(), CompileError> {
+    for (index, derivation) in derivations.iter().enumerate().rev() {
+        let element = match r#type {
+            CType::Pointer { pointee, .. } => pointee,
+            CType::Array { element, .. } | CType::VariableArray { element, .. } => element,
+            // The lengths in the parameters of a function type are not evaluated
+            _ => return Ok(()),
+        };
+        let variable = matches!(r#type, CType::VariableArray { .. });
+        r#type = element;
+        let (
+            true,
+            Derivation::Array(ArrayDeclarator {
+                location,
+                length: Some(expression),
+            }),
+        ) = (variable, derivation)
+        else {
+            continue;
+        };
+        let length = expression.add_value(builder)?;
+        if !length.r#type.is_integer() {
+            return Err(CompileError::new(
+                location.clone(),
+                format!("size of array has non-integer type '{}'", length.r#type),
+            ));
+        }
+        let location = location.mlir_location(builder.context);
+        let length = TypedValue {
+            value: add_conversion(builder, length, &CType::UNSIGNED_LONG, location),
+            r#type: CType::UNSIGNED_LONG,
+        };
+        builder.declare(
+            variable_length(identifier, index).0,
+            Variable::Value {
+                value: length,
+                r#const: true,
+            },
+        );
+    }
+    Ok(())
+}
+
+/// 6.7.6.3 Returns the adjusted type of a parameter with whether it is const
+fn parameter_type(
+    parameter: &ParameterDeclaration,
+    memory_model: MemoryModel,
+    operand_type: OperandType,
+) -> Result<(CType, bool), CompileError> {
+    let specifiers = &parameter.declaration_specifiers;
+    let identifier = parameter.declarator.as_deref().unwrap_or_default();
+    let derivations = &parameter.derivations;
+    match derivations.split_last() {
+        // A `memref` keeps the shape, so the lengths must be constants
+        Some((Derivation::Array(_), _))
+            if memory_model == MemoryModel::Memref
+                && specifiers.r#type.is_integer()
+                && derivations.iter().all(|derivation| match derivation {
+                    Derivation::Array(ArrayDeclarator {
+                        length: Some(length),
+                        ..
+                    }) => length.evaluate_with(operand_type).is_ok(),
+                    _ => false,
+                }) =>
+        {
+            declarator_type(specifiers, identifier, derivations, None, operand_type)
+        }
+        // An array is adjusted to a pointer to its element, qualified by the specifiers
+        Some((last @ Derivation::Array(_), derivations)) => {
+            let (element, r#const) =
+                declarator_type(specifiers, identifier, derivations, None, operand_type)?;
+            if let Some(message) = last.check(identifier, &element) {
+                return Err(CompileError::new(parameter.location.clone(), message));
+            }
+            Ok((
+                CType::Pointer {
+                    pointee: Box::new(element),
+                    r#const,
+                },
+                false,
+            ))
+        }
+        _ => match declarator_type(specifiers, identifier, derivations, None, operand_type)? {
+            // A typedef of an array type is adjusted as well
+            (CType::Array { element, .. }, r#const) => Ok((
+                CType::Pointer {
+                    pointee: element,
+                    r#const,
+                },
+                false,
+            )),
+            // A function is adjusted to a pointer to it
+            (r#type @ CType::Function { .. }, _) => Ok((
+                CType::Pointer {
+                    pointee: Box::new(r#type),
+                    r#const: false,
+                },
+                false,
+            )),
+            (r#type, r#const) => Ok((r#type, r#const)),
+        },
+    }
+}
+
+// 6.7.6.3 The adjusted types of parameters
+fn parameter_types(
+    parameter_type_list: &ParameterTypeList,
+    memory_model: MemoryModel,
+    operand_type: OperandType,
+) -> Result<Vec<CType>, CompileError> {
+    let mut parameters = Vec::new();
+    for parameter in &parameter_type_list.parameters {
+        // 6.7.6.3 `register` is the only one allowed, which is not supported
+        if parameter.declaration_specifiers.storage_class.is_some() {
+            return Err(CompileError::new(
+                parameter.location.clone(),
+                "invalid storage class specifier in function declarator".to_string(),
+            ));
+        }
+        let (r#type, _) = parameter_type(parameter, memory_model, operand_type)?;
+        if r#type.is_void() {
+            return Err(CompileError::new(
+                parameter.location.clone(),
+                "'void' must be the first and only parameter if specified".to_string(),
+            ));
+        }
+        parameters.push(r#type);
+    }
+    Ok(parameters)
+}
+
+// 6.7.6.3
+fn function_type(
+    declaration: &FunctionDeclaration,
+    memory_model: MemoryModel,
+    operand_type: OperandType,
+) -> Result<CType, CompileError> {
+    let parameters = parameter_types(&declaration.parameter_type_list, memory_model, operand_type)?;
+    // 6.7.3 Qualifiers of the return type are meaningless
+    let (return_type, _) = declarator_type(
+        &declaration.declaration_specifiers,
+        &declaration.identifier,
+        &declaration.derivations,
+        None,
+        operand_type,
+    )?;
+    if let Some(message) = check_return_type(&return_type) {
+        return Err(CompileError::new(declaration.location.clone(), message));
+    }
+    Ok(CType::Function {
+        return_type: Box::new(return_type),
+        parameters,
+        variadic: declaration.parameter_type_list.variadic,
+    })
+}
+
+impl FunctionDefinition {
+    fn add_function(
+        &self,
+        context: &Context,
+        module: &Module,
+        options: &Options,
+        file_scope: &mut FileScope,
+    ) -> Result<(), CompileError> {
+        let declaration = &self.declaration;
+        let location = declaration.location.mlir_location(context);
+        let r#type = function_type(declaration, options.memory_model, &|operand, location| {
+            file_scope.operand_type(context, operand, location)
+        })?;
+        let CType::Function {
+            return_type,
+            parameters: parameter_types,
+            variadic,
+        } = &r#type
+        else {
+            unreachable!()
+        };
+        let return_type = return_type.as_ref().clone();
+        // 6.9.1 Unlike in declarations, the types must be complete in a definition
+        if !return_type.is_void() && !return_type.is_complete() {
+            return Err(CompileError::new(
+                declaration.location.clone(),
+                format!(
+                    "incomplete result type '{}' in function definition",
+                    return_type
+                ),
+            ));
+        }
+        for (parameter, r#type) in declaration
+            .parameter_type_list
+            .parameters
+            .iter()
+            .zip(parameter_types)
+        {
+            if !r#type.is_complete() {
+                return Err(CompileError::new(
+                    parameter.location.clone(),
+                    format!("variable has incomplete type '{}'", r#type),
+                ));
+            }
+        }
+        // Declared before the body so that it can call itself
+        declaration.declare(file_scope, r#type.clone(), Definition::Definition)?;
+
+        let control_flow = match options.control_flow {
+            ControlFlow::Scf if scf::is_structured(&self.body) => ControlFlow::Scf,
+            _ => ControlFlow::Cf,
+        };
+        let blocks = Arena::new();
+        let mut builder = FunctionBuilder::new(
+            context,
+            &blocks,
+            control_flow,
+            options.memory_model,
+            return_type.clone(),
+            *variadic,
+            file_scope.clone(),
+        );
+        builder.builtin = !options.no_builtin;
+        builder.llvm = file_scope.is_llvm_function(&declaration.identifier);
+        if options.memory_model == MemoryModel::Memref {
+            let mut analysis = scf::Analysis::default();
+            self.body.analyze(&mut analysis);
+            builder.addressed = Rc::new(analysis.addressed);
+        }
+
+        // 6.9.1 Parameters are declared in the outermost block of the body
+        for (parameter, r#type) in declaration
+            .parameter_type_list
+            .parameters
+            .iter()
+            .zip(parameter_types)
+        {
+            let Some(identifier) = &parameter.declarator else {
+                return Err(CompileError::new(
+                    parameter.location.clone(),
+                    "parameter name omitted".to_string(),
+                ));
+            };
+            let (_, r#const) =
+                parameter_type(parameter, options.memory_model, &|operand, location| {
+                    builder.operand_type(operand, location)
+                })?;
+            // 6.9.1 The lengths of variable length arrays are evaluated on entry, and may use the
+            // parameters before
+            match (r#type, parameter.derivations.split_last()) {
+                (CType::Pointer { pointee, .. }, Some((Derivation::Array(_), derivations))) => {
+                    add_variable_lengths(&mut builder, identifier, derivations, pointee)?
+                }
+                _ => {
+                    add_variable_lengths(&mut builder, identifier, &parameter.derivations, r#type)?
+                }
+            }
+            let parameter_location = parameter.location.mlir_location(context);
+            let argument = match r#type {
+                CType::Array { .. } => builder.entry.add_argument(
+                    r#type.memref_type(context).unwrap().into(),
+                    parameter_location,
+                ),
+                CType::VaList => builder
+                    .entry
+                    .add_argument(llvm::r#type::pointer(context, 0), parameter_location),
+                _ => builder
+                    .entry
+                    .add_argument(r#type.mlir_type(context), parameter_location),
+            };
+            let variable = match (control_flow, r#type) {
+                // Passed by reference, so the callee accesses the array of the caller
+                (_, CType::Array { .. }) => Variable::Memref {
+                    memref: argument,
+                    r#type: r#type.clone(),
+                    r#const,
+                },
+                // 7.16 `va_list` is an array in x86-64 System V, so it decays to a pointer to the
+                // object of the caller, which the `va_` builtins take
+                (_, CType::VaList) => Variable::Memory {
+                    pointer: argument,
+                    r#type: CType::VaList,
+                    r#const,
+                },
+                (ControlFlow::Scf, r#type) if !r#type.is_record() => Variable::Value {
+                    value: TypedValue {
+                        value: argument,
+                        r#type: r#type.clone(),
+                    },
+                    r#const,
+                },
+                // Structures are copied into memory to access their members
+                _ => {
+                    let variable = builder.add_local(
+                        identifier,
+                        r#type.clone(),
+                        r#const,
+                        r#type.alignment(),
+                        parameter_location,
+                    );
+                    builder.store(&variable.lvalue(identifier), argument, parameter_location);
+                    variable
+                }
+            };
+            if !builder.declare(identifier.clone(), variable) {
+                return Err(CompileError::new(
+                    parameter.location.clone(),
+                    format!("redefinition of parameter '{}'", identifier),
+                ));
+            }
+        }
+
+        for item in &self.body.block_items {
+            item.add_block(&mut builder)?;
+        }
+        if let Some((identifier, location)) = builder.undefined_label() {
+            return Err(CompileError::new(
+                location.clone(),
+                format!("label '{}' used but not defined", identifier),
+            ));
+        }
+
+        // Reaching the closing brace returns from a void function, and from main with 0
+        // 5.1.2.2.3. Other functions return poison, since only using the value is undefined
+        // 6.9.1p12. A complex value is zero instead, since `llvm.poison` takes only LLVM types.
+        if builder.block().terminator().is_none() {
+            let value = if return_type.is_void() {
+                None
+            } else if declaration.identifier == "main" || return_type.is_complex() {
+                Some(add_zero(&builder, &return_type, location))
+            } else {
+                Some(
+                    builder
+                        .append_operation(llvm::poison(return_type.mlir_type(context), location))
+                        .result(0)
+                        .unwrap()
+                        .into(),
+                )
+            };
+            builder.append_operation(builder.r#return(value, location));
+        }
+
+        module.body().append_operation(function_operation(
+            context,
+            &declaration.identifier,
+            file_scope,
+            Some(FunctionBuilder::finish(blocks, location)),
+            location,
+        ));
+        Ok(())
+    }
+}
+
+// Lowers a translation unit to the text of the verified module for tests
+#[cfg(test)]
+fn lower(src: &str, options: &Options) -> Result<String, CompileError> {
+    use crate::parser::{Parse, Parser};
+    use melior::{
+        dialect::DialectRegistry, ir::operation::OperationPrintingFlags,
+        utility::register_all_dialects,
+    };
+
+    let mut parser = Parser::new(lexer::Lexer::new("test.c".into(), src.as_bytes().to_vec()));
+    let translation_unit = TranslationUnit::parse(&mut parser).unwrap();
+    let registry = DialectRegistry::new();
+    register_all_dialects(&registry);
+    let context = Context::new();
+    context.append_dialect_registry(&registry);
+    context.load_all_available_dialects();
+    let module = Module::new(Location::new(&context, "test.c", 1, 1));
+    translation_unit.add_module(&context, &module, options)?;
+    assert!(module.as_operation().verify());
+    Ok(module
+        .as_operation()
+        .to_string_with_flags(OperationPrintingFlags::default())
+        .unwrap())
+}
+
+#[test]
+fn test_bit_field_shifts() {
+    // The shifts are done in the integer of `unit` bits as the lowering does
+    let read = |bytes: u128, bit_field: &BitField, signed: bool| {
+        let unit = bit_field_unit(bit_field) as u32;
+        let (left, right) = bit_field_read_shifts(bit_field);
+        let value = (bytes << left) << (128 - unit);
+        if signed {
+            ((value as i128) >> (right as u32 + 128 - unit)) as u128
+        } else {
+            value >> (right as u32 + 128 - unit)
+        }
+    };
+    let write = |bytes: u128, bit_field: &BitField, value: u128| {
+        let unit = bit_field_unit(bit_field) as u32;
+        let mask = u128::MAX >> (128 - unit);
+        let (left, right) = bit_field_write_shifts(bit_field);
+        let mut result = ((value << left) & mask) >> right;
+        result |= bytes & ((1 << bit_field.bit_offset) - 1);
+        let shift = bit_field.bit_offset + bit_field.width;
+        if shift < unit as u64 {
+            result |= (bytes >> shift) << shift;
+        }
+        result
+    };
+
+    // `unsigned a : 3` after 6 bits spans 2 bytes
+    let bit_field = BitField {
+        width: 3,
+        bit_offset: 6,
+    };
+    assert_eq!(bit_field_unit(&bit_field), 16);
+    assert_eq!(read(0b1111_1110_0111_1111, &bit_field, false), 0b001);
+    let bytes = write(0xffff, &bit_field, 0b1010);
+    assert_eq!(bytes, 0b1111_1110_1011_1111);
+    assert_eq!(read(bytes, &bit_field, false), 0b010);
+
+    // `long b : 35` after 3 bits is in an i40
+    let bit_field = BitField {
+        width: 35,
+        bit_offset: 3,
+    };
+    assert_eq!(bit_field_unit(&bit_field), 40);
+    let bytes = write(0b101, &bit_field, -2i128 as u128);
+    assert_eq!(bytes, 0x3f_ffff_fff5);
+    // Sign-extended only when signed
+    assert_eq!(read(bytes, &bit_field, true), -2i128 as u128);
+    assert_eq!(read(bytes, &bit_field, false), (1 << 35) - 2);
+    let bytes = write(bytes, &bit_field, 5);
+    assert_eq!(bytes, 0x00_0000_002d);
+    assert_eq!(read(bytes, &bit_field, true), 5);
+    assert_eq!(write(0xff_ffff_ffff, &bit_field, 0), 0xc0_0000_0007);
+
+    // A whole unit leaves no bits around
+    let bit_field = BitField {
+        width: 8,
+        bit_offset: 0,
+    };
+    assert_eq!(bit_field_read_shifts(&bit_field), (0, 0));
+    assert_eq!(bit_field_write_shifts(&bit_field), (0, 0));
+    assert_eq!(read(0x80, &bit_field, true), -128i128 as u128);
+
+    // Every shift is less than the unit, since shifting by it is poison
+    for bit_offset in 0..8 {
+        for width in 1..=64 {
+            let bit_field = BitField { width, bit_offset };
+            let unit = bit_field_unit(&bit_field);
+            let (read_left, read_right) = bit_field_read_shifts(&bit_field);
+            let (write_left, write_right) = bit_field_write_shifts(&bit_field);
+            assert!([read_left, read_right, write_left, write_right]
+                .iter()
+                .all(|shift| *shift < unit));
+        }
+    }
+}
@@ -0,0 +1,1150 @@
+// Lowering to the structured control flow dialect for `--control-flow=scf`
+//
+// Locals are kept as SSA values. Locals assigned inside an `if` become its results and those
+// assigned inside a loop are carried as iter_args.
+
+use std::collections::HashSet;
+
+use ecow::EcoString;
+use melior::{
+    dialect::{arith, scf},
+    ir::{
+        attribute::IntegerAttribute, operation::OperationBuilder, r#type::IntegerType, Location,
+        OperationRef, Region, Value,
+    },
+};
+
+use crate::{
+    parser::{
+        declaration::{Declaration, Initializer},
+        expression::{
+            AdditiveExpression, AssignmentExpression, CastExpression, EqualityExpression, Evaluate,
+            Expression, MultiplicativeExpression, PostfixExpression, PrimaryExpression,
+            RelationalExpression, ShiftExpression, UnaryExpression, VaBuiltin,
+        },
+        statement::{
+            BlockItem, CompoundStatement, ForInit, IterationStatement, JumpStatement, Label,
+            LabeledStatement, SelectionStatement, Statement, UnlabeledStatement,
+        },
+    },
+    types::CType,
+};
+
+use super::{
+    add_condition, add_conversion, expression::add_discarded_value, AddBlock, AddValue,
+    CompileError, FunctionBuilder, TypedValue, Variable,
+};
+
+/// Syntactic facts about a piece of a function body
+pub struct Analysis {
+    /// Identifiers that are assigned
+    pub assigned: HashSet<EcoString>,
+    /// Identifiers that are read
+    pub used: HashSet<EcoString>,
+    /// Identifiers that are used other than as the function of a call, which includes functions
+    /// whose address is taken
+    pub designated: HashSet<EcoString>,
+    /// Identifiers that are the operand of `&`
+    pub addressed: HashSet<EcoString>,
+    /// Whether it can be lowered without jumps between blocks
+    pub structured: bool,
+}
+
+impl Default for Analysis {
+    fn default() -> Self {
+        Self {
+            assigned: HashSet::new(),
+            used: HashSet::new(),
+            designated: HashSet::new(),
+            addressed: HashSet::new(),
+            structured: true,
+        }
+    }
+}
+
+pub trait Analyze {
+    fn analyze(&self, analysis: &mut Analysis);
+}
+
+impl<T: Analyze> Analyze for Option<T> {
+    fn analyze(&self, analysis: &mut Analysis) {
+        if let Some(inner) = self {
+            inner.analyze(analysis);
+        }
+    }
+}
+
+/// A function body is structured if its only jump is a trailing `return`.
+pub fn is_structured(body: &CompoundStatement) -> bool {
+    let mut analysis = Analysis::default();
+    match body.block_items.split_last() {
+        Some((
+            BlockItem::UnlabeledStatement(UnlabeledStatement::JumpStatement(
+                JumpStatement::Return { expression, .. },
+            )),
+            block_items,
+        )) => {
+            expression.analyze(&mut analysis);
+            for item in block_items {
+                item.analyze(&mut analysis);
+            }
+        }
+        _ => body.analyze(&mut analysis),
+    }
+    analysis.structured
+}
+
+impl Analyze for Statement {
+    fn analyze(&self, analysis: &mut Analysis) {
+        match self {
+            Statement::LabeledStatement(labeled_statement) => labeled_statement.analyze(analysis),
+            Statement::UnlabeledStatement(unlabeled_statement) => {
+                unlabeled_statement.analyze(analysis)
+            }
+        }
+    }
+}
+
+impl Analyze for UnlabeledStatement {
+    fn analyze(&self, analysis: &mut Analysis) {
+        match self {
+            UnlabeledStatement::CompoundStatement(compound_statement) => {
+                compound_statement.analyze(analysis)
+            }
+            UnlabeledStatement::SelectionStatement(selection_statement) => {
+                selection_statement.analyze(analysis)
+            }
+            UnlabeledStatement::IterationStatement(iteration_statement) => {
+                iteration_statement.analyze(analysis)
+            }
+            UnlabeledStatement::ExpressionStatement(expression_statement) => {
+                expression_statement.expression.analyze(analysis)
+            }
+            UnlabeledStatement::JumpStatement(jump_statement) => jump_statement.analyze(analysis),
+        }
+    }
+}
+
+impl Analyze for Label {
+    fn analyze(&self, analysis: &mut Analysis) {
+        analysis.structured = false;
+    }
+}
+
+impl Analyze for LabeledStatement {
+    fn analyze(&self, analysis: &mut Analysis) {
+        self.label.analyze(analysis);
+        self.statement.analyze(analysis);
+    }
+}
+
+impl Analyze for CompoundStatement {
+    fn analyze(&self, analysis: &mut Analysis) {
+        for item in &self.block_items {
+            item.analyze(analysis);
+        }
+    }
+}
+
+impl Analyze for BlockItem {
+    fn analyze(&self, analysis: &mut Analysis) {
+        match self {
+            BlockItem::Declaration(declaration) => declaration.analyze(analysis),
+            BlockItem::UnlabeledStatement(unlabeled_statement) => {
+                unlabeled_statement.analyze(analysis)
+            }
+            BlockItem::Label(label) => label.analyze(analysis),
+        }
+    }
+}
+
+impl Analyze for SelectionStatement {
+    fn analyze(&self, analysis: &mut Analysis) {
+        match self {
+            SelectionStatement::If {
+                condition,
+                then_statement,
+                else_statement,
+                ..
+            } => {
+                condition.analyze(analysis);
+                then_statement.analyze(analysis);
+                if let Some(else_statement) = else_statement {
+                    else_statement.analyze(analysis);
+                }
+            }
+            SelectionStatement::Switch {
+                condition, body, ..
+            } => {
+                analysis.structured = false;
+                condition.analyze(analysis);
+                body.analyze(analysis);
+            }
+        }
+    }
+}
+
+impl Analyze for IterationStatement {
+    fn analyze(&self, analysis: &mut Analysis) {
+        match self {
+            IterationStatement::While {
+                condition, body, ..
+            }
+            | IterationStatement::DoWhile {
+                condition, body, ..
+            } => {
+                condition.analyze(analysis);
+                body.analyze(analysis);
+            }
+            IterationStatement::For {
+                init,
+                condition,
+                step,
+                body,
+                ..
+            } => {
+                match init {
+                    Some(ForInit::Declaration(declaration)) => declaration.analyze(analysis),
+                    Some(ForInit::Expression(expression)) => expression.analyze(analysis),
+                    None => {}
+                }
+                if let Some(condition) = condition {
+                    condition.analyze(analysis);
+                }
+                if let Some(step) = step {
+                    step.analyze(analysis);
+                }
+                body.analyze(analysis);
+            }
+        }
+    }
+}
+
+impl Analyze for JumpStatement {
+    fn analyze(&self, analysis: &mut Analysis) {
+        analysis.structured = false;
+        match self {
+            JumpStatement::Goto { .. }
+            | JumpStatement::Break { .. }
+            | JumpStatement::Continue { .. } => {}
+            JumpStatement::Return { expression, .. } => expression.analyze(analysis),
+        }
+    }
+}
+
+impl Analyze for Declaration {
+    fn analyze(&self, analysis: &mut Analysis) {
+        match self {
+            Declaration::NoAttr {
+                init_declarator_list,
+                ..
+            } => {
+                for init_declarator in init_declarator_list {
+                    init_declarator.initializer.analyze(analysis);
+                }
+            }
+            Declaration::Tag { .. }
+            | Declaration::Typedef { .. }
+            | Declaration::StaticAssert { .. } => {}
+        }
+    }
+}
+
+impl Analyze for Initializer {
+    fn analyze(&self, analysis: &mut Analysis) {
+        match self {
+            Initializer::Expression { expression, .. } => expression.analyze(analysis),
+            Initializer::List { initializers, .. } => {
+                for initializer in initializers {
+                    initializer.initializer.analyze(analysis);
+                }
+            }
+        }
+    }
+}
+
+impl Analyze for Expression {
+    fn analyze(&self, analysis: &mut Analysis) {
+        match self {
+            Expression::AssignmentExpression(assignment_expression) => {
+                assignment_expression.analyze(analysis)
+            }
+        }
+    }
+}
+
+impl Analyze for AssignmentExpression {
+    fn analyze(&self, analysis: &mut Analysis) {
+        match self {
+            AssignmentExpression::EqualityExpression(equality_expression) => {
+                equality_expression.analyze(analysis)
+            }
+            AssignmentExpression::Assign { lhs, rhs, .. } => {
+                if let Some(identifier) = lhs
+                    .as_unary_expression()
+                    .and_then(UnaryExpression::as_identifier)
+                {
+                    analysis.assigned.insert(identifier.clone());
+                }
+                lhs.analyze(analysis);
+                rhs.analyze(analysis);
+            }
+        }
+    }
+}
+
+impl Analyze for EqualityExpression {
+    fn analyze(&self, analysis: &mut Analysis) {
+        match self {
+            EqualityExpression::RelationalExpression(relational_expression) => {
+                relational_expression.analyze(analysis)
+            }
+            EqualityExpression::Equal { lhs, rhs, .. }
+            | EqualityExpression::NotEqual { lhs, rhs, .. } => {
+                lhs.analyze(analysis);
+                rhs.analyze(analysis);
+            }
+        }
+    }
+}
+
+impl Analyze for RelationalExpression {
+    fn analyze(&self, analysis: &mut Analysis) {
+        match self {
+            RelationalExpression::ShiftExpression(shift_expression) => {
+                shift_expression.analyze(analysis)
+            }
+            RelationalExpression::Less { lhs, rhs, .. }
+            | RelationalExpression::Greater { lhs, rhs, .. }
+            | RelationalExpression::LessEqual { lhs, rhs, .. }
+            | RelationalExpression::GreaterEqual { lhs, rhs, .. } => {
+                lhs.analyze(analysis);
+                rhs.analyze(analysis);
+            }
+        }
+    }
+}
+
+impl Analyze for ShiftExpression {
+    fn analyze(&self, analysis: &mut Analysis) {
+        match self {
+            ShiftExpression::AdditiveExpression(additive_expression) => {
+                additive_expression.analyze(analysis)
+            }
+            ShiftExpression::Left { lhs, rhs, .. } | ShiftExpression::Right { lhs, rhs, .. } => {
+                lhs.analyze(analysis);
+                rhs.analyze(analysis);
+            }
+        }
+    }
+}
+
+impl Analyze for AdditiveExpression {
+    fn analyze(&self, analysis: &mut Analysis) {
+        match self {
+            AdditiveExpression::PrimaryExpression(multiplicative_expression) => {
+                multiplicative_expression.analyze(analysis)
+            }
+            AdditiveExpression::Add { lhs, rhs, .. }
+            | AdditiveExpression::Minus { lhs, rhs, .. } => {
+                lhs.analyze(analysis);
+                rhs.analyze(analysis);
+            }
+        }
+    }
+}
+
+impl Analyze for MultiplicativeExpression {
+    fn analyze(&self, analysis: &mut Analysis) {
+        match self {
+            MultiplicativeExpression::CastExpression(cast_expression) => {
+                cast_expression.analyze(analysis)
+            }
+            MultiplicativeExpression::Mul { lhs, rhs, .. }
+            | MultiplicativeExpression::Div { lhs, rhs, .. }
+            | MultiplicativeExpression::Rem { lhs, rhs, .. } => {
+                lhs.analyze(analysis);
+                rhs.analyze(analysis);
+            }
+        }
+    }
+}
+
+impl Analyze for CastExpression {
+    fn analyze(&self, analysis: &mut Analysis) {
+        match self {
+            CastExpression::UnaryExpression(unary_expression) => unary_expression.analyze(analysis),
+            CastExpression::Cast { expression, .. } => expression.analyze(analysis),
+        }
+    }
+}
+
+impl Analyze for UnaryExpression {
+    fn analyze(&self, analysis: &mut Analysis) {
+        match self {
+            UnaryExpression::PostfixExpression(postfix_expression) => {
+                postfix_expression.analyze(analysis)
+            }
+            UnaryExpression::Increment { expression, .. }
+            | UnaryExpression::Decrement { expression, .. } => {
+                if let Some(identifier) = expression.as_identifier() {
+                    analysis.assigned.insert(identifier.clone());
+                }
+                expression.analyze(analysis);
+            }
+            // Locals kept as SSA values have no address
+            UnaryExpression::Address { expression, .. } => {
+                analysis.structured = false;
+                if let Some(identifier) = expression.as_identifier() {
+                    analysis.addressed.insert(identifier.clone());
+                }
+                expression.analyze(analysis);
+            }
+            UnaryExpression::Indirection { expression, .. }
+            | UnaryExpression::Plus { expression, .. }
+            | UnaryExpression::Minus { expression, .. }
+            | UnaryExpression::BitwiseNot { expression, .. }
+            | UnaryExpression::LogicalNot { expression, .. } => expression.analyze(analysis),
+            UnaryExpression::SizeofExpression { expression, .. } => expression.analyze(analysis),
+            UnaryExpression::SizeofType { .. } => {}
+        }
+    }
+}
+
+impl Analyze for PostfixExpression {
+    fn analyze(&self, analysis: &mut Analysis) {
+        match self {
+            PostfixExpression::PrimaryExpression(primary_expression) => {
+                primary_expression.analyze(analysis)
+            }
+            PostfixExpression::FunctionCall {
+                function,
+                arguments,
+                ..
+            } => {
+                match function.as_identifier() {
+                    Some(identifier) => {
+                        analysis.used.insert(identifier.clone());
+                    }
+                    None => function.analyze(analysis),
+                }
+                for argument in arguments {
+                    argument.analyze(analysis);
+                }
+            }
+            PostfixExpression::Increment { expression, .. }
+            | PostfixExpression::Decrement { expression, .. } => {
+                if let Some(identifier) = expression.as_identifier() {
+                    analysis.assigned.insert(identifier.clone());
+                }
+                expression.analyze(analysis);
+            }
+            PostfixExpression::Subscript { array, index, .. } => {
+                array.analyze(analysis);
+                index.analyze(analysis);
+            }
+            PostfixExpression::Member { structure, .. } => structure.analyze(analysis),
+            PostfixExpression::Arrow { pointer, .. } => pointer.analyze(analysis),
+        }
+    }
+}
+
+impl Analyze for PrimaryExpression {
+    fn analyze(&self, analysis: &mut Analysis) {
+        match self {
+            PrimaryExpression::Identifier { identifier, .. } => {
+                analysis.used.insert(identifier.clone());
+                analysis.designated.insert(identifier.clone());
+            }
+            PrimaryExpression::Constant { .. } | PrimaryExpression::StringLiteral { .. } => {}
+            PrimaryExpression::Parenthesized(expression) => expression.analyze(analysis),
+            // Which association is selected is not known here, so all of them are analyzed
+            PrimaryExpression::GenericSelection(generic_selection) => {
+                for association in &generic_selection.associations {
+                    association.expression.analyze(analysis);
+                }
+            }
+            // The va_list has to stay in memory
+            PrimaryExpression::VaBuiltin(va_builtin) => {
+                analysis.structured = false;
+                match va_builtin {
+                    VaBuiltin::Start { va_list, .. }
+                    | VaBuiltin::Arg { va_list, .. }
+                    | VaBuiltin::End { va_list, .. } => va_list.analyze(analysis),
+                    VaBuiltin::Copy {
+                        destination,
+                        source,
+                        ..
+                    } => {
+                        destination.analyze(analysis);
+                        source.analyze(analysis);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Locals visible here that are assigned in the analyzed code
+fn carried_variables<'c, 'a>(
+    builder: &FunctionBuilder<'c, 'a>,
+    analysis: &Analysis,
+) -> Vec<(EcoString, TypedValue<'c, 'a>)> {
+    builder
+        .value_variables()
+        .into_iter()
+        .filter(|(identifier, _)| analysis.assigned.contains(identifier))
+        .collect()
+}
+
+fn current_values<'c, 'a>(
+    builder: &FunctionBuilder<'c, 'a>,
+    identifiers: &[(EcoString, CType)],
+) -> Vec<Value<'c, 'a>> {
+    identifiers
+        .iter()
+        .map(|(identifier, _)| match builder.lookup(identifier) {
+            Some(Variable::Value { value, .. }) => value.value,
+            _ => unreachable!("SSA variable '{}' is not in scope", identifier),
+        })
+        .collect()
+}
+
+// Binds the carried locals to the block arguments starting from `offset`
+fn bind_arguments(
+    builder: &mut FunctionBuilder,
+    identifiers: &[(EcoString, CType)],
+    offset: usize,
+) {
+    for (index, (identifier, r#type)) in identifiers.iter().enumerate() {
+        let value = builder.block().argument(offset + index).unwrap().into();
+        builder.assign(
+            identifier,
+            TypedValue {
+                value,
+                r#type: r#type.clone(),
+            },
+        );
+    }
+}
+
+fn bind_results<'c, 'a>(
+    builder: &mut FunctionBuilder<'c, 'a>,
+    identifiers: &[(EcoString, CType)],
+    operation: OperationRef<'c, 'a>,
+) {
+    for (index, (identifier, r#type)) in identifiers.iter().enumerate() {
+        builder.assign(
+            identifier,
+            TypedValue {
+                value: operation.result(index).unwrap().into(),
+                r#type: r#type.clone(),
+            },
+        );
+    }
+}
+
+pub fn add_if<'c, 'a>(
+    builder: &mut FunctionBuilder<'c, 'a>,
+    condition: &Expression,
+    then_statement: &Statement,
+    else_statement: Option<&Statement>,
+    location: Location<'c>,
+) -> Result<(), CompileError> {
+    let context = builder.context;
+    let v0 = condition.add_value(builder)?;
+    let condition = add_condition(builder, v0, location);
+
+    let mut analysis = Analysis::default();
+    then_statement.analyze(&mut analysis);
+    if let Some(else_statement) = else_statement {
+        else_statement.analyze(&mut analysis);
+    }
+    let carried = carried_variables(builder, &analysis);
+    let identifiers = carried
+        .iter()
+        .map(|(identifier, value)| (identifier.clone(), value.r#type.clone()))
+        .collect::<Vec<_>>();
+    let types = identifiers
+        .iter()
+        .map(|(_, r#type)| r#type.mlir_type(context))
+        .collect::<Vec<_>>();
+
+    let then_region = builder.add_region(&[], location, |builder| {
+        then_statement.add_block(builder)?;
+        let values = current_values(builder, &identifiers);
+        builder.append_operation(scf::r#yield(&values, location));
+        Ok(())
+    })?;
+    // The else region may be empty only if there are no results
+    let else_region = if else_statement.is_some() || !identifiers.is_empty() {
+        builder.add_region(&[], location, |builder| {
+            if let Some(else_statement) = else_statement {
+                else_statement.add_block(builder)?;
+            }
+            let values = current_values(builder, &identifiers);
+            builder.append_operation(scf::r#yield(&values, location));
+            Ok(())
+        })?
+    } else {
+        Region::new()
+    };
+
+    let operation = builder.append_operation(scf::r#if(
+        condition,
+        &types,
+        then_region,
+        else_region,
+        location,
+    ));
+    bind_results(builder, &identifiers, operation);
+    Ok(())
+}
+
+pub fn add_iteration<'c, 'a>(
+    builder: &mut FunctionBuilder<'c, 'a>,
+    iteration_statement: &IterationStatement,
+) -> Result<(), CompileError> {
+    let context = builder.context;
+    match iteration_statement {
+        IterationStatement::While {
+            location,
+            condition,
+            body,
+        } => add_while(
+            builder,
+            None,
+            Some(condition),
+            Some(body),
+            None,
+            location.mlir_location(context),
+        ),
+        IterationStatement::DoWhile {
+            location,
+            body,
+            condition,
+        } => add_while(
+            builder,
+            Some(body),
+            Some(condition),
+            None,
+            None,
+            location.mlir_location(context),
+        ),
+        IterationStatement::For {
+            location,
+            init,
+            condition,
+            step,
+            body,
+        } => {
+            let location = location.mlir_location(context);
+            builder.push_scope();
+            match init {
+                Some(ForInit::Declaration(declaration)) => declaration.add_block(builder)?,
+                Some(ForInit::Expression(Expression::AssignmentExpression(expression))) => {
+                    add_discarded_value(builder, expression)?
+                }
+                None => {}
+            }
+            if !add_counted_for(builder, init, condition, step, body, location)? {
+                add_while(
+                    builder,
+                    None,
+                    condition.as_ref(),
+                    Some(body),
+                    step.as_ref(),
+                    location,
+                )?;
+            }
+            builder.pop_scope();
+            Ok(())
+        }
+    }
+}
+
+/// Lowers to `scf.while`. `before_body` runs before the condition as in `do` statements, and
+/// `after_body` and `step` after it.
+fn add_while<'c, 'a>(
+    builder: &mut FunctionBuilder<'c, 'a>,
+    before_body: Option<&Statement>,
+    condition: Option<&Expression>,
+    after_body: Option<&Statement>,
+    step: Option<&Expression>,
+    location: Location<'c>,
+) -> Result<(), CompileError> {
+    let context = builder.context;
+    let mut analysis = Analysis::default();
+    if let Some(before_body) = before_body {
+        before_body.analyze(&mut analysis);
+    }
+    if let Some(condition) = condition {
+        condition.analyze(&mut analysis);
+    }
+    if let Some(after_body) = after_body {
+        after_body.analyze(&mut analysis);
+    }
+    if let Some(step) = step {
+        step.analyze(&mut analysis);
+    }
+    let carried = carried_variables(builder, &analysis);
+    let identifiers = carried
+        .iter()
+        .map(|(identifier, value)| (identifier.clone(), value.r#type.clone()))
+        .collect::<Vec<_>>();
+    let arguments = identifiers
+        .iter()
+        .map(|(_, r#type)| (r#type.mlir_type(context), location))
+        .collect::<Vec<_>>();
+    let types = arguments
+        .iter()
+        .map(|(r#type, _)| *r#type)
+        .collect::<Vec<_>>();
+    let initial_values = carried
+        .iter()
+        .map(|(_, value)| value.value)
+        .collect::<Vec<_>>();
+
+    let before_region = builder.add_region(&arguments, location, |builder| {
+        bind_arguments(builder, &identifiers, 0);
+        if let Some(before_body) = before_body {
+            before_body.add_block(builder)?;
+        }
+        // An omitted condition is replaced by a nonzero constant
+        let condition = match condition {
+            Some(condition) => {
+                let v0 = condition.add_value(builder)?;
+                add_condition(builder, v0, location)
+            }
+            None => builder
+                .append_operation(arith::constant(
+                    context,
+                    IntegerAttribute::new(IntegerType::new(context, 1).into(), 1).into(),
+                    location,
+                ))
+                .result(0)
+                .unwrap()
+                .into(),
+        };
+        let values = current_values(builder, &identifiers);
+        builder.append_operation(scf::condition(condition, &values, location));
+        Ok(())
+    })?;
+    let after_region = builder.add_region(&arguments, location, |builder| {
+        bind_arguments(builder, &identifiers, 0);
+        if let Some(after_body) = after_body {
+            after_body.add_block(builder)?;
+        }
+        if let Some(Expression::AssignmentExpression(step)) = step {
+            add_discarded_value(builder, step)?;
+        }
+        let values = current_values(builder, &identifiers);
+        builder.append_operation(scf::r#yield(&values, location));
+        Ok(())
+    })?;
+
+    let operation = builder.append_operation(scf::r#while(
+        &initial_values,
+        &types,
+        before_region,
+        after_region,
+        location,
+    ));
+    bind_results(builder, &identifiers, operation);
+    Ok(())
+}
+
+struct CountedLoop<'s> {
+    identifier: &'s EcoString,
+    upper_bound: &'s ShiftExpression,
+    step: i128,
+}
+
+// Recognizes `for (int i = init; i < upper_bound; i = i + step)` where `step` is a positive
+// constant, `upper_bound` is a constant or an identifier, and neither `i` nor `upper_bound` is
+// modified in the body.
+fn counted_loop<'s>(
+    init: &'s Option<ForInit>,
+    condition: &'s Option<Expression>,
+    step: &'s Option<Expression>,
+    body: &Statement,
+) -> Option<CountedLoop<'s>> {
+    let Some(ForInit::Declaration(declaration)) = init else {
+        return None;
+    };
+    let Declaration::NoAttr {
+        init_declarator_list,
+        ..
+    } = declaration.as_ref()
+    else {
+        return None;
+    };
+    let [init_declarator] = init_declarator_list.as_slice() else {
+        return None;
+    };
+    init_declarator.initializer.as_ref()?;
+    let identifier = &init_declarator.declarator;
+
+    let Some(Expression::AssignmentExpression(condition)) = condition else {
+        return None;
+    };
+    let AssignmentExpression::EqualityExpression(EqualityExpression::RelationalExpression(
+        RelationalExpression::Less {
+            lhs,
+            rhs: upper_bound,
+            ..
+        },
+    )) = condition.as_ref()
+    else {
+        return None;
+    };
+    if lhs.as_unary_expression()?.as_identifier() != Some(identifier) {
+        return None;
+    }
+    // It is evaluated once, so others such as `a[0]`, `*p` or `f()` might change in the loop
+    if upper_bound.evaluate().is_err()
+        && upper_bound
+            .as_unary_expression()
+            .and_then(UnaryExpression::as_identifier)
+            .is_none()
+    {
+        return None;
+    }
+
+    let Some(Expression::AssignmentExpression(step)) = step else {
+        return None;
+    };
+    let step = match step.as_ref() {
+        AssignmentExpression::Assign { lhs, rhs, .. } => {
+            if lhs.as_unary_expression()?.as_identifier() != Some(identifier) {
+                return None;
+            }
+            let AssignmentExpression::EqualityExpression(EqualityExpression::RelationalExpression(
+                RelationalExpression::ShiftExpression(ShiftExpression::AdditiveExpression(
+                    AdditiveExpression::Add { lhs, rhs: step, .. },
+                )),
+            )) = rhs.as_ref()
+            else {
+                return None;
+            };
+            if lhs.as_unary_expression()?.as_identifier() != Some(identifier) {
+                return None;
+            }
+            step.evaluate().ok().filter(|step| step.value > 0)?.value
+        }
+        // `++i` and `i++`
+        AssignmentExpression::EqualityExpression(step) => match step.as_unary_expression()? {
+            UnaryExpression::Increment { expression, .. }
+                if expression.as_identifier() == Some(identifier) =>
+            {
+                1
+            }
+            UnaryExpression::PostfixExpression(PostfixExpression::Increment {
+                expression, ..
+            }) if expression.as_identifier() == Some(identifier) => 1,
+            _ => return None,
+        },
+    };
+
+    let mut body_analysis = Analysis::default();
+    body.analyze(&mut body_analysis);
+    let mut upper_bound_analysis = Analysis::default();
+    upper_bound.analyze(&mut upper_bound_analysis);
+    if body_analysis.assigned.contains(identifier)
+        || upper_bound_analysis.used.contains(identifier)
+        || !upper_bound_analysis
+            .used
+            .is_disjoint(&body_analysis.assigned)
+    {
+        return None;
+    }
+
+    Some(CountedLoop {
+        identifier,
+        upper_bound,
+        step,
+    })
+}
+
+/// Lowers a counted `for` loop to `scf.for`. The init clause must have been lowered already.
+/// Returns `false` if the loop is not a counted one.
+fn add_counted_for<'c, 'a>(
+    builder: &mut FunctionBuilder<'c, 'a>,
+    init: &Option<ForInit>,
+    condition: &Option<Expression>,
+    step: &Option<Expression>,
+    body: &Statement,
+    location: Location<'c>,
+) -> Result<bool, CompileError> {
+    let context = builder.context;
+    let Some(counted_loop) = counted_loop(init, condition, step, body) else {
+        return Ok(false);
+    };
+    // A const counter is left to the generic lowering to report its assignment
+    let Some(Variable::Value {
+        value: lower_bound,
+        r#const: false,
+    }) = builder.lookup(counted_loop.identifier).cloned()
+    else {
+        return Ok(false);
+    };
+    // Nothing is emitted before the loop is known to be a counted one, since the generic
+    // lowering evaluates the upper bound again
+    let upper_bound_type = match counted_loop.upper_bound.evaluate() {
+        Ok(upper_bound) => upper_bound.r#type,
+        Err(_) => match counted_loop
+            .upper_bound
+            .as_unary_expression()
+            .and_then(UnaryExpression::as_identifier)
+            .and_then(|identifier| builder.lookup(identifier))
+        {
+            // Not assigned in the body, so it is loop invariant
+            Some(Variable::Value { value, .. }) => value.r#type.clone(),
+            // Globals may be changed by the functions called in the body
+            _ => return Ok(false),
+        },
+    };
+    // Pointer counters are left to the generic lowering
+    if !lower_bound.r#type.is_integer() || !upper_bound_type.is_integer() {
+        return Ok(false);
+    }
+    // 6.5.8 `i < upper_bound` compares in the common type, and `scf.for` compares signed
+    let induction_type = CType::usual_arithmetic_conversion(&lower_bound.r#type, &upper_bound_type);
+    if !induction_type.is_signed()
+        || induction_type.convert_constant(counted_loop.step) != counted_loop.step
+    {
+        return Ok(false);
+    }
+    let upper_bound = counted_loop.upper_bound.add_value(builder)?;
+    let variable_type = lower_bound.r#type.clone();
+    let lower_bound = add_conversion(builder, lower_bound, &induction_type, location);
+    let upper_bound = add_conversion(builder, upper_bound, &induction_type, location);
+    let step = builder
+        .append_operation(arith::constant(
+            context,
+            IntegerAttribute::new(induction_type.mlir_type(context), counted_loop.step as i64)
+                .into(),
+            location,
+        ))
+        .result(0)
+        .unwrap()
+        .into();
+
+    let mut analysis = Analysis::default();
+    body.analyze(&mut analysis);
+    let carried = carried_variables(builder, &analysis);
+    let identifiers = carried
+        .iter()
+        .map(|(identifier, value)| (identifier.clone(), value.r#type.clone()))
+        .collect::<Vec<_>>();
+    let types = identifiers
+        .iter()
+        .map(|(_, r#type)| r#type.mlir_type(context))
+        .collect::<Vec<_>>();
+    let initial_values = carried
+        .iter()
+        .map(|(_, value)| value.value)
+        .collect::<Vec<_>>();
+    let arguments = [(induction_type.mlir_type(context), location)]
+        .into_iter()
+        .chain(types.iter().map(|r#type| (*r#type, location)))
+        .collect::<Vec<_>>();
+
+    let region = builder.add_region(&arguments, location, |builder| {
+        let induction_variable = TypedValue {
+            value: builder.block().argument(0).unwrap().into(),
+            r#type: induction_type.clone(),
+        };
+        let value = add_conversion(builder, induction_variable, &variable_type, location);
+        builder.assign(
+            counted_loop.identifier,
+            TypedValue {
+                value,
+                r#type: variable_type.clone(),
+            },
+        );
+        bind_arguments(builder, &identifiers, 1);
+        body.add_block(builder)?;
+        let values = current_values(builder, &identifiers);
+        builder.append_operation(scf::r#yield(&values, location));
+        Ok(())
+    })?;
+
+    let operation = builder.append_operation(
+        OperationBuilder::new("scf.for", location)
+            .add_operands(&[lower_bound, upper_bound, step])
+            .add_operands(&initial_values)
+            .add_results(&types)
+            .add_regions([region])
+            .build()
+            .unwrap(),
+    );
+    bind_results(builder, &identifiers, operation);
+    Ok(true)
+}
+
+#[test]
+fn test_addressed() {
+    use crate::{
+        lexer::Lexer,
+        parser::{Parse, Parser},
+    };
+
+    let src =
+        "{ int x; int y; int z; int a[2]; int *p = &x; *p = z; p = &a[1]; return &(y) != p; }";
+    let mut parser = Parser::new(Lexer::new("test.c".into(), src.as_bytes().to_vec()));
+    let body = CompoundStatement::parse(&mut parser).unwrap();
+    let mut analysis = Analysis::default();
+    body.analyze(&mut analysis);
+
+    // Elements of arrays stay in their `memref`
+    assert_eq!(analysis.addressed, HashSet::from(["x".into(), "y".into()]));
+    assert!(!analysis.structured);
+}
+
+#[test]
+fn test_is_structured() {
+    use crate::{
+        lexer::Lexer,
+        parser::{Parse, Parser},
+    };
+
+    let is_structured = |src: &str| {
+        let mut parser = Parser::new(Lexer::new("test.c".into(), src.as_bytes().to_vec()));
+        is_structured(&CompoundStatement::parse(&mut parser).unwrap())
+    };
+
+    assert!(is_structured("{ int x = 1; x = x + 1; }"));
+    assert!(is_structured(
+        "{ int s = 0; \
+           for (int i = 0; i < 10; i++) { if (i < 5) s = s + i; else s = s - 1; } \
+           while (s < 100) s = s * 2; \
+           do s = s - 1; while (s > 50); \
+           return s; }"
+    ));
+    // Only a trailing `return`
+    assert!(!is_structured("{ if (1) return 1; return 0; }"));
+    assert!(!is_structured("{ return 0; return 1; }"));
+    assert!(!is_structured("{ goto end; end: return 0; }"));
+    assert!(!is_structured("{ while (1) break; return 0; }"));
+    assert!(!is_structured(
+        "{ int i = 0; while (i < 10) { i = i + 1; continue; } }"
+    ));
+    assert!(!is_structured(
+        "{ int x = 0; switch (x) { case 0: x = 1; } return x; }"
+    ));
+    assert!(!is_structured("{ int x = 0; int *p = &x; return *p; }"));
+    // Even in the returned expression
+    assert!(!is_structured("{ int x = 0; return *&x; }"));
+}
+
+#[test]
+fn test_counted_loop() {
+    use crate::{
+        lexer::Lexer,
+        parser::{Parse, Parser},
+    };
+
+    let counted_loop = |src: &str| {
+        let mut parser = Parser::new(Lexer::new("test.c".into(), src.as_bytes().to_vec()));
+        let Statement::UnlabeledStatement(UnlabeledStatement::IterationStatement(
+            IterationStatement::For {
+                init,
+                condition,
+                step,
+                body,
+                ..
+            },
+        )) = Statement::parse(&mut parser).unwrap()
+        else {
+            panic!("expected for statement");
+        };
+        counted_loop(&init, &condition, &step, &body)
+            .map(|counted_loop| (counted_loop.identifier.to_string(), counted_loop.step))
+    };
+
+    assert_eq!(
+        counted_loop("for (int i = 0; i < n; i++) s = s + i;"),
+        Some(("i".to_string(), 1))
+    );
+    assert_eq!(
+        counted_loop("for (int i = 0; i < n; ++i) ;"),
+        Some(("i".to_string(), 1))
+    );
+    assert_eq!(
+        counted_loop("for (int j = n; j < 10 * 2; j = j + (1 << 2)) { s = s + j; }"),
+        Some(("j".to_string(), 4))
+    );
+
+    // Not of the form
+    assert_eq!(counted_loop("for (i = 0; i < n; i++) ;"), None);
+    assert_eq!(counted_loop("for (int i; i < n; i++) ;"), None);
+    assert_eq!(counted_loop("for (int i = 0; i <= n; i++) ;"), None);
+    assert_eq!(counted_loop("for (int i = 0; n > i; i++) ;"), None);
+    assert_eq!(counted_loop("for (int i = 0; j < n; i++) ;"), None);
+    assert_eq!(counted_loop("for (int i = 0; i < n; j++) ;"), None);
+    assert_eq!(counted_loop("for (int i = 0; i < n; i--) ;"), None);
+    assert_eq!(counted_loop("for (int i = 0; i < n; i = j + 1) ;"), None);
+    assert_eq!(counted_loop("for (int i = 0; i < n; i = i - 1) ;"), None);
+    assert_eq!(counted_loop("for (int i = 0; i < n; i = 1 + i) ;"), None);
+    assert_eq!(counted_loop("for (int i = 0; i < n;) ;"), None);
+    assert_eq!(counted_loop("for (int i = 0; ; i++) ;"), None);
+    // The step must be a positive constant
+    assert_eq!(counted_loop("for (int i = 0; i < n; i = i + 0) ;"), None);
+    assert_eq!(counted_loop("for (int i = 0; i < n; i = i + -1) ;"), None);
+    assert_eq!(counted_loop("for (int i = 0; i < n; i = i + k) ;"), None);
+    // Neither the counter nor the upper bound may be modified in the body
+    assert_eq!(counted_loop("for (int i = 0; i < n; i++) i = i + 1;"), None);
+    assert_eq!(counted_loop("for (int i = 0; i < n; i++) n--;"), None);
+    assert_eq!(
+        counted_loop("for (int i = 0; i < m; i++) if (i) m = 0;"),
+        None
+    );
+    assert_eq!(counted_loop("for (int i = 0; i < i + n; i++) ;"), None);
+    // Nor may the upper bound be anything else that could change
+    assert_eq!(counted_loop("for (int i = 0; i < n * 2; i++) ;"), None);
+    assert_eq!(
+        counted_loop("for (int i = 0; i < a[0]; i++) a[0] = 0;"),
+        None
+    );
+    assert_eq!(counted_loop("for (int i = 0; i < *p; i++) ;"), None);
+    assert_eq!(counted_loop("for (int i = 0; i < s.n; i++) ;"), None);
+    assert_eq!(counted_loop("for (int i = 0; i < f(); i++) ;"), None);
+}
+
+#[test]
+fn test_assigned() {
+    use crate::{
+        lexer::Lexer,
+        parser::{Parse, Parser},
+    };
+
+    // Those assigned in a loop are carried as iter_args
+    let src = "while (i < n) { int t = s; s = t + f(i); i++; --j; a[k] = 0; }";
+    let mut parser = Parser::new(Lexer::new("test.c".into(), src.as_bytes().to_vec()));
+    let statement = Statement::parse(&mut parser).unwrap();
+    let mut analysis = Analysis::default();
+    statement.analyze(&mut analysis);
+
+    // An initialization is not an assignment, since the local is declared in the loop
+    assert_eq!(
+        analysis.assigned,
+        HashSet::from(["s".into(), "i".into(), "j".into()])
+    );
+    assert_eq!(
+        analysis.used,
+        HashSet::from([
+            "i".into(),
+            "n".into(),
+            "s".into(),
+            "t".into(),
+            "f".into(),
+            "j".into(),
+            "a".into(),
+            "k".into()
+        ])
+    );
+    // A direct callee is not designated
+    assert!(!analysis.designated.contains("f"));
+    assert!(analysis.structured);
+}
@@ -0,0 +1,504 @@
+use melior::{
+    dialect::cf,
+    ir::{Block, Value},
+};
+
+use crate::parser::{
+    expression::Expression,
+    statement::{
+        BlockItem, CompoundStatement, ExpressionStatement, ForInit, IterationStatement,
+        JumpStatement, Label, LabeledStatement, SelectionStatement, Statement, UnlabeledStatement,
+    },
+};
+
+use super::{
+    add_condition, add_conversion, constant_error,
+    expression::{add_assignment, add_discarded_value},
+    scf, AddBlock, AddValue, CompileError, ControlFlow, FunctionBuilder, Switch,
+};
+
+impl AddBlock for Statement {
+    fn add_block<'c, 'a>(&self, builder: &mut FunctionBuilder<'c, 'a>) -> Result<(), CompileError> {
+        match self {
+            Statement::LabeledStatement(labeled_statement) => labeled_statement.add_block(builder),
+            Statement::UnlabeledStatement(unlabeled_statement) => {
+                unlabeled_statement.add_block(builder)
+            }
+        }
+    }
+}
+
+impl AddBlock for UnlabeledStatement {
+    fn add_block<'c, 'a>(&self, builder: &mut FunctionBuilder<'c, 'a>) -> Result<(), CompileError> {
+        match self {
+            UnlabeledStatement::CompoundStatement(compound_statement) => {
+                compound_statement.add_block(builder)
+            }
+            UnlabeledStatement::ExpressionStatement(expression_statement) => {
+                expression_statement.add_block(builder)
+            }
+            UnlabeledStatement::SelectionStatement(selection_statement) => {
+                selection_statement.add_block(builder)
+            }
+            UnlabeledStatement::IterationStatement(iteration_statement) => {
+                iteration_statement.add_block(builder)
+            }
+            UnlabeledStatement::JumpStatement(jump_statement) => jump_statement.add_block(builder),
+        }
+    }
+}
+
+impl AddBlock for Label {
+    fn add_block<'c, 'a>(&self, builder: &mut FunctionBuilder<'c, 'a>) -> Result<(), CompileError> {
+        let context = builder.context;
+        match self {
+            Label::Identifier {
+                location,
+                identifier,
+            } => {
+                if !builder.define_label(identifier, location.mlir_location(context)) {
+                    return Err(CompileError::new(
+                        location.clone(),
+                        format!("duplicate label '{}'", identifier),
+                    ));
+                }
+            }
+            Label::Case {
+                location,
+                expression,
+            } => {
+                let value = builder.evaluate(expression.as_ref()).map_err(|error| {
+                    constant_error(error, "expression is not an integer constant expression")
+                })?;
+                let Some(switch) = builder.switches.last() else {
+                    return Err(CompileError::new(
+                        location.clone(),
+                        "'case' label not within a switch statement".to_string(),
+                    ));
+                };
+                // 6.8.4.2 The constant is converted to the promoted type of the controlling expression
+                let value = value.convert(&switch.r#type).value as i64;
+                if switch.cases.iter().any(|(case, _)| *case == value) {
+                    return Err(CompileError::new(
+                        location.clone(),
+                        format!("duplicate case value '{}'", value),
+                    ));
+                }
+
+                let block = builder.new_block();
+                builder.branch(block, location.mlir_location(context));
+                builder.position_at_end(block);
+                builder
+                    .switches
+                    .last_mut()
+                    .unwrap()
+                    .cases
+                    .push((value, block));
+            }
+            Label::Default { location } => {
+                let Some(switch) = builder.switches.last() else {
+                    return Err(CompileError::new(
+                        location.clone(),
+                        "'default' label not within a switch statement".to_string(),
+                    ));
+                };
+                if switch.default.is_some() {
+                    return Err(CompileError::new(
+                        location.clone(),
+                        "multiple default labels in one switch".to_string(),
+                    ));
+                }
+
+                let block = builder.new_block();
+                builder.branch(block, location.mlir_location(context));
+                builder.position_at_end(block);
+                builder.switches.last_mut().unwrap().default = Some(block);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl AddBlock for LabeledStatement {
+    fn add_block<'c, 'a>(&self, builder: &mut FunctionBuilder<'c, 'a>) -> Result<(), CompileError> {
+        self.label.add_block(builder)?;
+        self.statement.add_block(builder)
+    }
+}
+
+impl AddBlock for CompoundStatement {
+    fn add_block<'c, 'a>(&self, builder: &mut FunctionBuilder<'c, 'a>) -> Result<(), CompileError> {
+        builder.push_scope();
+        for item in &self.block_items {
+            item.add_block(builder)?;
+        }
+        builder.pop_scope();
+        Ok(())
+    }
+}
+
+impl AddBlock for BlockItem {
+    fn add_block<'c, 'a>(&self, builder: &mut FunctionBuilder<'c, 'a>) -> Result<(), CompileError> {
+        match self {
+            BlockItem::Declaration(declaration) => declaration.add_block(builder),
+            BlockItem::UnlabeledStatement(unlabeled_statement) => {
+                unlabeled_statement.add_block(builder)
+            }
+            BlockItem::Label(label) => label.add_block(builder),
+        }
+    }
+}
+
+impl AddBlock for ExpressionStatement {
+    fn add_block<'c, 'a>(&self, builder: &mut FunctionBuilder<'c, 'a>) -> Result<(), CompileError> {
+        let Some(Expression::AssignmentExpression(expression)) = &self.expression else {
+            return Ok(());
+        };
+        add_discarded_value(builder, expression)
+    }
+}
+
+impl AddBlock for SelectionStatement {
+    fn add_block<'c, 'a>(&self, builder: &mut FunctionBuilder<'c, 'a>) -> Result<(), CompileError> {
+        let context = builder.context;
+        match self {
+            SelectionStatement::If {
+                location,
+                condition,
+                then_statement,
+                else_statement,
+            } => {
+                let location = location.mlir_location(context);
+                if builder.control_flow == ControlFlow::Scf {
+                    return scf::add_if(
+                        builder,
+                        condition,
+                        then_statement,
+                        else_statement.as_deref(),
+                        location,
+                    );
+                }
+                let v0 = condition.add_value(builder)?;
+                let condition = add_condition(builder, v0, location);
+
+                let then_block = builder.new_block();
+                let end_block = builder.new_block();
+                let else_block = if else_statement.is_some() {
+                    builder.new_block()
+                } else {
+                    end_block
+                };
+                builder.append_operation(cf::cond_br(
+                    context,
+                    condition,
+                    then_block,
+                    else_block,
+                    &[],
+                    &[],
+                    location,
+                ));
+
+                builder.position_at_end(then_block);
+                then_statement.add_block(builder)?;
+                builder.branch(end_block, location);
+
+                if let Some(else_statement) = else_statement {
+                    builder.position_at_end(else_block);
+                    else_statement.add_block(builder)?;
+                    builder.branch(end_block, location);
+                }
+
+                builder.position_at_end(end_block);
+            }
+            SelectionStatement::Switch {
+                location,
+                condition,
+                body,
+            } => {
+                let v0 = condition.add_value(builder)?;
+                if !v0.r#type.is_integer() {
+                    return Err(CompileError::new(
+                        location.clone(),
+                        format!(
+                            "statement requires expression of integer type ('{}' invalid)",
+                            v0.r#type
+                        ),
+                    ));
+                }
+                let location = location.mlir_location(context);
+                // 6.8.4.2 The integer promotions are performed on the controlling expression
+                let r#type = v0.r#type.integer_promotion();
+                let flag = add_conversion(builder, v0, &r#type, location);
+
+                let head_block = builder.block();
+                let end_block = builder.new_block();
+                builder.switches.push(Switch {
+                    r#type: r#type.clone(),
+                    cases: Vec::new(),
+                    default: None,
+                });
+                builder
+                    .break_targets
+                    .push((end_block, builder.scopes.len()));
+
+                // Statements before the first label are unreachable
+                let body_block = builder.new_block();
+                builder.position_at_end(body_block);
+                body.add_block(builder)?;
+                builder.branch(end_block, location);
+
+                builder.break_targets.pop();
+                let switch = builder.switches.pop().unwrap();
+                let no_operands: &[Value] = &[];
+                let case_values = switch
+                    .cases
+                    .iter()
+                    .map(|(value, _)| *value)
+                    .collect::<Vec<_>>();
+                let case_destinations = switch
+                    .cases
+                    .iter()
+                    .map(|(_, block)| (*block as &Block, no_operands))
+                    .collect::<Vec<_>>();
+                head_block.append_operation(
+                    cf::switch(
+                        context,
+                        &case_values,
+                        flag,
+                        r#type.mlir_type(context),
+                        (switch.default.unwrap_or(end_block), no_operands),
+                        &case_destinations,
+                        location,
+                    )
+                    .unwrap(),
+                );
+
+                builder.position_at_end(end_block);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl AddBlock for IterationStatement {
+    fn add_block<'c, 'a>(&self, builder: &mut FunctionBuilder<'c, 'a>) -> Result<(), CompileError> {
+        let context = builder.context;
+        if builder.control_flow == ControlFlow::Scf {
+            return scf::add_iteration(builder, self);
+        }
+        match self {
+            IterationStatement::While {
+                location,
+                condition,
+                body,
+            } => {
+                let location = location.mlir_location(context);
+                let condition_block = builder.new_block();
+                let body_block = builder.new_block();
+                let end_block = builder.new_block();
+
+                builder.branch(condition_block, location);
+                builder.position_at_end(condition_block);
+                let v0 = condition.add_value(builder)?;
+                let condition = add_condition(builder, v0, location);
+                builder.append_operation(cf::cond_br(
+                    context,
+                    condition,
+                    body_block,
+                    end_block,
+                    &[],
+                    &[],
+                    location,
+                ));
+
+                builder.position_at_end(body_block);
+                add_loop_body(builder, body, end_block, condition_block)?;
+                builder.branch(condition_block, location);
+
+                builder.position_at_end(end_block);
+            }
+            IterationStatement::DoWhile {
+                location,
+                body,
+                condition,
+            } => {
+                let location = location.mlir_location(context);
+                let body_block = builder.new_block();
+                let condition_block = builder.new_block();
+                let end_block = builder.new_block();
+
+                builder.branch(body_block, location);
+                builder.position_at_end(body_block);
+                add_loop_body(builder, body, end_block, condition_block)?;
+                builder.branch(condition_block, location);
+
+                builder.position_at_end(condition_block);
+                let v0 = condition.add_value(builder)?;
+                let condition = add_condition(builder, v0, location);
+                builder.append_operation(cf::cond_br(
+                    context,
+                    condition,
+                    body_block,
+                    end_block,
+                    &[],
+                    &[],
+                    location,
+                ));
+
+                builder.position_at_end(end_block);
+            }
+            IterationStatement::For {
+                location,
+                init,
+                condition,
+                step,
+                body,
+            } => {
+                let location = location.mlir_location(context);
+                // 6.8.5 The for statement is a block whose scope covers the declaration in its init
+                builder.push_scope();
+                match init {
+                    Some(ForInit::Declaration(declaration)) => declaration.add_block(builder)?,
+                    Some(ForInit::Expression(Expression::AssignmentExpression(expression))) => {
+                        add_discarded_value(builder, expression)?
+                    }
+                    None => {}
+                }
+
+                let condition_block = builder.new_block();
+                let body_block = builder.new_block();
+                let step_block = builder.new_block();
+                let end_block = builder.new_block();
+
+                builder.branch(condition_block, location);
+                builder.position_at_end(condition_block);
+                // An omitted condition is replaced by a nonzero constant
+                if let Some(condition) = condition {
+                    let v0 = condition.add_value(builder)?;
+                    let condition = add_condition(builder, v0, location);
+                    builder.append_operation(cf::cond_br(
+                        context,
+                        condition,
+                        body_block,
+                        end_block,
+                        &[],
+                        &[],
+                        location,
+                    ));
+                } else {
+                    builder.branch(body_block, location);
+                }
+
+                builder.position_at_end(body_block);
+                add_loop_body(builder, body, end_block, step_block)?;
+                builder.branch(step_block, location);
+
+                builder.position_at_end(step_block);
+                if let Some(Expression::AssignmentExpression(step)) = step {
+                    add_discarded_value(builder, step)?;
+                }
+                builder.branch(condition_block, location);
+
+                builder.position_at_end(end_block);
+                builder.pop_scope();
+            }
+        }
+        Ok(())
+    }
+}
+
+fn add_loop_body<'c, 'a>(
+    builder: &mut FunctionBuilder<'c, 'a>,
+    body: &Statement,
+    break_target: &'a Block<'c>,
+    continue_target: &'a Block<'c>,
+) -> Result<(), CompileError> {
+    let depth = builder.scopes.len();
+    builder.break_targets.push((break_target, depth));
+    builder.continue_targets.push((continue_target, depth));
+    body.add_block(builder)?;
+    builder.continue_targets.pop();
+    builder.break_targets.pop();
+    Ok(())
+}
+
+impl AddBlock for JumpStatement {
+    fn add_block<'c, 'a>(&self, builder: &mut FunctionBuilder<'c, 'a>) -> Result<(), CompileError> {
+        let context = builder.context;
+        match self {
+            JumpStatement::Goto {
+                location,
+                identifier,
+            } => builder.add_goto(identifier, location),
+            JumpStatement::Break { location } => {
+                let Some((target, depth)) = builder.break_target() else {
+                    return Err(CompileError::new(
+                        location.clone(),
+                        "'break' statement not in loop or switch statement".to_string(),
+                    ));
+                };
+                let location = location.mlir_location(context);
+                builder.restore_stack(depth, location);
+                builder.terminate(cf::br(target, &[], location));
+            }
+            JumpStatement::Continue { location } => {
+                let Some((target, depth)) = builder.continue_target() else {
+                    return Err(CompileError::new(
+                        location.clone(),
+                        "'continue' statement not in loop statement".to_string(),
+                    ));
+                };
+                let location = location.mlir_location(context);
+                builder.restore_stack(depth, location);
+                builder.terminate(cf::br(target, &[], location));
+            }
+            JumpStatement::Return {
+                expression,
+                location,
+            } => {
+                let return_type = builder.return_type().clone();
+                let value = match expression {
+                    // 6.8.6.4
+                    Some(_) if return_type.is_void() => {
+                        return Err(CompileError::new(
+                            location.clone(),
+                            "void function should not return a value".to_string(),
+                        ));
+                    }
+                    None if !return_type.is_void() => {
+                        return Err(CompileError::new(
+                            location.clone(),
+                            "non-void function should return a value".to_string(),
+                        ));
+                    }
+                    Some(Expression::AssignmentExpression(expression)) => {
+                        Some(add_assignment(builder, expression, &return_type, location)?)
+                    }
+                    None => None,
+                };
+                builder.terminate(builder.r#return(value, location.mlir_location(context)));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_lower_void_for_clauses() {
+    use super::{lower, Options};
+
+    // 6.8.5.3 The init and step expressions are evaluated as void expressions
+    let src = "int n; \
+               void reset(void) { n = 0; } \
+               void next(void) { n = n + 1; } \
+               int count(void) { int i = 0; for (reset(); n < 10; next()) i = i + 1; return i; }";
+    for control_flow in [ControlFlow::Cf, ControlFlow::Scf] {
+        let options = Options {
+            control_flow,
+            ..Options::default()
+        };
+        let module = lower(src, &options).unwrap();
+        assert!(module.contains("@reset()"));
+        assert!(module.contains("@next()"));
+    }
+}
@@ -1,27 +1,1722 @@
 // 6.7
 
+use std::rc::Rc;
+
 use ecow::EcoString;
 
-use crate::lexer::TokenKind;
+use crate::{
+    lexer::{Location, TokenKind},
+    types::{BitField, CType, FloatingKind, IntegerKind, Layout, Member, Record, RecordKind},
+};
 
-use super::{Parse, ParseError, Parser};
+use super::{
+    expression::{AssignmentExpression, ConstantExpression, Evaluate},
+    Ordinary, Parse, ParseError, Parser, Tag,
+};
 
 #[derive(Debug)]
 pub enum Declaration {
     // TODO
     NoAttr {
-        // TODO
-        // declaration_specifiers: DeclarationSpecifiers,
-        // TODO init_declarator_list: InitDeclaratorList,
-        init_declarator: EcoString,
+        declaration_specifiers: DeclarationSpecifiers,
+        init_declarator_list: Vec<InitDeclarator>,
+    },
+    // 6.7.2.3 Only declares the tag of a structure, union or enumeration, or enumeration
+    // constants, which the parser has resolved
+    Tag {
+        declaration_specifiers: DeclarationSpecifiers,
+    },
+    // 6.7.8 Declares typedef names, which the parser has resolved
+    Typedef {
+        location: Location,
+        identifiers: Vec<EcoString>,
+    },
+    // 6.7.10 The message is optional since C23
+    StaticAssert {
+        location: Location,
+        expression: Box<ConstantExpression>,
+        message: Option<Vec<u8>>,
     },
 }
 
 impl Parse for Declaration {
     fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
-        parser.expect(TokenKind::Int)?;
-        let init_declarator = parser.expect_identifier()?;
+        if let Ok(token) = parser.expect(TokenKind::StaticAssert) {
+            parser.expect(TokenKind::LParen)?;
+            let expression = ConstantExpression::parse(parser)?;
+            let message = if parser.expect(TokenKind::Comma).is_ok() {
+                let Some(mut message) = parser.expect_string_literal() else {
+                    return Err(ParseError::new(
+                        parser.peek_location(),
+                        parser.lexer.current_line().to_string(),
+                        "expected string literal".to_string(),
+                    ));
+                };
+                while let Some(literal) = parser.expect_string_literal() {
+                    message.extend(literal);
+                }
+                Some(message)
+            } else {
+                None
+            };
+            parser.expect(TokenKind::RParen)?;
+            parser.expect(TokenKind::SemiColon)?;
+            return Ok(Declaration::StaticAssert {
+                location: token.location,
+                expression: Box::new(expression),
+                message,
+            });
+        }
+        let declaration_specifiers = DeclarationSpecifiers::parse(parser)?;
+        if let Ok(token) = parser.expect(TokenKind::SemiColon) {
+            if !declaration_specifiers.tag_specifier {
+                return Err(ParseError::new(
+                    token.location,
+                    parser.lexer.current_line().to_string(),
+                    "declaration does not declare anything".to_string(),
+                ));
+            }
+            return Ok(Declaration::Tag {
+                declaration_specifiers,
+            });
+        }
+        if declaration_specifiers.storage_class == Some(StorageClassSpecifier::Typedef) {
+            return typedef_declaration(parser, &declaration_specifiers);
+        }
+        let mut init_declarator_list = vec![InitDeclarator::parse(parser)?];
+        while parser.expect(TokenKind::Comma).is_ok() {
+            init_declarator_list.push(InitDeclarator::parse(parser)?);
+        }
+        parser.expect(TokenKind::SemiColon)?;
+        Ok(Declaration::NoAttr {
+            declaration_specifiers,
+            init_declarator_list,
+        })
+    }
+}
+
+// 6.7.8 The declarators following `typedef` and the declaration specifiers
+fn typedef_declaration(
+    parser: &mut Parser,
+    declaration_specifiers: &DeclarationSpecifiers,
+) -> Result<Declaration, ParseError> {
+    let location = parser.peek_location();
+    let mut identifiers = Vec::new();
+    loop {
+        let (location, identifier, derivations) = declarator(parser, DeclaratorKind::Concrete)?;
+        let identifier = identifier.unwrap();
+        let (r#type, r#const) = constant_type(
+            parser,
+            declaration_specifiers,
+            Some(&identifier),
+            &derivations,
+            "typedef arrays must have a constant size",
+        )?;
+        if let Ok(token) = parser.expect(TokenKind::Equal) {
+            return Err(ParseError::new(
+                token.location,
+                parser.lexer.current_line().to_string(),
+                "illegal initializer (only variables can be initialized)".to_string(),
+            ));
+        }
+        // 6.2.1 The scope begins just after the declarator, so a later one may use it
+        parser.declare_typedef(&identifier, r#type, r#const, &location)?;
+        identifiers.push(identifier);
+        if parser.expect(TokenKind::Comma).is_err() {
+            break;
+        }
+    }
+    parser.expect(TokenKind::SemiColon)?;
+    Ok(Declaration::Typedef {
+        location,
+        identifiers,
+    })
+}
+
+// 6.7.1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageClassSpecifier {
+    // 6.7.8 Only for convenience of syntax
+    Typedef,
+    Extern,
+    Static,
+}
+
+// 6.7.2, 6.7.3, 6.7.5
+#[derive(Debug)]
+pub struct DeclarationSpecifiers {
+    pub location: Location,
+    pub storage_class: Option<StorageClassSpecifier>,
+    pub r#const: bool,
+    pub r#type: CType,
+    // The strictest of `_Alignas`
+    pub alignment: Option<u64>,
+    // Whether the type is a structure, union or enumeration specifier, which may declare a tag
+    pub tag_specifier: bool,
+}
+
+impl Parse for DeclarationSpecifiers {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let location = parser.peek_location();
+        let mut storage_class = None;
+        let mut r#const = false;
+        let mut void = false;
+        let mut va_list = false;
+        let mut bool = false;
+        let mut char = false;
+        let mut short = false;
+        let mut int = false;
+        // The number of `long`
+        let mut long = 0;
+        let mut int128 = false;
+        let mut float = false;
+        let mut double = false;
+        let mut complex = false;
+        let mut signed = None;
+        let mut record = None;
+        let mut enumeration = None;
+        let mut typedef = None;
+        let mut alignment = None;
+        loop {
+            let pos = parser.lexer.current_position();
+            let Some(token) = parser.lexer.next() else {
+                break;
+            };
+            // The specifiers may appear in any order but each at most once
+            let alone = void
+                || va_list
+                || bool
+                || record.is_some()
+                || enumeration.is_some()
+                || typedef.is_some();
+            let integer = char || short || int || int128 || signed.is_some();
+            let floating = float || double || complex;
+            let any = alone || integer || long > 0 || floating;
+            let conflict = match token.kind {
+                // At most one storage class 6.7.1
+                TokenKind::Typedef => storage_class
+                    .replace(StorageClassSpecifier::Typedef)
+                    .is_some(),
+                TokenKind::Extern => storage_class
+                    .replace(StorageClassSpecifier::Extern)
+                    .is_some(),
+                TokenKind::Static => storage_class
+                    .replace(StorageClassSpecifier::Static)
+                    .is_some(),
+                // 6.7.3 Repeated qualifiers behave as if they appeared once
+                TokenKind::Const => {
+                    r#const = true;
+                    false
+                }
+                TokenKind::Void => std::mem::replace(&mut void, true) || any,
+                TokenKind::VaList => std::mem::replace(&mut va_list, true) || any,
+                TokenKind::Bool => std::mem::replace(&mut bool, true) || any,
+                TokenKind::Char => {
+                    std::mem::replace(&mut char, true)
+                        || alone
+                        || short
+                        || int
+                        || long > 0
+                        || int128
+                        || floating
+                }
+                TokenKind::Short => {
+                    std::mem::replace(&mut short, true)
+                        || alone
+                        || char
+                        || long > 0
+                        || int128
+                        || floating
+                }
+                TokenKind::Int => {
+                    std::mem::replace(&mut int, true) || alone || char || int128 || floating
+                }
+                // `long double` is rejected with the type
+                TokenKind::Long => {
+                    long += 1;
+                    long > 2 || alone || char || short || int128 || float || complex
+                }
+                TokenKind::Int128 => {
+                    std::mem::replace(&mut int128, true)
+                        || alone
+                        || char
+                        || short
+                        || int
+                        || long > 0
+                        || floating
+                }
+                TokenKind::Signed => signed.replace(true).is_some() || alone || floating,
+                TokenKind::Unsigned => signed.replace(false).is_some() || alone || floating,
+                TokenKind::Float => {
+                    std::mem::replace(&mut float, true) || alone || integer || long > 0 || double
+                }
+                TokenKind::Double => {
+                    std::mem::replace(&mut double, true) || alone || integer || float
+                }
+                TokenKind::Complex => {
+                    std::mem::replace(&mut complex, true) || alone || integer || long > 0
+                }
+                TokenKind::Struct | TokenKind::Union => {
+                    let kind = if token.kind == TokenKind::Struct {
+                        RecordKind::Struct
+                    } else {
+                        RecordKind::Union
+                    };
+                    record.replace(record_specifier(parser, kind)?).is_some() || any
+                }
+                TokenKind::Enum => enumeration.replace(enum_specifier(parser)?).is_some() || any,
+                // 6.7.8 A typedef name is a type specifier only where no other one is, so that
+                // `T T;` in an inner scope declares an object hiding the type
+                TokenKind::Identifier(ref identifier) if !any => {
+                    match parser.lookup_ordinary(identifier) {
+                        Some(Ordinary::Typedef {
+                            r#type,
+                            r#const: typedef_const,
+                        }) => {
+                            typedef = Some(r#type);
+                            r#const |= typedef_const;
+                            false
+                        }
+                        _ => {
+                            parser.lexer.set_position(pos);
+                            break;
+                        }
+                    }
+                }
+                TokenKind::Alignas => {
+                    let value = alignment_specifier(parser)?;
+                    alignment = alignment.max(Some(value)).filter(|&value| value > 0);
+                    false
+                }
+                _ => {
+                    parser.lexer.set_position(pos);
+                    break;
+                }
+            };
+            if conflict {
+                return Err(ParseError::new(
+                    token.location,
+                    parser.lexer.current_line().to_string(),
+                    format!(
+                        "cannot combine {:?} with previous declaration specifiers",
+                        token.kind
+                    ),
+                ));
+            }
+        }
+
+        let tag_specifier = record.is_some() || enumeration.is_some();
+        let floating = float || double || complex;
+        let r#type = if let Some(record) = record {
+            CType::Record(record)
+        } else if let Some(r#type) = enumeration.or(typedef) {
+            r#type
+        } else if void {
+            CType::Void
+        } else if va_list {
+            CType::VaList
+        } else if bool {
+            CType::BOOL
+        } else if floating {
+            if long > 0 {
+                return Err(ParseError::new(
+                    location,
+                    parser.lexer.current_line().to_string(),
+                    "'long double' is not supported".to_string(),
+                ));
+            }
+            // Plain `_Complex` is taken as `_Complex double` like GCC
+            let kind = if float {
+                FloatingKind::Float
+            } else {
+                FloatingKind::Double
+            };
+            if complex {
+                CType::Complex(kind)
+            } else {
+                CType::Floating(kind)
+            }
+        } else if char {
+            match signed {
+                Some(signed) => CType::Integer {
+                    kind: IntegerKind::Char,
+                    signed,
+                },
+                None => parser.char_type(),
+            }
+        } else if short || int || long > 0 || int128 || signed.is_some() {
+            let kind = match long {
+                _ if short => IntegerKind::Short,
+                _ if int128 => IntegerKind::Int128,
+                0 => IntegerKind::Int,
+                1 => IntegerKind::Long,
+                _ => IntegerKind::LongLong,
+            };
+            CType::Integer {
+                kind,
+                signed: signed.unwrap_or(true),
+            }
+        } else {
+            return Err(ParseError::new(
+                location,
+                parser.lexer.current_line().to_string(),
+                "expected type specifier".to_string(),
+            ));
+        };
+        Ok(DeclarationSpecifiers {
+            location,
+            storage_class,
+            r#const,
+            r#type,
+            alignment,
+            tag_specifier,
+        })
+    }
+}
+
+// 6.7.2.1 Following `struct` or `union`. Tags are resolved while parsing, since the layout is
+// needed by constant expressions such as `sizeof`.
+fn record_specifier(parser: &mut Parser, kind: RecordKind) -> Result<Rc<Record>, ParseError> {
+    let location = parser.peek_location();
+    let tag = parser.expect_identifier().ok();
+    let brace_location = parser.peek_location();
+    let error = |parser: &Parser, location: Location, message: String| {
+        Err(ParseError::new(
+            location,
+            parser.lexer.current_line().to_string(),
+            message,
+        ))
+    };
+    let mismatch = |tag: &str| {
+        format!(
+            "use of '{}' with tag type that does not match previous declaration",
+            tag
+        )
+    };
+    if parser.expect(TokenKind::LBrace).is_err() {
+        let Some(tag) = tag else {
+            return error(parser, location, "expected identifier or '{'".to_string());
+        };
+        // 6.7.2.3 `struct s;` declares a new type in the current scope even if an outer one has
+        // the tag
+        let pos = parser.lexer.current_position();
+        let declaration = parser.expect(TokenKind::SemiColon).is_ok();
+        parser.lexer.set_position(pos);
+        let visible = if declaration {
+            parser.current_tag(&tag)
+        } else {
+            parser.lookup_tag(&tag)
+        };
+        return match visible {
+            Some(Tag::Record(record)) if record.kind == kind => Ok(record),
+            Some(_) => error(parser, location, mismatch(&tag)),
+            None => {
+                let record = Rc::new(Record::new(kind, Some(tag.clone())));
+                parser.declare_tag(tag, Tag::Record(record.clone()));
+                Ok(record)
+            }
+        };
+    }
+
+    let record = match tag {
+        Some(tag) => match parser.current_tag(&tag) {
+            Some(Tag::Record(record)) if record.kind == kind => {
+                // The same definition is seen again after backtracking
+                if record
+                    .layout()
+                    .is_some_and(|layout| layout.location != brace_location)
+                {
+                    return error(parser, location, format!("redefinition of '{}'", tag));
+                }
+                record
+            }
+            Some(_) => return error(parser, location, mismatch(&tag)),
+            // Declared before the members so that they can point to it
+            None => {
+                let record = Rc::new(Record::new(kind, Some(tag.clone())));
+                parser.declare_tag(tag, Tag::Record(record.clone()));
+                record
+            }
+        },
+        None => Rc::new(Record::new(kind, None)),
+    };
+    let pack = parser.lexer.pack_alignment();
+    let mut members = Vec::new();
+    while parser.expect(TokenKind::RBrace).is_err() {
+        member_declaration(parser, pack, &mut members)?;
+    }
+    if record.layout().is_none() {
+        record.define(Layout::new(kind, brace_location, members));
+    }
+    Ok(record)
+}
+
+// 6.7.2.2 Following `enum`. Enumeration constants are declared as they are parsed, so that
+// later ones can refer to them. The type is unsigned int unless a constant is negative, as in
+// GCC.
+fn enum_specifier(parser: &mut Parser) -> Result<CType, ParseError> {
+    let location = parser.peek_location();
+    let tag = parser.expect_identifier().ok();
+    let brace_location = parser.peek_location();
+    let error = |parser: &Parser, location: Location, message: String| {
+        Err(ParseError::new(
+            location,
+            parser.lexer.current_line().to_string(),
+            message,
+        ))
+    };
+    let mismatch = |tag: &str| {
+        format!(
+            "use of '{}' with tag type that does not match previous declaration",
+            tag
+        )
+    };
+    if parser.expect(TokenKind::LBrace).is_err() {
+        let Some(tag) = tag else {
+            return error(parser, location, "expected identifier or '{'".to_string());
+        };
+        return match parser.lookup_tag(&tag) {
+            Some(Tag::Enum { r#type, .. }) => Ok(r#type),
+            Some(Tag::Record(_)) => error(parser, location, mismatch(&tag)),
+            None => error(
+                parser,
+                location,
+                "ISO C forbids forward references to 'enum' types".to_string(),
+            ),
+        };
+    }
+    if let Some(tag) = &tag {
+        match parser.current_tag(tag) {
+            // The same definition is seen again after backtracking
+            Some(Tag::Enum { location, .. }) if location == brace_location => {}
+            Some(Tag::Enum { .. }) => {
+                return error(parser, location, format!("redefinition of '{}'", tag))
+            }
+            Some(Tag::Record(_)) => return error(parser, location, mismatch(tag)),
+            None => {}
+        }
+    }
+
+    let mut value: i128 = 0;
+    let mut negative = false;
+    loop {
+        let location = parser.peek_location();
+        let identifier = parser.expect_identifier()?;
+        if parser.expect(TokenKind::Equal).is_ok() {
+            let expression = ConstantExpression::parse(parser)?;
+            value = match expression.evaluate() {
+                Ok(constant) => constant.value,
+                Err(_) => {
+                    return error(
+                        parser,
+                        expression.location,
+                        "expression is not an integer constant expression".to_string(),
+                    )
+                }
+            };
+        }
+        if value < i32::MIN as i128 || value > i32::MAX as i128 {
+            return error(
+                parser,
+                location,
+                format!(
+                    "ISO C restricts enumerator values to range of 'int' ({} is too large)",
+                    value
+                ),
+            );
+        }
+        match parser.current_ordinary(&identifier) {
+            Some(Ordinary::Enumerator {
+                location: previous, ..
+            }) if previous == location => {}
+            Some(Ordinary::Enumerator { .. }) => {
+                return error(
+                    parser,
+                    location,
+                    format!("redefinition of enumerator '{}'", identifier),
+                )
+            }
+            Some(Ordinary::Object | Ordinary::Typedef { .. }) => {
+                return error(
+                    parser,
+                    location,
+                    format!(
+                        "redefinition of '{}' as different kind of symbol",
+                        identifier
+                    ),
+                )
+            }
+            None => {}
+        }
+        parser.declare_ordinary(
+            identifier,
+            Ordinary::Enumerator {
+                value,
+                location: location.clone(),
+            },
+        );
+        negative |= value < 0;
+        value += 1;
+        if parser.expect(TokenKind::Comma).is_err() {
+            parser.expect(TokenKind::RBrace)?;
+            break;
+        }
+        // A trailing comma is allowed
+        if parser.expect(TokenKind::RBrace).is_ok() {
+            break;
+        }
+    }
+    let r#type = CType::Integer {
+        kind: IntegerKind::Int,
+        signed: negative,
+    };
+    if let Some(tag) = tag {
+        parser.declare_tag(
+            tag,
+            Tag::Enum {
+                r#type: r#type.clone(),
+                location: brace_location,
+            },
+        );
+    }
+    Ok(r#type)
+}
+
+// 6.7.2.1 A struct-declaration, whose members are appended to `members`. `pack` is the maximum
+// alignment set by `#pragma pack`.
+fn member_declaration(
+    parser: &mut Parser,
+    pack: Option<u64>,
+    members: &mut Vec<Member>,
+) -> Result<(), ParseError> {
+    let declaration_specifiers = DeclarationSpecifiers::parse(parser)?;
+    let error = |parser: &Parser, location: Location, message: String| {
+        Err(ParseError::new(
+            location,
+            parser.lexer.current_line().to_string(),
+            message,
+        ))
+    };
+    if declaration_specifiers.storage_class.is_some() {
+        return error(
+            parser,
+            declaration_specifiers.location,
+            "type name does not allow storage class to be specified".to_string(),
+        );
+    }
+    let mut declarators = Vec::new();
+    // An anonymous structure or union, whose members are those of the enclosing one
+    if let Ok(token) = parser.expect(TokenKind::SemiColon) {
+        match &declaration_specifiers.r#type {
+            CType::Record(record) if record.tag.is_none() => declarators.push((
+                token.location,
+                None,
+                declaration_specifiers.r#type.clone(),
+                None,
+            )),
+            _ => {
+                return error(
+                    parser,
+                    token.location,
+                    "declaration does not declare anything".to_string(),
+                )
+            }
+        }
+    } else {
+        loop {
+            // An unnamed bit-field only pads
+            let pos = parser.lexer.current_position();
+            let (location, identifier, derivations) = match parser.expect(TokenKind::Colon) {
+                Ok(token) => {
+                    parser.lexer.set_position(pos);
+                    (token.location, None, Vec::new())
+                }
+                Err(_) => declarator(parser, DeclaratorKind::Concrete)?,
+            };
+            let (r#type, _) = constant_type(
+                parser,
+                &declaration_specifiers,
+                identifier.as_deref(),
+                &derivations,
+                "fields must have a constant size",
+            )?;
+            let width = match parser.expect(TokenKind::Colon) {
+                Ok(_) => Some(bit_field_width(parser, identifier.as_deref(), &r#type)?),
+                Err(_) => None,
+            };
+            declarators.push((location, identifier, r#type, width));
+            if parser.expect(TokenKind::Comma).is_err() {
+                break;
+            }
+        }
         parser.expect(TokenKind::SemiColon)?;
-        Ok(Declaration::NoAttr { init_declarator })
     }
+
+    for (location, identifier, r#type, width) in declarators {
+        if !r#type.is_complete() {
+            return error(
+                parser,
+                location,
+                format!("field has incomplete type '{}'", r#type),
+            );
+        }
+        if let Some(identifier) = &identifier {
+            let duplicate =
+                members
+                    .iter()
+                    .any(|member| match (&member.identifier, &member.r#type) {
+                        (Some(name), _) => name == identifier,
+                        (None, CType::Record(record)) => record.find_member(identifier).is_some(),
+                        _ => false,
+                    });
+            if duplicate {
+                return error(
+                    parser,
+                    location,
+                    format!("duplicate member '{}'", identifier),
+                );
+            }
+        }
+        let alignment = r#type.alignment();
+        if declaration_specifiers
+            .alignment
+            .is_some_and(|requested| requested < alignment)
+        {
+            return error(
+                parser,
+                location,
+                format!(
+                    "requested alignment is less than minimum alignment of {} for type '{}'",
+                    alignment, r#type
+                ),
+            );
+        }
+        let alignment = declaration_specifiers.alignment.unwrap_or(alignment);
+        members.push(Member {
+            identifier,
+            r#type,
+            // 6.7.3 Qualifiers of an array apply to its elements
+            r#const: declaration_specifiers.r#const,
+            // `#pragma pack` takes precedence over `_Alignas`, as in GCC
+            alignment: pack.map_or(alignment, |pack| alignment.min(pack)),
+            offset: 0,
+            bit_field: width.map(|width| BitField {
+                width,
+                bit_offset: 0,
+            }),
+        });
+    }
+    Ok(())
+}
+
+// 6.7.6 The type of a declarator of a member, a typedef or a type name, whose array lengths are
+// known while parsing. `message` is for a length that is not an integer constant expression.
+// Returns it with whether the declared identifier itself is const.
+fn constant_type(
+    parser: &Parser,
+    declaration_specifiers: &DeclarationSpecifiers,
+    identifier: Option<&str>,
+    derivations: &[Derivation],
+    message: &str,
+) -> Result<(CType, bool), ParseError> {
+    let identifier = identifier.unwrap_or_default();
+    let error = |location: &Location, message: String| {
+        ParseError::new(
+            location.clone(),
+            parser.lexer.current_line().to_string(),
+            message,
+        )
+    };
+    declaration_specifiers.derived_type(derivations, |_, derivation, r#type| {
+        if let Some(message) = derivation.check(identifier, &r#type) {
+            return Err(error(derivation.location(), message));
+        }
+        match derivation {
+            Derivation::Array(array) => {
+                let length = array.length.as_ref().map(AssignmentExpression::evaluate);
+                match length {
+                    Some(Ok(length)) if length.value > 0 && length.value <= u32::MAX as i128 => {
+                        Ok(CType::Array {
+                            element: Box::new(r#type),
+                            length: length.value as u64,
+                        })
+                    }
+                    Some(Ok(_)) => Err(error(
+                        &array.location,
+                        format!("array '{}' has an invalid size", identifier),
+                    )),
+                    _ => Err(error(&array.location, message.to_string())),
+                }
+            }
+            Derivation::Function(parameter_type_list) => Ok(CType::Function {
+                return_type: Box::new(r#type),
+                parameters: parameter_types(parser, parameter_type_list)?,
+                variadic: parameter_type_list.variadic,
+            }),
+            Derivation::Pointer(_) => unreachable!(),
+        }
+    })
+}
+
+// 6.7.6.3 The adjusted types of the parameters of a function type derived while parsing
+fn parameter_types(
+    parser: &Parser,
+    parameter_type_list: &ParameterTypeList,
+) -> Result<Vec<CType>, ParseError> {
+    let mut parameters = Vec::new();
+    for parameter in &parameter_type_list.parameters {
+        let error = |message: &str| {
+            Err(ParseError::new(
+                parameter.location.clone(),
+                parser.lexer.current_line().to_string(),
+                message.to_string(),
+            ))
+        };
+        // `register` is the only one allowed, which is not supported
+        if parameter.declaration_specifiers.storage_class.is_some() {
+            return error("invalid storage class specifier in function declarator");
+        }
+        let specifiers = &parameter.declaration_specifiers;
+        let identifier = parameter.declarator.as_deref();
+        let message = "variable length array parameters of function types are not supported";
+        let r#type = match parameter.derivations.split_last() {
+            // An array is adjusted to a pointer to its element, whose length is not used
+            Some((last @ Derivation::Array(_), derivations)) => {
+                let (element, r#const) =
+                    constant_type(parser, specifiers, identifier, derivations, message)?;
+                if let Some(message) = last.check(identifier.unwrap_or_default(), &element) {
+                    return error(&message);
+                }
+                CType::Pointer {
+                    pointee: Box::new(element),
+                    r#const,
+                }
+            }
+            _ => match constant_type(
+                parser,
+                specifiers,
+                identifier,
+                &parameter.derivations,
+                message,
+            )? {
+                // A typedef of an array type is adjusted as well
+                (CType::Array { element, .. }, r#const) => CType::Pointer {
+                    pointee: element,
+                    r#const,
+                },
+                // A function is adjusted to a pointer to it
+                (r#type @ CType::Function { .. }, _) => CType::Pointer {
+                    pointee: Box::new(r#type),
+                    r#const: false,
+                },
+                (r#type, _) => r#type,
+            },
+        };
+        if r#type.is_void() {
+            return error("'void' must be the first and only parameter if specified");
+        }
+        parameters.push(r#type);
+    }
+    Ok(parameters)
+}
+
+// 6.7.2.1 The width of a bit-field following `:`, which is an integer constant expression not
+// exceeding the width of its integer type. Only an unnamed bit-field may have zero width.
+fn bit_field_width(
+    parser: &mut Parser,
+    identifier: Option<&str>,
+    r#type: &CType,
+) -> Result<u64, ParseError> {
+    let expression = ConstantExpression::parse(parser)?;
+    let name = match identifier {
+        Some(identifier) => format!("bit-field '{}'", identifier),
+        None => "anonymous bit-field".to_string(),
+    };
+    let message = if !r#type.is_integer() {
+        format!("{} has non-integral type '{}'", name, r#type)
+    } else {
+        match expression.evaluate() {
+            Ok(width) if width.value < 0 => {
+                format!("{} has negative width ({})", name, width.value)
+            }
+            Ok(width) if width.value == 0 && identifier.is_some() => {
+                format!("named {} has zero width", name)
+            }
+            // 6.2.6.2 The width of `_Bool` is 1
+            Ok(width) if *r#type == CType::BOOL && width.value > 1 => format!(
+                "width of {} ({} bits) exceeds the width of its type (1 bit)",
+                name, width.value
+            ),
+            Ok(width) if width.value > r#type.width() as i128 => format!(
+                "width of {} ({} bits) exceeds the width of its type ({} bits)",
+                name,
+                width.value,
+                r#type.width()
+            ),
+            Ok(width) => return Ok(width.value as u64),
+            Err(_) => format!(
+                "{} has a width that is not an integer constant expression",
+                name
+            ),
+        }
+    };
+    Err(ParseError::new(
+        expression.location,
+        parser.lexer.current_line().to_string(),
+        message,
+    ))
+}
+
+// 6.7.5 The operand of `_Alignas` in parentheses, which is a power of two or 0 for no effect.
+// `_Alignas(type-name)` is `_Alignas(_Alignof(type-name))`.
+fn alignment_specifier(parser: &mut Parser) -> Result<u64, ParseError> {
+    let location = parser.expect(TokenKind::LParen)?.location;
+    let alignment = if parser.at_declaration_specifiers() {
+        let type_name = TypeName::parse(parser)?;
+        parser.expect(TokenKind::RParen)?;
+        let r#type = type_name.r#type();
+        if !r#type.is_complete() {
+            return Err(ParseError::new(
+                type_name.location,
+                parser.lexer.current_line().to_string(),
+                format!(
+                    "invalid application of '_Alignas' to an incomplete type '{}'",
+                    r#type
+                ),
+            ));
+        }
+        return Ok(r#type.alignment());
+    } else {
+        let expression = ConstantExpression::parse(parser)?;
+        parser.expect(TokenKind::RParen)?;
+        expression.evaluate()
+    };
+    match alignment.map(|alignment| u64::try_from(alignment.value)) {
+        Ok(Ok(alignment)) if alignment == 0 || alignment.is_power_of_two() => Ok(alignment),
+        Ok(_) => Err(ParseError::new(
+            location,
+            parser.lexer.current_line().to_string(),
+            "requested alignment is not a power of 2".to_string(),
+        )),
+        Err(_) => Err(ParseError::new(
+            location,
+            parser.lexer.current_line().to_string(),
+            "'_Alignas' requires an integer constant expression".to_string(),
+        )),
+    }
+}
+
+impl DeclarationSpecifiers {
+    /// 6.7.6 Derives the type of a declarator with `derivations`, where `derive` derives an array
+    /// or a function from the type so far with the index of the derivation. Returns it with
+    /// whether the declared identifier itself is const.
+    pub fn derived_type<E>(
+        &self,
+        derivations: &[Derivation],
+        mut derive: impl FnMut(usize, &Derivation, CType) -> Result<CType, E>,
+    ) -> Result<(CType, bool), E> {
+        let mut r#type = self.r#type.clone();
+        let mut r#const = self.r#const;
+        for (index, derivation) in derivations.iter().enumerate() {
+            match derivation {
+                Derivation::Pointer(pointer) => {
+                    r#type = CType::Pointer {
+                        pointee: Box::new(r#type),
+                        r#const,
+                    };
+                    r#const = pointer.r#const;
+                }
+                // 6.7.3 Qualifiers of an array apply to its elements
+                Derivation::Array(_) => r#type = derive(index, derivation, r#type)?,
+                // 6.7.3 Qualifiers of the return type are meaningless
+                Derivation::Function(_) => {
+                    r#type = derive(index, derivation, r#type)?;
+                    r#const = false;
+                }
+            }
+        }
+        Ok((r#type, r#const))
+    }
+}
+
+// 6.7.6 A declarator flattened into the derivations applied to the type of the declaration
+// specifiers, in order. `int *(*x)[4]` derives a pointer, an array of 4 and then a pointer, so
+// `x` is a pointer to an array of pointers.
+#[derive(Debug)]
+pub enum Derivation {
+    Pointer(Pointer),
+    Array(ArrayDeclarator),
+    Function(ParameterTypeList),
+}
+
+impl Derivation {
+    pub fn location(&self) -> &Location {
+        match self {
+            Derivation::Pointer(pointer) => &pointer.location,
+            Derivation::Array(array) => &array.location,
+            Derivation::Function(parameter_type_list) => &parameter_type_list.location,
+        }
+    }
+
+    /// 6.7.6.2, 6.7.6.3 Why the derivation cannot apply to `r#type` in the declarator of
+    /// `identifier`, if it cannot
+    pub fn check(&self, identifier: &str, r#type: &CType) -> Option<String> {
+        match (self, r#type) {
+            (Derivation::Array(_), CType::Void) => {
+                Some("array has incomplete element type 'void'".to_string())
+            }
+            (Derivation::Array(_), CType::Function { .. }) => Some(format!(
+                "'{}' declared as array of functions of type '{}'",
+                identifier, r#type
+            )),
+            (Derivation::Function(_), _) => check_return_type(r#type),
+            _ => None,
+        }
+    }
+}
+
+// 6.7.6.3 Why a function cannot return `r#type`, if it cannot
+pub fn check_return_type(r#type: &CType) -> Option<String> {
+    match r#type {
+        CType::Array { .. } | CType::VariableArray { .. } => {
+            Some(format!("function cannot return array type '{}'", r#type))
+        }
+        CType::Function { .. } => {
+            Some(format!("function cannot return function type '{}'", r#type))
+        }
+        _ => None,
+    }
+}
+
+// Whether a declarator has an identifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum DeclaratorKind {
+    Concrete,
+    // 6.7.7 As in a type name
+    Abstract,
+    // 6.7.6.3 As in a parameter declaration
+    Either,
+}
+
+// 6.7.6 Parses a declarator into where its identifier is or would be, the identifier, which a
+// concrete declarator has, and its derivations
+pub(super) fn declarator(
+    parser: &mut Parser,
+    kind: DeclaratorKind,
+) -> Result<(Location, Option<EcoString>, Vec<Derivation>), ParseError> {
+    let pointers: Vec<Pointer> = parser.many0()?;
+    let mut location = parser.peek_location();
+    let mut identifier = match kind {
+        DeclaratorKind::Abstract => None,
+        _ => parser.expect_identifier().ok(),
+    };
+    let mut inner = Vec::new();
+    // A parenthesized declarator, unless the parenthesis begins parameters
+    let pos = parser.lexer.current_position();
+    if identifier.is_none() && parser.expect(TokenKind::LParen).is_ok() {
+        if parser.at_declaration_specifiers() || parser.expect(TokenKind::RParen).is_ok() {
+            parser.lexer.set_position(pos);
+        } else {
+            (location, identifier, inner) = declarator(parser, kind)?;
+            parser.expect(TokenKind::RParen)?;
+        }
+    }
+    if identifier.is_none() && kind == DeclaratorKind::Concrete {
+        parser.expect_identifier()?;
+    }
+    let mut suffixes = Vec::new();
+    loop {
+        let pos = parser.lexer.current_position();
+        let array = parser.expect(TokenKind::LBracket).is_ok();
+        let function = !array && parser.expect(TokenKind::LParen).is_ok();
+        parser.lexer.set_position(pos);
+        if array {
+            suffixes.push(Derivation::Array(ArrayDeclarator::parse(parser)?));
+        } else if function {
+            suffixes.push(Derivation::Function(ParameterTypeList::parse(parser)?));
+        } else {
+            break;
+        }
+    }
+    // The pointers apply first, and the suffixes bind tighter than an enclosing declarator
+    let derivations = pointers
+        .into_iter()
+        .map(Derivation::Pointer)
+        .chain(suffixes.into_iter().rev())
+        .chain(inner)
+        .collect();
+    Ok((location, identifier, derivations))
+}
+
+// 6.7.6.1 A `*` with its type qualifiers
+#[derive(Debug)]
+pub struct Pointer {
+    pub location: Location,
+    pub r#const: bool,
+}
+
+impl Parse for Pointer {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let location = parser.expect(TokenKind::Asterisk)?.location;
+        let mut r#const = false;
+        // 6.7.3 Repeated qualifiers behave as if they appeared once
+        while parser.expect(TokenKind::Const).is_ok() {
+            r#const = true;
+        }
+        Ok(Pointer { location, r#const })
+    }
+}
+
+// 6.7.6.2 `[N]` or `[]`
+#[derive(Debug)]
+pub struct ArrayDeclarator {
+    pub location: Location,
+    pub length: Option<AssignmentExpression>,
+}
+
+impl Parse for ArrayDeclarator {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let location = parser.expect(TokenKind::LBracket)?.location;
+        let length = if parser.expect(TokenKind::RBracket).is_ok() {
+            None
+        } else {
+            let length = AssignmentExpression::parse(parser)?;
+            parser.expect(TokenKind::RBracket)?;
+            Some(length)
+        };
+        Ok(ArrayDeclarator { location, length })
+    }
+}
+
+#[derive(Debug)]
+pub struct InitDeclarator {
+    pub location: Location,
+    pub declarator: EcoString,
+    pub derivations: Vec<Derivation>,
+    pub initializer: Option<Initializer>,
+}
+
+impl Parse for InitDeclarator {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let (location, declarator, derivations) = declarator(parser, DeclaratorKind::Concrete)?;
+        let declarator = declarator.unwrap();
+        // 6.2.1 The scope begins just after the declarator, so the initializer may refer to it
+        parser.declare_object(&declarator, &location)?;
+        let initializer = if parser.expect(TokenKind::Equal).is_ok() {
+            Some(Initializer::parse(parser)?)
+        } else {
+            None
+        };
+        Ok(InitDeclarator {
+            location,
+            declarator,
+            derivations,
+            initializer,
+        })
+    }
+}
+
+// 6.7.9
+#[derive(Debug)]
+pub enum Initializer {
+    Expression {
+        location: Location,
+        expression: AssignmentExpression,
+    },
+    // A brace-enclosed list, which may be empty like C23
+    List {
+        location: Location,
+        initializers: Vec<DesignatedInitializer>,
+    },
+}
+
+impl Parse for Initializer {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let location = parser.peek_location();
+        if parser.expect(TokenKind::LBrace).is_err() {
+            let expression = AssignmentExpression::parse(parser)?;
+            return Ok(Initializer::Expression {
+                location,
+                expression,
+            });
+        }
+        let mut initializers = Vec::new();
+        while parser.expect(TokenKind::RBrace).is_err() {
+            initializers.push(DesignatedInitializer::parse(parser)?);
+            // A trailing comma is allowed
+            if parser.expect(TokenKind::Comma).is_err() {
+                parser.expect(TokenKind::RBrace)?;
+                break;
+            }
+        }
+        Ok(Initializer::List {
+            location,
+            initializers,
+        })
+    }
+}
+
+impl Initializer {
+    pub fn location(&self) -> &Location {
+        match self {
+            Initializer::Expression { location, .. } | Initializer::List { location, .. } => {
+                location
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DesignatedInitializer {
+    pub designators: Vec<Designator>,
+    pub initializer: Initializer,
+}
+
+impl Parse for DesignatedInitializer {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let designators = parser.many0()?;
+        if !designators.is_empty() {
+            parser.expect(TokenKind::Equal)?;
+        }
+        let initializer = Initializer::parse(parser)?;
+        Ok(DesignatedInitializer {
+            designators,
+            initializer,
+        })
+    }
+}
+
+// 6.7.9
+#[derive(Debug)]
+pub enum Designator {
+    Index(ConstantExpression),
+    Member {
+        location: Location,
+        identifier: EcoString,
+    },
+}
+
+impl Parse for Designator {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let location = parser.peek_location();
+        if parser.expect(TokenKind::LBracket).is_ok() {
+            let index = ConstantExpression::parse(parser)?;
+            parser.expect(TokenKind::RBracket)?;
+            return Ok(Designator::Index(index));
+        }
+        parser.expect(TokenKind::Dot)?;
+        let identifier = parser.expect_identifier()?;
+        Ok(Designator::Member {
+            location,
+            identifier,
+        })
+    }
+}
+
+impl Designator {
+    pub fn location(&self) -> &Location {
+        match self {
+            Designator::Index(expression) => &expression.location,
+            Designator::Member { location, .. } => location,
+        }
+    }
+}
+
+// 6.7.6.3
+#[derive(Debug)]
+pub struct ParameterDeclaration {
+    pub location: Location,
+    pub declaration_specifiers: DeclarationSpecifiers,
+    // Omitted in an abstract declarator
+    pub declarator: Option<EcoString>,
+    pub derivations: Vec<Derivation>,
+}
+
+impl Parse for ParameterDeclaration {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let location = parser.peek_location();
+        let declaration_specifiers = DeclarationSpecifiers::parse(parser)?;
+        let (_, declarator, derivations) = declarator(parser, DeclaratorKind::Either)?;
+        Ok(ParameterDeclaration {
+            location,
+            declaration_specifiers,
+            declarator,
+            derivations,
+        })
+    }
+}
+
+// 6.7.7 Resolved while parsing, since the lengths of its arrays are constants
+#[derive(Debug)]
+pub struct TypeName {
+    pub location: Location,
+    r#type: CType,
+}
+
+impl Parse for TypeName {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let location = parser.peek_location();
+        let declaration_specifiers = DeclarationSpecifiers::parse(parser)?;
+        let (_, _, derivations) = declarator(parser, DeclaratorKind::Abstract)?;
+        let (r#type, _) = constant_type(
+            parser,
+            &declaration_specifiers,
+            None,
+            &derivations,
+            "variable length array type names are not supported",
+        )?;
+        Ok(TypeName { location, r#type })
+    }
+}
+
+impl TypeName {
+    pub fn r#type(&self) -> CType {
+        self.r#type.clone()
+    }
+}
+
+// Including the parentheses
+#[derive(Debug)]
+pub struct ParameterTypeList {
+    // Of the opening parenthesis
+    pub location: Location,
+    pub parameters: Vec<ParameterDeclaration>,
+    // Ends with `, ...`
+    pub variadic: bool,
+}
+
+impl Parse for ParameterTypeList {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let location = parser.expect(TokenKind::LParen)?.location;
+        if parser.expect(TokenKind::RParen).is_ok() {
+            return Ok(ParameterTypeList {
+                location,
+                parameters: Vec::new(),
+                variadic: false,
+            });
+        }
+        let mut parameters = vec![ParameterDeclaration::parse(parser)?];
+        let mut variadic = false;
+        while parser.expect(TokenKind::Comma).is_ok() {
+            if parser.expect(TokenKind::Ellipsis).is_ok() {
+                variadic = true;
+                break;
+            }
+            parameters.push(ParameterDeclaration::parse(parser)?);
+        }
+        parser.expect(TokenKind::RParen)?;
+
+        // A single unnamed `void` means no parameters
+        if matches!(
+            parameters.as_slice(),
+            [ParameterDeclaration {
+                declaration_specifiers:
+                    DeclarationSpecifiers {
+                        r#type: CType::Void,
+                        ..
+                    },
+                declarator: None,
+                derivations,
+                ..
+            }] if derivations.is_empty()
+        ) {
+            parameters.clear();
+        }
+        Ok(ParameterTypeList {
+            location,
+            parameters,
+            variadic,
+        })
+    }
+}
+
+#[test]
+fn test_parameter_type_list() {
+    use crate::lexer::Lexer;
+
+    let src = "(void) (int a, unsigned, ...) (signed int int)"
+        .as_bytes()
+        .to_vec();
+    let mut parser = Parser::new(Lexer::new("test.c".into(), src));
+
+    let parameter_type_list = ParameterTypeList::parse(&mut parser).unwrap();
+    assert!(parameter_type_list.parameters.is_empty());
+    assert!(!parameter_type_list.variadic);
+    let ParameterTypeList {
+        parameters,
+        variadic,
+        ..
+    } = ParameterTypeList::parse(&mut parser).unwrap();
+    assert!(variadic);
+    assert_eq!(parameters.len(), 2);
+    assert_eq!(parameters[0].declarator.as_deref(), Some("a"));
+    assert_eq!(
+        parameters[1].declaration_specifiers.r#type,
+        CType::Integer {
+            kind: IntegerKind::Int,
+            signed: false
+        }
+    );
+    assert!(ParameterTypeList::parse(&mut parser).is_err());
+}
+
+#[test]
+fn test_pointer_declarator() {
+    use crate::lexer::Lexer;
+
+    let src = "const int *const *p = 0; (void *)".as_bytes().to_vec();
+    let mut parser = Parser::new(Lexer::new("test.c".into(), src));
+
+    let Declaration::NoAttr {
+        declaration_specifiers,
+        init_declarator_list,
+    } = Declaration::parse(&mut parser).unwrap()
+    else {
+        panic!("expected a declarator");
+    };
+    let [init_declarator] = init_declarator_list.as_slice() else {
+        panic!("expected a single declarator");
+    };
+    assert_eq!(init_declarator.declarator, "p");
+    let pointers = |_: usize, _: &Derivation, _: CType| -> Result<CType, ()> { unreachable!() };
+    let (r#type, r#const) = declaration_specifiers
+        .derived_type(&init_declarator.derivations, pointers)
+        .unwrap();
+    assert!(!r#const);
+    assert_eq!(r#type.to_string(), "const int *const *");
+    let ParameterTypeList { parameters, .. } = ParameterTypeList::parse(&mut parser).unwrap();
+    let (r#type, _) = parameters[0]
+        .declaration_specifiers
+        .derived_type(&parameters[0].derivations, pointers)
+        .unwrap();
+    assert_eq!(r#type.to_string(), "void *");
+}
+
+#[test]
+fn test_array_declarator() {
+    use crate::lexer::Lexer;
+
+    let src = "char *m[2][N] = \"abc\"; (int a[], char)"
+        .as_bytes()
+        .to_vec();
+    let mut parser = Parser::new(Lexer::new("test.c".into(), src));
+
+    let Declaration::NoAttr {
+        declaration_specifiers,
+        init_declarator_list,
+    } = Declaration::parse(&mut parser).unwrap()
+    else {
+        panic!("expected a declarator");
+    };
+    let [init_declarator] = init_declarator_list.as_slice() else {
+        panic!("expected a single declarator");
+    };
+    assert_eq!(declaration_specifiers.r#type, CType::CHAR);
+    // The innermost array applies first
+    let [Derivation::Pointer(_), Derivation::Array(inner), Derivation::Array(outer)] =
+        init_declarator.derivations.as_slice()
+    else {
+        panic!("expected a pointer and two arrays");
+    };
+    assert!(inner.length.is_some());
+    assert!(outer.length.is_some());
+    assert!(init_declarator.initializer.is_some());
+    let ParameterTypeList { parameters, .. } = ParameterTypeList::parse(&mut parser).unwrap();
+    assert_eq!(parameters.len(), 2);
+    assert!(matches!(
+        parameters[0].derivations.as_slice(),
+        [Derivation::Array(ArrayDeclarator { length: None, .. })]
+    ));
+    assert!(parameters[1].derivations.is_empty());
+}
+
+#[test]
+fn test_integer_type_specifiers() {
+    use crate::lexer::Lexer;
+
+    let parse = |src: &str, unsigned_char: bool| {
+        let mut parser = Parser::new(Lexer::new("test.c".into(), src.as_bytes().to_vec()));
+        parser.set_unsigned_char(unsigned_char);
+        DeclarationSpecifiers::parse(&mut parser).map(|specifiers| specifiers.r#type.to_string())
+    };
+    assert_eq!(
+        parse("long unsigned int long", false).unwrap(),
+        "unsigned long long"
+    );
+    assert_eq!(parse("short signed", false).unwrap(), "short");
+    assert_eq!(parse("_Bool", false).unwrap(), "_Bool");
+    assert_eq!(
+        parse("unsigned __int128", false).unwrap(),
+        "unsigned __int128"
+    );
+    assert_eq!(parse("char", true).unwrap(), "char");
+    assert_eq!(parse("signed char", true).unwrap(), "signed char");
+    assert_eq!(parse("char unsigned", true).unwrap(), "unsigned char");
+    assert!(parse("long short", false).is_err());
+    assert!(parse("long long long", false).is_err());
+    assert!(parse("unsigned _Bool", false).is_err());
+    assert_eq!(parse("float _Complex", false).unwrap(), "_Complex float");
+    assert!(parse("long double", false).is_err());
+    assert!(parse("unsigned double", false).is_err());
+}
+
+#[test]
+fn test_initializer() {
+    use crate::lexer::Lexer;
+
+    let src = "{ .a.b = 1, [2] = { 3, }, 4, {} }".as_bytes().to_vec();
+    let mut parser = Parser::new(Lexer::new("test.c".into(), src));
+
+    let Initializer::List { initializers, .. } = Initializer::parse(&mut parser).unwrap() else {
+        panic!("expected an initializer list");
+    };
+    assert_eq!(initializers.len(), 4);
+    assert!(matches!(
+        initializers[0].designators.as_slice(),
+        [Designator::Member { .. }, Designator::Member { identifier, .. }] if identifier == "b"
+    ));
+    assert!(matches!(
+        initializers[1].designators.as_slice(),
+        [Designator::Index(_)]
+    ));
+    assert!(matches!(
+        &initializers[1].initializer,
+        Initializer::List { initializers, .. } if initializers.len() == 1
+    ));
+    assert!(initializers[2].designators.is_empty());
+    assert!(matches!(
+        &initializers[3].initializer,
+        Initializer::List { initializers, .. } if initializers.is_empty()
+    ));
+}
+
+#[test]
+fn test_enum_specifier() {
+    use crate::lexer::Lexer;
+
+    let src = "enum E { A, B = -5, C, }; enum E x[C + 10]; int A;"
+        .as_bytes()
+        .to_vec();
+    let mut parser = Parser::new(Lexer::new("test.c".into(), src));
+
+    let Declaration::Tag { .. } = Declaration::parse(&mut parser).unwrap() else {
+        panic!("expected a tag declaration");
+    };
+    let Declaration::NoAttr {
+        declaration_specifiers,
+        init_declarator_list,
+    } = Declaration::parse(&mut parser).unwrap()
+    else {
+        panic!("expected a declarator");
+    };
+    let [init_declarator] = init_declarator_list.as_slice() else {
+        panic!("expected a single declarator");
+    };
+    assert_eq!(declaration_specifiers.r#type, CType::INT);
+    let [Derivation::Array(ArrayDeclarator {
+        length: Some(length),
+        ..
+    })] = init_declarator.derivations.as_slice()
+    else {
+        panic!("expected an array");
+    };
+    assert_eq!(length.evaluate().unwrap().value, 6);
+    // An object cannot be declared with the name of a constant in the same scope
+    assert!(Declaration::parse(&mut parser).is_err());
+}
+
+#[test]
+fn test_parenthesized_declarator() {
+    use crate::lexer::Lexer;
+
+    let src = "int (*fp)(int); typedef void handler(int); handler *h;"
+        .as_bytes()
+        .to_vec();
+    let mut parser = Parser::new(Lexer::new("test.c".into(), src));
+
+    let Declaration::NoAttr {
+        init_declarator_list,
+        ..
+    } = Declaration::parse(&mut parser).unwrap()
+    else {
+        panic!("expected a declarator");
+    };
+    let [init_declarator] = init_declarator_list.as_slice() else {
+        panic!("expected a single declarator");
+    };
+    assert_eq!(init_declarator.declarator, "fp");
+    let [Derivation::Function(parameter_type_list), Derivation::Pointer(_)] =
+        init_declarator.derivations.as_slice()
+    else {
+        panic!("expected a pointer to a function");
+    };
+    assert_eq!(parameter_type_list.parameters.len(), 1);
+    assert!(matches!(
+        Declaration::parse(&mut parser).unwrap(),
+        Declaration::Typedef { identifiers, .. } if identifiers == ["handler"]
+    ));
+    assert!(matches!(
+        parser.lookup_ordinary("handler"),
+        Some(Ordinary::Typedef {
+            r#type: CType::Function { .. },
+            ..
+        })
+    ));
+    assert!(Declaration::parse(&mut parser).is_ok());
+
+    let parse = |src: &str| {
+        let mut parser = Parser::new(Lexer::new("test.c".into(), src.as_bytes().to_vec()));
+        TypeName::parse(&mut parser).map(|type_name| type_name.r#type().to_string())
+    };
+    assert_eq!(parse("int (*)[4]").unwrap(), "int (*)[4]");
+    assert_eq!(parse("int *(*)[4]").unwrap(), "int *(*)[4]");
+    assert_eq!(
+        parse("int (*)(int, char *)").unwrap(),
+        "int (*)(int, char *)"
+    );
+    // Parameters are adjusted
+    assert_eq!(
+        parse("void (*)(int [2], int (int))").unwrap(),
+        "void (*)(int *, int (*)(int))"
+    );
+    assert!(parse("int (void)[2]").is_err());
+    assert!(parse("int [2](void)").is_err());
+}
+
+#[test]
+fn test_static_assert() {
+    use crate::{
+        lexer::Lexer,
+        parser::statement::{BlockItem, CompoundStatement},
+    };
+
+    let parse = |src: &str| {
+        let mut parser = Parser::new(Lexer::new("test.c".into(), src.as_bytes().to_vec()));
+        Declaration::parse(&mut parser)
+    };
+    let Declaration::StaticAssert {
+        expression,
+        message,
+        ..
+    } = parse(r#"_Static_assert(sizeof(int) == 4, "int " "is 4 bytes");"#).unwrap()
+    else {
+        panic!("expected a static assertion");
+    };
+    assert_eq!(expression.evaluate().unwrap().value, 1);
+    // Adjacent string literals are concatenated
+    assert_eq!(message.as_deref(), Some(b"int is 4 bytes".as_slice()));
+    // Without a message
+    let Declaration::StaticAssert {
+        expression,
+        message,
+        ..
+    } = parse("_Static_assert(1 - 1);").unwrap()
+    else {
+        panic!("expected a static assertion");
+    };
+    assert_eq!(expression.evaluate().unwrap().value, 0);
+    assert!(message.is_none());
+    assert!(matches!(
+        parse("static_assert(1);").unwrap(),
+        Declaration::StaticAssert { .. }
+    ));
+    assert!(parse("_Static_assert(1, 2);").is_err());
+    assert!(parse(r#"_Static_assert(1, "a")"#).is_err());
+    assert!(parse("_Static_assert 1;").is_err());
+
+    // Also a declaration in a block
+    let src = r#"{ int x = 0; _Static_assert(sizeof x == 4, "x"); return x; }"#;
+    let mut parser = Parser::new(Lexer::new("test.c".into(), src.as_bytes().to_vec()));
+    let compound_statement = CompoundStatement::parse(&mut parser).unwrap();
+    assert!(matches!(
+        compound_statement.block_items.as_slice(),
+        [
+            BlockItem::Declaration(Declaration::NoAttr { .. }),
+            BlockItem::Declaration(Declaration::StaticAssert {
+                message: Some(_),
+                ..
+            }),
+            BlockItem::UnlabeledStatement(_),
+        ]
+    ));
+}
+
+#[test]
+fn test_init_declarator_list() {
+    use crate::lexer::Lexer;
+
+    let src = "static int x = 1, *y, z[2] = {x}; typedef int A, *B; B b, c; A (f)(void), g(A);"
+        .as_bytes()
+        .to_vec();
+    let mut parser = Parser::new(Lexer::new("test.c".into(), src));
+
+    let Declaration::NoAttr {
+        declaration_specifiers,
+        init_declarator_list,
+    } = Declaration::parse(&mut parser).unwrap()
+    else {
+        panic!("expected declarators");
+    };
+    assert_eq!(
+        declaration_specifiers.storage_class,
+        Some(StorageClassSpecifier::Static)
+    );
+    let declarators = init_declarator_list
+        .iter()
+        .map(|init_declarator| {
+            (
+                init_declarator.declarator.as_str(),
+                init_declarator.derivations.len(),
+                init_declarator.initializer.is_some(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        declarators,
+        vec![("x", 0, true), ("y", 1, false), ("z", 1, true)]
+    );
+
+    // A typedef name may be used in the declarators following it
+    assert!(matches!(
+        Declaration::parse(&mut parser).unwrap(),
+        Declaration::Typedef { identifiers, .. } if identifiers == ["A", "B"]
+    ));
+    let Declaration::NoAttr {
+        declaration_specifiers,
+        init_declarator_list,
+    } = Declaration::parse(&mut parser).unwrap()
+    else {
+        panic!("expected declarators");
+    };
+    assert_eq!(
+        declaration_specifiers.r#type,
+        CType::Pointer {
+            pointee: Box::new(CType::INT),
+            r#const: false
+        }
+    );
+    assert_eq!(init_declarator_list.len(), 2);
+    let Declaration::NoAttr {
+        init_declarator_list,
+        ..
+    } = Declaration::parse(&mut parser).unwrap()
+    else {
+        panic!("expected declarators");
+    };
+    assert!(init_declarator_list.iter().all(|init_declarator| matches!(
+        init_declarator.derivations.as_slice(),
+        [Derivation::Function(_)]
+    )));
+
+    let parse = |src: &str| {
+        let mut parser = Parser::new(Lexer::new("test.c".into(), src.as_bytes().to_vec()));
+        Declaration::parse(&mut parser)
+    };
+    assert!(parse("int a, ;").is_err());
+    assert!(parse("int a b;").is_err());
+    assert!(parse("typedef int A, B = 1;").is_err());
+    // Each declarator is in the scope of those before it
+    assert!(parse("typedef int a, a;").is_ok());
+    assert!(parse("typedef int a, *a;").is_err());
+    assert!(parse("int a, a;").is_ok());
 }
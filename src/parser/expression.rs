@@ -1,6 +1,13 @@
-use crate::lexer::{Location, TokenKind};
+use std::cmp::Ordering;
 
-use super::{Constant, Parse, ParseError, Parser};
+use ecow::EcoString;
+
+use crate::{
+    lexer::{Location, TokenKind},
+    types::{CType, FloatingKind},
+};
+
+use super::{declaration::TypeName, Constant, Ordinary, Parse, ParseError, Parser};
 
 // 6.5
 
@@ -8,62 +15,537 @@ use super::{Constant, Parse, ParseError, Parser};
 
 #[derive(Debug)]
 pub enum PrimaryExpression {
-    Constant { value: Constant, location: Location },
+    Identifier {
+        identifier: EcoString,
+        location: Location,
+    },
+    Constant {
+        value: Constant,
+        location: Location,
+    },
+    // 6.4.5 Adjacent literals are concatenated. Without the terminating null character.
+    StringLiteral {
+        value: Vec<u8>,
+        // An array of plain char with the terminating null character
+        r#type: CType,
+        location: Location,
+    },
+    Parenthesized(Box<Expression>),
+    VaBuiltin(VaBuiltin),
+    GenericSelection(Box<GenericSelection>),
 }
 
 impl Parse for PrimaryExpression {
     fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
-        let (location, value) = parser.expect_integer()?;
-        Ok(PrimaryExpression::Constant {
-            value: Constant::Integer(value),
+        let location = parser.peek_location();
+        if let Ok(identifier) = parser.expect_identifier() {
+            // 6.4.4.3 An enumeration constant is an integer constant of type int
+            if let Some(Ordinary::Enumerator { value, .. }) = parser.lookup_ordinary(&identifier) {
+                return Ok(PrimaryExpression::Constant {
+                    value: Constant::Integer {
+                        value: value as u64,
+                        r#type: CType::INT,
+                    },
+                    location,
+                });
+            }
+            return Ok(PrimaryExpression::Identifier {
+                identifier,
+                location,
+            });
+        }
+        if let Some(mut value) = parser.expect_string_literal() {
+            while let Some(literal) = parser.expect_string_literal() {
+                value.extend(literal);
+            }
+            let r#type = CType::Array {
+                element: Box::new(parser.char_type()),
+                length: value.len() as u64 + 1,
+            };
+            return Ok(PrimaryExpression::StringLiteral {
+                value,
+                r#type,
+                location,
+            });
+        }
+        if parser.expect(TokenKind::LParen).is_ok() {
+            let expression = Expression::parse(parser)?;
+            parser.expect(TokenKind::RParen)?;
+            return Ok(PrimaryExpression::Parenthesized(Box::new(expression)));
+        }
+        let pos = parser.lexer.current_position();
+        if let Ok(va_builtin) = VaBuiltin::parse(parser) {
+            return Ok(PrimaryExpression::VaBuiltin(va_builtin));
+        }
+        parser.lexer.set_position(pos);
+        if parser.expect(TokenKind::Generic).is_ok() {
+            parser.lexer.set_position(pos);
+            let generic_selection = GenericSelection::parse(parser)?;
+            return Ok(PrimaryExpression::GenericSelection(Box::new(
+                generic_selection,
+            )));
+        }
+        let (location, value) = parser.expect_constant()?;
+        Ok(PrimaryExpression::Constant { value, location })
+    }
+}
+
+// 7.16.1 Variable argument list access
+#[derive(Debug)]
+pub enum VaBuiltin {
+    Start {
+        location: Location,
+        va_list: Box<AssignmentExpression>,
+        // The last named parameter, which is not evaluated
+        parameter: EcoString,
+    },
+    Arg {
+        location: Location,
+        va_list: Box<AssignmentExpression>,
+        // TODO type name
+        r#type: TypeName,
+    },
+    End {
+        location: Location,
+        va_list: Box<AssignmentExpression>,
+    },
+    Copy {
+        location: Location,
+        destination: Box<AssignmentExpression>,
+        source: Box<AssignmentExpression>,
+    },
+}
+
+// 6.5.1.1
+#[derive(Debug)]
+pub struct GenericSelection {
+    pub location: Location,
+    pub controlling_expression: AssignmentExpression,
+    pub associations: Vec<GenericAssociation>,
+}
+
+#[derive(Debug)]
+pub struct GenericAssociation {
+    pub location: Location,
+    // `None` for `default`
+    pub type_name: Option<TypeName>,
+    pub expression: AssignmentExpression,
+}
+
+impl Parse for GenericSelection {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let location = parser.expect(TokenKind::Generic)?.location;
+        parser.expect(TokenKind::LParen)?;
+        let controlling_expression = AssignmentExpression::parse(parser)?;
+        let mut associations = Vec::new();
+        while parser.expect(TokenKind::Comma).is_ok() {
+            let location = parser.peek_location();
+            let type_name = match parser.expect(TokenKind::Default) {
+                Ok(_) => None,
+                Err(_) => Some(TypeName::parse(parser)?),
+            };
+            parser.expect(TokenKind::Colon)?;
+            let expression = AssignmentExpression::parse(parser)?;
+            associations.push(GenericAssociation {
+                location,
+                type_name,
+                expression,
+            });
+        }
+        parser.expect(TokenKind::RParen)?;
+        if associations.is_empty() {
+            return Err(ParseError::new(
+                location,
+                parser.lexer.current_line().to_string(),
+                "expected a generic association".to_string(),
+            ));
+        }
+        Ok(GenericSelection {
             location,
+            controlling_expression,
+            associations,
+        })
+    }
+}
+
+impl GenericSelection {
+    /// The expression of the association whose type is compatible with the type of the
+    /// controlling expression after lvalue conversion, or the default one
+    pub fn select(&self, r#type: &CType) -> Option<&AssignmentExpression> {
+        self.associations
+            .iter()
+            .find(|association| {
+                association
+                    .type_name
+                    .as_ref()
+                    .is_some_and(|type_name| type_name.r#type() == *r#type)
+            })
+            .or_else(|| {
+                self.associations
+                    .iter()
+                    .find(|association| association.type_name.is_none())
+            })
+            .map(|association| &association.expression)
+    }
+}
+
+impl VaBuiltin {
+    pub fn location(&self) -> &Location {
+        match self {
+            VaBuiltin::Start { location, .. }
+            | VaBuiltin::Arg { location, .. }
+            | VaBuiltin::End { location, .. }
+            | VaBuiltin::Copy { location, .. } => location,
+        }
+    }
+}
+
+impl Parse for VaBuiltin {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        if let Ok(t) = parser.expect(TokenKind::VaStart) {
+            parser.expect(TokenKind::LParen)?;
+            let va_list = AssignmentExpression::parse(parser)?;
+            parser.expect(TokenKind::Comma)?;
+            let parameter = parser.expect_identifier()?;
+            parser.expect(TokenKind::RParen)?;
+            return Ok(VaBuiltin::Start {
+                location: t.location,
+                va_list: Box::new(va_list),
+                parameter,
+            });
+        }
+        if let Ok(t) = parser.expect(TokenKind::VaArg) {
+            parser.expect(TokenKind::LParen)?;
+            let va_list = AssignmentExpression::parse(parser)?;
+            parser.expect(TokenKind::Comma)?;
+            let r#type = TypeName::parse(parser)?;
+            parser.expect(TokenKind::RParen)?;
+            return Ok(VaBuiltin::Arg {
+                location: t.location,
+                va_list: Box::new(va_list),
+                r#type,
+            });
+        }
+        if let Ok(t) = parser.expect(TokenKind::VaCopy) {
+            parser.expect(TokenKind::LParen)?;
+            let destination = AssignmentExpression::parse(parser)?;
+            parser.expect(TokenKind::Comma)?;
+            let source = AssignmentExpression::parse(parser)?;
+            parser.expect(TokenKind::RParen)?;
+            return Ok(VaBuiltin::Copy {
+                location: t.location,
+                destination: Box::new(destination),
+                source: Box::new(source),
+            });
+        }
+        let location = parser.expect(TokenKind::VaEnd)?.location;
+        parser.expect(TokenKind::LParen)?;
+        let va_list = AssignmentExpression::parse(parser)?;
+        parser.expect(TokenKind::RParen)?;
+        Ok(VaBuiltin::End {
+            location,
+            va_list: Box::new(va_list),
         })
     }
 }
 
 // 6.5.2
 
-// pub enum PostfixExpression {}
+#[derive(Debug)]
+pub enum PostfixExpression {
+    PrimaryExpression(PrimaryExpression),
+    FunctionCall {
+        function: Box<PostfixExpression>,
+        arguments: Vec<AssignmentExpression>,
+        location: Location,
+    },
+    Increment {
+        expression: Box<PostfixExpression>,
+        location: Location,
+    },
+    Decrement {
+        expression: Box<PostfixExpression>,
+        location: Location,
+    },
+    // 6.5.2.1 `array[index]` is `*(array + index)`, so they may be swapped
+    Subscript {
+        array: Box<PostfixExpression>,
+        index: Box<Expression>,
+        location: Location,
+    },
+    // 6.5.2.3 `structure.identifier`
+    Member {
+        structure: Box<PostfixExpression>,
+        identifier: EcoString,
+        location: Location,
+    },
+    // 6.5.2.3 `pointer->identifier`
+    Arrow {
+        pointer: Box<PostfixExpression>,
+        identifier: EcoString,
+        location: Location,
+    },
+}
+
+impl Parse for PostfixExpression {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let primary_expression = PrimaryExpression::parse(parser)?;
+        let mut expression = PostfixExpression::PrimaryExpression(primary_expression);
+        while {
+            if let Ok(t) = parser.expect(TokenKind::LBracket) {
+                let index = Expression::parse(parser)?;
+                parser.expect(TokenKind::RBracket)?;
+                expression = PostfixExpression::Subscript {
+                    array: Box::new(expression),
+                    index: Box::new(index),
+                    location: t.location,
+                };
+                true
+            } else if let Ok(t) = parser.expect(TokenKind::LParen) {
+                let mut arguments = Vec::new();
+                if parser.expect(TokenKind::RParen).is_err() {
+                    arguments.push(AssignmentExpression::parse(parser)?);
+                    while parser.expect(TokenKind::Comma).is_ok() {
+                        arguments.push(AssignmentExpression::parse(parser)?);
+                    }
+                    parser.expect(TokenKind::RParen)?;
+                }
+                expression = PostfixExpression::FunctionCall {
+                    function: Box::new(expression),
+                    arguments,
+                    location: t.location,
+                };
+                true
+            } else if let Ok(t) = parser.expect(TokenKind::Dot) {
+                expression = PostfixExpression::Member {
+                    structure: Box::new(expression),
+                    identifier: parser.expect_identifier()?,
+                    location: t.location,
+                };
+                true
+            } else if let Ok(t) = parser.expect(TokenKind::Arrow) {
+                expression = PostfixExpression::Arrow {
+                    pointer: Box::new(expression),
+                    identifier: parser.expect_identifier()?,
+                    location: t.location,
+                };
+                true
+            } else if let Ok(t) = parser.expect(TokenKind::PlusPlus) {
+                expression = PostfixExpression::Increment {
+                    expression: Box::new(expression),
+                    location: t.location,
+                };
+                true
+            } else if let Ok(t) = parser.expect(TokenKind::MinusMinus) {
+                expression = PostfixExpression::Decrement {
+                    expression: Box::new(expression),
+                    location: t.location,
+                };
+                true
+            } else {
+                false
+            }
+        } {}
+        Ok(expression)
+    }
+}
 
 // 6.5.3
 
-// pub enum UnaryExpression {}
+#[derive(Debug)]
+pub enum UnaryExpression {
+    PostfixExpression(PostfixExpression),
+    Increment {
+        expression: Box<UnaryExpression>,
+        location: Location,
+    },
+    Decrement {
+        expression: Box<UnaryExpression>,
+        location: Location,
+    },
+    // 6.5.3.2 `&`
+    Address {
+        expression: Box<CastExpression>,
+        location: Location,
+    },
+    // 6.5.3.2 `*`
+    Indirection {
+        expression: Box<CastExpression>,
+        location: Location,
+    },
+    // 6.5.3.3 `+`
+    Plus {
+        expression: Box<CastExpression>,
+        location: Location,
+    },
+    // 6.5.3.3 `-`
+    Minus {
+        expression: Box<CastExpression>,
+        location: Location,
+    },
+    // 6.5.3.3 `~`
+    BitwiseNot {
+        expression: Box<CastExpression>,
+        location: Location,
+    },
+    // 6.5.3.3 `!`
+    LogicalNot {
+        expression: Box<CastExpression>,
+        location: Location,
+    },
+    // 6.5.3.4 The operand is not evaluated
+    SizeofExpression {
+        expression: Box<UnaryExpression>,
+        location: Location,
+    },
+    SizeofType {
+        type_name: Box<TypeName>,
+        location: Location,
+    },
+}
+
+impl Parse for UnaryExpression {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        if let Ok(t) = parser.expect(TokenKind::Sizeof) {
+            let pos = parser.lexer.current_position();
+            if parser.expect(TokenKind::LParen).is_ok() && parser.at_declaration_specifiers() {
+                let type_name = TypeName::parse(parser)?;
+                parser.expect(TokenKind::RParen)?;
+                return Ok(UnaryExpression::SizeofType {
+                    type_name: Box::new(type_name),
+                    location: t.location,
+                });
+            }
+            parser.lexer.set_position(pos);
+            let expression = UnaryExpression::parse(parser)?;
+            return Ok(UnaryExpression::SizeofExpression {
+                expression: Box::new(expression),
+                location: t.location,
+            });
+        }
+        if let Ok(t) = parser.expect(TokenKind::Ampersand) {
+            let expression = CastExpression::parse(parser)?;
+            return Ok(UnaryExpression::Address {
+                expression: Box::new(expression),
+                location: t.location,
+            });
+        }
+        if let Ok(t) = parser.expect(TokenKind::Asterisk) {
+            let expression = CastExpression::parse(parser)?;
+            return Ok(UnaryExpression::Indirection {
+                expression: Box::new(expression),
+                location: t.location,
+            });
+        }
+        if let Ok(t) = parser.expect(TokenKind::Plus) {
+            let expression = CastExpression::parse(parser)?;
+            return Ok(UnaryExpression::Plus {
+                expression: Box::new(expression),
+                location: t.location,
+            });
+        }
+        if let Ok(t) = parser.expect(TokenKind::Minus) {
+            let expression = CastExpression::parse(parser)?;
+            return Ok(UnaryExpression::Minus {
+                expression: Box::new(expression),
+                location: t.location,
+            });
+        }
+        if let Ok(t) = parser.expect(TokenKind::Tilde) {
+            let expression = CastExpression::parse(parser)?;
+            return Ok(UnaryExpression::BitwiseNot {
+                expression: Box::new(expression),
+                location: t.location,
+            });
+        }
+        if let Ok(t) = parser.expect(TokenKind::Exclamation) {
+            let expression = CastExpression::parse(parser)?;
+            return Ok(UnaryExpression::LogicalNot {
+                expression: Box::new(expression),
+                location: t.location,
+            });
+        }
+        if let Ok(t) = parser.expect(TokenKind::PlusPlus) {
+            let expression = UnaryExpression::parse(parser)?;
+            return Ok(UnaryExpression::Increment {
+                expression: Box::new(expression),
+                location: t.location,
+            });
+        }
+        if let Ok(t) = parser.expect(TokenKind::MinusMinus) {
+            let expression = UnaryExpression::parse(parser)?;
+            return Ok(UnaryExpression::Decrement {
+                expression: Box::new(expression),
+                location: t.location,
+            });
+        }
+        let postfix_expression = PostfixExpression::parse(parser)?;
+        Ok(UnaryExpression::PostfixExpression(postfix_expression))
+    }
+}
 
 // 6.5.4
 
-// pub enum CastExpression {}
+#[derive(Debug)]
+pub enum CastExpression {
+    UnaryExpression(UnaryExpression),
+    Cast {
+        type_name: Box<TypeName>,
+        expression: Box<CastExpression>,
+        location: Location,
+    },
+}
+
+impl Parse for CastExpression {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let pos = parser.lexer.current_position();
+        if let Ok(t) = parser.expect(TokenKind::LParen) {
+            if parser.at_declaration_specifiers() {
+                let type_name = TypeName::parse(parser)?;
+                parser.expect(TokenKind::RParen)?;
+                let expression = CastExpression::parse(parser)?;
+                return Ok(CastExpression::Cast {
+                    type_name: Box::new(type_name),
+                    expression: Box::new(expression),
+                    location: t.location,
+                });
+            }
+            parser.lexer.set_position(pos);
+        }
+        let unary_expression = UnaryExpression::parse(parser)?;
+        Ok(CastExpression::UnaryExpression(unary_expression))
+    }
+}
 
 // 6.5.5
 
 #[derive(Debug)]
 pub enum MultiplicativeExpression {
-    // TODO
-    PrimaryExpression(PrimaryExpression),
+    CastExpression(CastExpression),
     Mul {
         lhs: Box<MultiplicativeExpression>,
-        // TODO cast
-        rhs: Box<PrimaryExpression>,
+        rhs: Box<CastExpression>,
         location: Location,
     },
     Div {
         lhs: Box<MultiplicativeExpression>,
-        rhs: Box<PrimaryExpression>,
+        rhs: Box<CastExpression>,
         location: Location,
     },
     Rem {
         lhs: Box<MultiplicativeExpression>,
-        rhs: Box<PrimaryExpression>,
+        rhs: Box<CastExpression>,
         location: Location,
     },
 }
 
 impl Parse for MultiplicativeExpression {
     fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
-        let primary_expression = PrimaryExpression::parse(parser)?;
-        let mut lhs = MultiplicativeExpression::PrimaryExpression(primary_expression);
+        let cast_expression = CastExpression::parse(parser)?;
+        let mut lhs = MultiplicativeExpression::CastExpression(cast_expression);
         while {
             if let Ok(t) = parser.expect(TokenKind::Asterisk) {
-                let rhs = PrimaryExpression::parse(parser)?;
+                let rhs = CastExpression::parse(parser)?;
                 lhs = MultiplicativeExpression::Mul {
                     lhs: Box::new(lhs),
                     rhs: Box::new(rhs),
@@ -71,7 +553,7 @@ impl Parse for MultiplicativeExpression {
                 };
                 true
             } else if let Ok(t) = parser.expect(TokenKind::Slash) {
-                let rhs = PrimaryExpression::parse(parser)?;
+                let rhs = CastExpression::parse(parser)?;
                 lhs = MultiplicativeExpression::Div {
                     lhs: Box::new(lhs),
                     rhs: Box::new(rhs),
@@ -79,7 +561,7 @@ impl Parse for MultiplicativeExpression {
                 };
                 true
             } else if let Ok(t) = parser.expect(TokenKind::Percent) {
-                let rhs = PrimaryExpression::parse(parser)?;
+                let rhs = CastExpression::parse(parser)?;
                 lhs = MultiplicativeExpression::Rem {
                     lhs: Box::new(lhs),
                     rhs: Box::new(rhs),
@@ -143,15 +625,167 @@ impl Parse for AdditiveExpression {
 
 // 6.5.7
 
-// pub enum ShiftExpression {}
+#[derive(Debug)]
+pub enum ShiftExpression {
+    AdditiveExpression(AdditiveExpression),
+    Left {
+        lhs: Box<ShiftExpression>,
+        rhs: Box<AdditiveExpression>,
+        location: Location,
+    },
+    Right {
+        lhs: Box<ShiftExpression>,
+        rhs: Box<AdditiveExpression>,
+        location: Location,
+    },
+}
+
+impl Parse for ShiftExpression {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let additive_expression = AdditiveExpression::parse(parser)?;
+        let mut lhs = ShiftExpression::AdditiveExpression(additive_expression);
+        while {
+            if let Ok(t) = parser.expect(TokenKind::LessLess) {
+                let rhs = AdditiveExpression::parse(parser)?;
+                lhs = ShiftExpression::Left {
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                    location: t.location,
+                };
+                true
+            } else if let Ok(t) = parser.expect(TokenKind::GreaterGreater) {
+                let rhs = AdditiveExpression::parse(parser)?;
+                lhs = ShiftExpression::Right {
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                    location: t.location,
+                };
+                true
+            } else {
+                false
+            }
+        } {}
+        Ok(lhs)
+    }
+}
 
 // 6.5.8
 
-// pub enum RelationalExpression {}
+#[derive(Debug)]
+pub enum RelationalExpression {
+    ShiftExpression(ShiftExpression),
+    Less {
+        lhs: Box<RelationalExpression>,
+        rhs: Box<ShiftExpression>,
+        location: Location,
+    },
+    Greater {
+        lhs: Box<RelationalExpression>,
+        rhs: Box<ShiftExpression>,
+        location: Location,
+    },
+    LessEqual {
+        lhs: Box<RelationalExpression>,
+        rhs: Box<ShiftExpression>,
+        location: Location,
+    },
+    GreaterEqual {
+        lhs: Box<RelationalExpression>,
+        rhs: Box<ShiftExpression>,
+        location: Location,
+    },
+}
+
+impl Parse for RelationalExpression {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let shift_expression = ShiftExpression::parse(parser)?;
+        let mut lhs = RelationalExpression::ShiftExpression(shift_expression);
+        while {
+            if let Ok(t) = parser.expect(TokenKind::Less) {
+                let rhs = ShiftExpression::parse(parser)?;
+                lhs = RelationalExpression::Less {
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                    location: t.location,
+                };
+                true
+            } else if let Ok(t) = parser.expect(TokenKind::Greater) {
+                let rhs = ShiftExpression::parse(parser)?;
+                lhs = RelationalExpression::Greater {
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                    location: t.location,
+                };
+                true
+            } else if let Ok(t) = parser.expect(TokenKind::LessEqual) {
+                let rhs = ShiftExpression::parse(parser)?;
+                lhs = RelationalExpression::LessEqual {
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                    location: t.location,
+                };
+                true
+            } else if let Ok(t) = parser.expect(TokenKind::GreaterEqual) {
+                let rhs = ShiftExpression::parse(parser)?;
+                lhs = RelationalExpression::GreaterEqual {
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                    location: t.location,
+                };
+                true
+            } else {
+                false
+            }
+        } {}
+        Ok(lhs)
+    }
+}
 
 // 6.5.9
 
-// pub enum EqualityExpression {}
+#[derive(Debug)]
+pub enum EqualityExpression {
+    RelationalExpression(RelationalExpression),
+    Equal {
+        lhs: Box<EqualityExpression>,
+        rhs: Box<RelationalExpression>,
+        location: Location,
+    },
+    NotEqual {
+        lhs: Box<EqualityExpression>,
+        rhs: Box<RelationalExpression>,
+        location: Location,
+    },
+}
+
+impl Parse for EqualityExpression {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let relational_expression = RelationalExpression::parse(parser)?;
+        let mut lhs = EqualityExpression::RelationalExpression(relational_expression);
+        while {
+            if let Ok(t) = parser.expect(TokenKind::EqualEqual) {
+                let rhs = RelationalExpression::parse(parser)?;
+                lhs = EqualityExpression::Equal {
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                    location: t.location,
+                };
+                true
+            } else if let Ok(t) = parser.expect(TokenKind::ExclamationEqual) {
+                let rhs = RelationalExpression::parse(parser)?;
+                lhs = EqualityExpression::NotEqual {
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                    location: t.location,
+                };
+                true
+            } else {
+                false
+            }
+        } {}
+        Ok(lhs)
+    }
+}
 
 // 6.5.10
 
@@ -179,20 +813,1524 @@ impl Parse for AdditiveExpression {
 
 // 6.5.16
 
-// pub enum AssignmentExpression {}
+#[derive(Debug)]
+pub enum AssignmentExpression {
+    // TODO
+    EqualityExpression(EqualityExpression),
+    Assign {
+        // TODO unary
+        // Whether it is an lvalue is checked on lowering
+        lhs: Box<EqualityExpression>,
+        rhs: Box<AssignmentExpression>,
+        location: Location,
+    },
+}
+
+impl Parse for AssignmentExpression {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let lhs = EqualityExpression::parse(parser)?;
+        if let Ok(t) = parser.expect(TokenKind::Equal) {
+            // Right associative
+            let rhs = AssignmentExpression::parse(parser)?;
+            return Ok(AssignmentExpression::Assign {
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+                location: t.location,
+            });
+        }
+        Ok(AssignmentExpression::EqualityExpression(lhs))
+    }
+}
 
 // 6.5.17
 
 #[derive(Debug)]
 pub enum Expression {
-    AdditiveExpression(Box<AdditiveExpression>),
+    AssignmentExpression(Box<AssignmentExpression>),
 }
 
 impl Parse for Expression {
     fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
-        let additive_expression = AdditiveExpression::parse(parser)?;
-        Ok(Expression::AdditiveExpression(Box::new(
-            additive_expression,
+        let assignment_expression = AssignmentExpression::parse(parser)?;
+        Ok(Expression::AssignmentExpression(Box::new(
+            assignment_expression,
         )))
     }
 }
+
+// 6.6
+
+#[derive(Debug)]
+pub struct ConstantExpression {
+    pub location: Location,
+    pub expression: EqualityExpression,
+}
+
+impl Parse for ConstantExpression {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let location = parser.peek_location();
+        let expression = EqualityExpression::parse(parser)?;
+        Ok(ConstantExpression {
+            location,
+            expression,
+        })
+    }
+}
+
+// 6.6 Integer constant expressions are folded with the types of their operands
+
+/// The value of an integer constant expression, kept converted to its type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntegerConstant {
+    pub value: i128,
+    pub r#type: CType,
+}
+
+#[derive(Debug)]
+pub enum ConstantError {
+    /// An operand or operator that may not appear in a constant expression
+    NotConstant(Location),
+    /// 6.6 The result would be undefined, such as an overflow or a division by zero
+    Undefined { location: Location, message: String },
+}
+
+fn undefined<T>(location: &Location, message: &str) -> Result<T, ConstantError> {
+    Err(ConstantError::Undefined {
+        location: location.clone(),
+        message: message.to_string(),
+    })
+}
+
+impl IntegerConstant {
+    // 6.3.1.3
+    pub fn convert(&self, r#type: &CType) -> IntegerConstant {
+        IntegerConstant {
+            value: r#type.convert_constant(self.value),
+            r#type: r#type.clone(),
+        }
+    }
+
+    fn is_true(&self) -> bool {
+        self.value != 0
+    }
+
+    fn truth(value: bool) -> IntegerConstant {
+        IntegerConstant {
+            value: value as i128,
+            r#type: CType::INT,
+        }
+    }
+
+    /// Applies an arithmetic operator after the usual arithmetic conversions. Unsigned results
+    /// wrap around 6.2.5 and signed ones must be representable 6.5.
+    fn arithmetic(
+        lhs: IntegerConstant,
+        rhs: IntegerConstant,
+        location: &Location,
+        signed: impl Fn(i128, i128) -> Option<i128>,
+        unsigned: impl Fn(u128, u128) -> u128,
+    ) -> Result<IntegerConstant, ConstantError> {
+        let r#type = CType::usual_arithmetic_conversion(&lhs.r#type, &rhs.r#type);
+        let (lhs, rhs) = (lhs.convert(&r#type).value, rhs.convert(&r#type).value);
+        let value = if r#type.is_signed() {
+            match signed(lhs, rhs) {
+                Some(value) if r#type.convert_constant(value) == value => value,
+                _ => return undefined(location, "overflow in constant expression"),
+            }
+        } else {
+            r#type.convert_constant(unsigned(lhs as u128, rhs as u128) as i128)
+        };
+        Ok(IntegerConstant { value, r#type })
+    }
+
+    // 6.5.5p6 `a % b` is undefined if `a / b` is not representable, such as INT_MIN / -1
+    fn division(
+        lhs: IntegerConstant,
+        rhs: IntegerConstant,
+        location: &Location,
+        remainder: bool,
+    ) -> Result<IntegerConstant, ConstantError> {
+        if !rhs.is_true() {
+            return undefined(location, "division by zero in constant expression");
+        }
+        let quotient = IntegerConstant::arithmetic(
+            lhs.clone(),
+            rhs.clone(),
+            location,
+            i128::checked_div,
+            |lhs, rhs| lhs / rhs,
+        )?;
+        if !remainder {
+            return Ok(quotient);
+        }
+        IntegerConstant::arithmetic(lhs, rhs, location, i128::checked_rem, |lhs, rhs| lhs % rhs)
+    }
+
+    // 6.5.7 The type is that of the promoted left operand
+    fn shift(
+        lhs: IntegerConstant,
+        rhs: IntegerConstant,
+        location: &Location,
+        left: bool,
+    ) -> Result<IntegerConstant, ConstantError> {
+        let lhs = lhs.convert(&lhs.r#type.integer_promotion());
+        let r#type = lhs.r#type.clone();
+        let rhs = rhs.convert(&rhs.r#type.integer_promotion());
+        if rhs.r#type.is_signed() && rhs.value < 0 {
+            return undefined(location, "shift count is negative");
+        }
+        if rhs.value as u128 >= r#type.width() as u128 {
+            return undefined(location, "shift count >= width of type");
+        }
+        let count = rhs.value as u32;
+        let value = if !left {
+            if r#type.is_signed() {
+                // Right shifts of negative values are arithmetic like GCC
+                lhs.value >> count
+            } else {
+                ((lhs.value as u128) >> count) as i128
+            }
+        } else if r#type.is_signed() {
+            // 6.5.7 The result must be representable and the operand nonnegative
+            let value = lhs.value << count;
+            if lhs.value < 0
+                || value >> count != lhs.value
+                || r#type.convert_constant(value) != value
+            {
+                return undefined(location, "overflow in constant expression");
+            }
+            value
+        } else {
+            r#type.convert_constant(((lhs.value as u128) << count) as i128)
+        };
+        Ok(IntegerConstant { value, r#type })
+    }
+
+    // 6.5.8, 6.5.9 The result is an int
+    fn comparison(
+        lhs: IntegerConstant,
+        rhs: IntegerConstant,
+        predicate: impl Fn(Ordering) -> bool,
+    ) -> IntegerConstant {
+        let r#type = CType::usual_arithmetic_conversion(&lhs.r#type, &rhs.r#type);
+        let (lhs, rhs) = (lhs.convert(&r#type).value, rhs.convert(&r#type).value);
+        let ordering = if r#type.is_signed() {
+            lhs.cmp(&rhs)
+        } else {
+            (lhs as u128).cmp(&(rhs as u128))
+        };
+        IntegerConstant::truth(predicate(ordering))
+    }
+}
+
+/// The type of the operand of `sizeof` at the location of the operator, which is known from the
+/// declarations in scope while lowering. `None` if it is not known.
+pub type OperandType<'s> = &'s dyn Fn(&UnaryExpression, &Location) -> Option<CType>;
+
+pub trait Evaluate {
+    fn evaluate_with(&self, operand_type: OperandType) -> Result<IntegerConstant, ConstantError>;
+
+    /// Without the declarations, `sizeof` of an expression is not a constant
+    fn evaluate(&self) -> Result<IntegerConstant, ConstantError> {
+        self.evaluate_with(&|_, _| None)
+    }
+}
+
+impl Evaluate for ConstantExpression {
+    fn evaluate_with(&self, operand_type: OperandType) -> Result<IntegerConstant, ConstantError> {
+        self.expression.evaluate_with(operand_type)
+    }
+}
+
+impl Evaluate for PrimaryExpression {
+    fn evaluate_with(&self, operand_type: OperandType) -> Result<IntegerConstant, ConstantError> {
+        match self {
+            PrimaryExpression::Identifier { location, .. } => {
+                Err(ConstantError::NotConstant(location.clone()))
+            }
+            PrimaryExpression::StringLiteral { location, .. } => {
+                Err(ConstantError::NotConstant(location.clone()))
+            }
+            PrimaryExpression::Parenthesized(expression) => expression.evaluate_with(operand_type),
+            PrimaryExpression::VaBuiltin(va_builtin) => {
+                Err(ConstantError::NotConstant(va_builtin.location().clone()))
+            }
+            // Only the type of the controlling expression is needed, which is known if it is a
+            // constant too
+            PrimaryExpression::GenericSelection(generic_selection) => {
+                let r#type = generic_selection
+                    .controlling_expression
+                    .evaluate_with(operand_type)?
+                    .r#type;
+                match generic_selection.select(&r#type) {
+                    Some(expression) => expression.evaluate_with(operand_type),
+                    None => Err(ConstantError::NotConstant(
+                        generic_selection.location.clone(),
+                    )),
+                }
+            }
+            PrimaryExpression::Constant {
+                value: Constant::Integer { value, r#type },
+                ..
+            } => Ok(IntegerConstant {
+                // Negative enumeration constants are stored in two's complement
+                value: r#type.convert_constant(*value as i128),
+                r#type: r#type.clone(),
+            }),
+            // 6.6 Only as the immediate operand of a cast
+            PrimaryExpression::Constant {
+                value: Constant::Floating { .. },
+                location,
+            } => Err(ConstantError::NotConstant(location.clone())),
+        }
+    }
+}
+
+impl Evaluate for PostfixExpression {
+    fn evaluate_with(&self, operand_type: OperandType) -> Result<IntegerConstant, ConstantError> {
+        match self {
+            PostfixExpression::PrimaryExpression(primary_expression) => {
+                primary_expression.evaluate_with(operand_type)
+            }
+            // 6.6 Constant expressions shall not contain function calls or increments
+            PostfixExpression::FunctionCall { location, .. }
+            | PostfixExpression::Increment { location, .. }
+            | PostfixExpression::Decrement { location, .. }
+            | PostfixExpression::Subscript { location, .. }
+            | PostfixExpression::Member { location, .. }
+            | PostfixExpression::Arrow { location, .. } => {
+                Err(ConstantError::NotConstant(location.clone()))
+            }
+        }
+    }
+}
+
+impl Evaluate for UnaryExpression {
+    fn evaluate_with(&self, operand_type: OperandType) -> Result<IntegerConstant, ConstantError> {
+        match self {
+            UnaryExpression::PostfixExpression(postfix_expression) => {
+                postfix_expression.evaluate_with(operand_type)
+            }
+            // Address constants are not integer constant expressions
+            UnaryExpression::Increment { location, .. }
+            | UnaryExpression::Decrement { location, .. }
+            | UnaryExpression::Address { location, .. }
+            | UnaryExpression::Indirection { location, .. } => {
+                Err(ConstantError::NotConstant(location.clone()))
+            }
+            UnaryExpression::Plus { expression, .. } => {
+                let operand = expression.evaluate_with(operand_type)?;
+                Ok(operand.convert(&operand.r#type.integer_promotion()))
+            }
+            // 6.5.3.3 `-x` is `0 - x` in the promoted type
+            UnaryExpression::Minus {
+                expression,
+                location,
+            } => {
+                let operand = expression.evaluate_with(operand_type)?;
+                let zero = IntegerConstant {
+                    value: 0,
+                    r#type: operand.r#type.integer_promotion(),
+                };
+                IntegerConstant::arithmetic(
+                    zero,
+                    operand,
+                    location,
+                    i128::checked_sub,
+                    u128::wrapping_sub,
+                )
+            }
+            UnaryExpression::BitwiseNot { expression, .. } => {
+                let operand = expression.evaluate_with(operand_type)?;
+                let r#type = operand.r#type.integer_promotion();
+                Ok(IntegerConstant {
+                    value: r#type.convert_constant(!operand.value),
+                    r#type,
+                })
+            }
+            UnaryExpression::LogicalNot { expression, .. } => Ok(IntegerConstant::truth(
+                !expression.evaluate_with(operand_type)?.is_true(),
+            )),
+            UnaryExpression::SizeofExpression {
+                expression,
+                location,
+            } => match operand_type(expression, location) {
+                Some(r#type) if r#type.is_complete() && !r#type.is_variably_sized() => {
+                    Ok(IntegerConstant {
+                        value: r#type.size() as i128,
+                        r#type: CType::UNSIGNED_LONG,
+                    })
+                }
+                _ => Err(ConstantError::NotConstant(location.clone())),
+            },
+            UnaryExpression::SizeofType {
+                type_name,
+                location,
+            } => match type_name.r#type() {
+                r#type if !r#type.is_complete() => {
+                    Err(ConstantError::NotConstant(location.clone()))
+                }
+                r#type => Ok(IntegerConstant {
+                    value: r#type.size() as i128,
+                    r#type: CType::UNSIGNED_LONG,
+                }),
+            },
+        }
+    }
+}
+
+impl Evaluate for CastExpression {
+    fn evaluate_with(&self, operand_type: OperandType) -> Result<IntegerConstant, ConstantError> {
+        match self {
+            CastExpression::UnaryExpression(unary_expression) => {
+                unary_expression.evaluate_with(operand_type)
+            }
+            // 6.6 Only casts to integer types are allowed, whose immediate operand may be a
+            // floating constant
+            CastExpression::Cast {
+                type_name,
+                expression,
+                location,
+            } => match (type_name.r#type(), expression.as_unary_expression()) {
+                (
+                    r#type,
+                    Some(UnaryExpression::PostfixExpression(PostfixExpression::PrimaryExpression(
+                        constant @ PrimaryExpression::Constant {
+                            value: Constant::Floating { .. },
+                            ..
+                        },
+                    ))),
+                ) if r#type.is_integer() => {
+                    match constant
+                        .evaluate_arithmetic(operand_type)?
+                        .convert(&r#type, location)?
+                    {
+                        ArithmeticConstant::Integer(constant) => Ok(constant),
+                        ArithmeticConstant::Floating { .. } => unreachable!(),
+                    }
+                }
+                (r#type, _) if r#type.is_integer() => {
+                    Ok(expression.evaluate_with(operand_type)?.convert(&r#type))
+                }
+                _ => Err(ConstantError::NotConstant(location.clone())),
+            },
+        }
+    }
+}
+
+impl Evaluate for MultiplicativeExpression {
+    fn evaluate_with(&self, operand_type: OperandType) -> Result<IntegerConstant, ConstantError> {
+        match self {
+            MultiplicativeExpression::CastExpression(cast_expression) => {
+                cast_expression.evaluate_with(operand_type)
+            }
+            MultiplicativeExpression::Mul { lhs, rhs, location } => IntegerConstant::arithmetic(
+                lhs.evaluate_with(operand_type)?,
+                rhs.evaluate_with(operand_type)?,
+                location,
+                i128::checked_mul,
+                u128::wrapping_mul,
+            ),
+            MultiplicativeExpression::Div { lhs, rhs, location } => IntegerConstant::division(
+                lhs.evaluate_with(operand_type)?,
+                rhs.evaluate_with(operand_type)?,
+                location,
+                false,
+            ),
+            MultiplicativeExpression::Rem { lhs, rhs, location } => IntegerConstant::division(
+                lhs.evaluate_with(operand_type)?,
+                rhs.evaluate_with(operand_type)?,
+                location,
+                true,
+            ),
+        }
+    }
+}
+
+impl Evaluate for AdditiveExpression {
+    fn evaluate_with(&self, operand_type: OperandType) -> Result<IntegerConstant, ConstantError> {
+        match self {
+            AdditiveExpression::PrimaryExpression(multiplicative_expression) => {
+                multiplicative_expression.evaluate_with(operand_type)
+            }
+            AdditiveExpression::Add { lhs, rhs, location } => IntegerConstant::arithmetic(
+                lhs.evaluate_with(operand_type)?,
+                rhs.evaluate_with(operand_type)?,
+                location,
+                i128::checked_add,
+                u128::wrapping_add,
+            ),
+            AdditiveExpression::Minus { lhs, rhs, location } => IntegerConstant::arithmetic(
+                lhs.evaluate_with(operand_type)?,
+                rhs.evaluate_with(operand_type)?,
+                location,
+                i128::checked_sub,
+                u128::wrapping_sub,
+            ),
+        }
+    }
+}
+
+impl Evaluate for ShiftExpression {
+    fn evaluate_with(&self, operand_type: OperandType) -> Result<IntegerConstant, ConstantError> {
+        match self {
+            ShiftExpression::AdditiveExpression(additive_expression) => {
+                additive_expression.evaluate_with(operand_type)
+            }
+            ShiftExpression::Left { lhs, rhs, location } => IntegerConstant::shift(
+                lhs.evaluate_with(operand_type)?,
+                rhs.evaluate_with(operand_type)?,
+                location,
+                true,
+            ),
+            ShiftExpression::Right { lhs, rhs, location } => IntegerConstant::shift(
+                lhs.evaluate_with(operand_type)?,
+                rhs.evaluate_with(operand_type)?,
+                location,
+                false,
+            ),
+        }
+    }
+}
+
+impl Evaluate for RelationalExpression {
+    fn evaluate_with(&self, operand_type: OperandType) -> Result<IntegerConstant, ConstantError> {
+        match self {
+            RelationalExpression::ShiftExpression(shift_expression) => {
+                shift_expression.evaluate_with(operand_type)
+            }
+            RelationalExpression::Less { lhs, rhs, .. } => Ok(IntegerConstant::comparison(
+                lhs.evaluate_with(operand_type)?,
+                rhs.evaluate_with(operand_type)?,
+                Ordering::is_lt,
+            )),
+            RelationalExpression::Greater { lhs, rhs, .. } => Ok(IntegerConstant::comparison(
+                lhs.evaluate_with(operand_type)?,
+                rhs.evaluate_with(operand_type)?,
+                Ordering::is_gt,
+            )),
+            RelationalExpression::LessEqual { lhs, rhs, .. } => Ok(IntegerConstant::comparison(
+                lhs.evaluate_with(operand_type)?,
+                rhs.evaluate_with(operand_type)?,
+                Ordering::is_le,
+            )),
+            RelationalExpression::GreaterEqual { lhs, rhs, .. } => Ok(IntegerConstant::comparison(
+                lhs.evaluate_with(operand_type)?,
+                rhs.evaluate_with(operand_type)?,
+                Ordering::is_ge,
+            )),
+        }
+    }
+}
+
+impl Evaluate for EqualityExpression {
+    fn evaluate_with(&self, operand_type: OperandType) -> Result<IntegerConstant, ConstantError> {
+        match self {
+            EqualityExpression::RelationalExpression(relational_expression) => {
+                relational_expression.evaluate_with(operand_type)
+            }
+            EqualityExpression::Equal { lhs, rhs, .. } => Ok(IntegerConstant::comparison(
+                lhs.evaluate_with(operand_type)?,
+                rhs.evaluate_with(operand_type)?,
+                Ordering::is_eq,
+            )),
+            EqualityExpression::NotEqual { lhs, rhs, .. } => Ok(IntegerConstant::comparison(
+                lhs.evaluate_with(operand_type)?,
+                rhs.evaluate_with(operand_type)?,
+                Ordering::is_ne,
+            )),
+        }
+    }
+}
+
+impl Evaluate for AssignmentExpression {
+    fn evaluate_with(&self, operand_type: OperandType) -> Result<IntegerConstant, ConstantError> {
+        match self {
+            AssignmentExpression::EqualityExpression(equality_expression) => {
+                equality_expression.evaluate_with(operand_type)
+            }
+            // 6.6 Constant expressions shall not contain assignments
+            AssignmentExpression::Assign { location, .. } => {
+                Err(ConstantError::NotConstant(location.clone()))
+            }
+        }
+    }
+}
+
+impl Evaluate for Expression {
+    fn evaluate_with(&self, operand_type: OperandType) -> Result<IntegerConstant, ConstantError> {
+        match self {
+            Expression::AssignmentExpression(assignment_expression) => {
+                assignment_expression.evaluate_with(operand_type)
+            }
+        }
+    }
+}
+
+/// The value of an arithmetic constant expression 6.6, which may have floating operands. A
+/// floating value is kept rounded to its type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArithmeticConstant {
+    Integer(IntegerConstant),
+    Floating { value: f64, kind: FloatingKind },
+}
+
+impl ArithmeticConstant {
+    fn floating(value: f64, kind: FloatingKind) -> ArithmeticConstant {
+        let value = match kind {
+            FloatingKind::Float => value as f32 as f64,
+            FloatingKind::Double => value,
+        };
+        ArithmeticConstant::Floating { value, kind }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            ArithmeticConstant::Integer(constant) => constant.value as f64,
+            ArithmeticConstant::Floating { value, .. } => *value,
+        }
+    }
+
+    /// 6.3.1.4, 6.3.1.5 Converts to an arithmetic type, where a floating value is truncated
+    /// toward zero for an integer type
+    pub fn convert(
+        &self,
+        r#type: &CType,
+        location: &Location,
+    ) -> Result<ArithmeticConstant, ConstantError> {
+        match (self, r#type) {
+            (_, CType::Floating(kind) | CType::Complex(kind)) => {
+                Ok(ArithmeticConstant::floating(self.to_f64(), *kind))
+            }
+            (ArithmeticConstant::Integer(constant), _) => {
+                Ok(ArithmeticConstant::Integer(constant.convert(r#type)))
+            }
+            // 6.3.1.2
+            (ArithmeticConstant::Floating { value, .. }, _) if *r#type == CType::BOOL => Ok(
+                ArithmeticConstant::Integer(IntegerConstant::truth(*value != 0.0)),
+            ),
+            (ArithmeticConstant::Floating { value, .. }, _) => {
+                let truncated = IntegerConstant {
+                    value: value.trunc() as i128,
+                    r#type: r#type.clone(),
+                };
+                // `as` saturates, so a value out of range does not round trip
+                if !value.is_finite() || truncated.convert(r#type) != truncated {
+                    return undefined(
+                        location,
+                        &format!("value {} is outside the range of '{}'", value, r#type),
+                    );
+                }
+                Ok(ArithmeticConstant::Integer(truncated))
+            }
+        }
+    }
+
+    // 6.3.1.8 The integer operands are converted to the floating type of the other one
+    fn floating_kind(lhs: &ArithmeticConstant, rhs: &ArithmeticConstant) -> FloatingKind {
+        [lhs, rhs]
+            .into_iter()
+            .filter_map(|operand| match operand {
+                ArithmeticConstant::Integer(_) => None,
+                ArithmeticConstant::Floating { kind, .. } => Some(*kind),
+            })
+            .max()
+            .unwrap()
+    }
+
+    fn floating_arithmetic(
+        lhs: ArithmeticConstant,
+        rhs: ArithmeticConstant,
+        operation: impl Fn(f64, f64) -> f64,
+    ) -> ArithmeticConstant {
+        let kind = ArithmeticConstant::floating_kind(&lhs, &rhs);
+        ArithmeticConstant::floating(operation(lhs.to_f64(), rhs.to_f64()), kind)
+    }
+
+    /// 6.5.8, 6.5.9 The result is an int. A NaN operand is unordered, for which only `!=` holds.
+    fn comparison(
+        lhs: ArithmeticConstant,
+        rhs: ArithmeticConstant,
+        predicate: impl Fn(Ordering) -> bool,
+        unordered: bool,
+    ) -> ArithmeticConstant {
+        let (lhs, rhs) = match (lhs, rhs) {
+            (ArithmeticConstant::Integer(lhs), ArithmeticConstant::Integer(rhs)) => {
+                return ArithmeticConstant::Integer(IntegerConstant::comparison(
+                    lhs, rhs, predicate,
+                ))
+            }
+            (lhs, rhs) => {
+                let kind = ArithmeticConstant::floating_kind(&lhs, &rhs);
+                (
+                    ArithmeticConstant::floating(lhs.to_f64(), kind).to_f64(),
+                    ArithmeticConstant::floating(rhs.to_f64(), kind).to_f64(),
+                )
+            }
+        };
+        let holds = lhs.partial_cmp(&rhs).map_or(unordered, predicate);
+        ArithmeticConstant::Integer(IntegerConstant::truth(holds))
+    }
+}
+
+/// 6.6 Evaluates an arithmetic constant expression, which only an initializer may be. Integer
+/// operations are those of `Evaluate`.
+pub trait EvaluateArithmetic: Evaluate {
+    fn evaluate_arithmetic(
+        &self,
+        operand_type: OperandType,
+    ) -> Result<ArithmeticConstant, ConstantError>;
+
+    // Of an operator that takes only integers
+    fn evaluate_integer(
+        &self,
+        operand_type: OperandType,
+    ) -> Result<ArithmeticConstant, ConstantError> {
+        self.evaluate_with(operand_type)
+            .map(ArithmeticConstant::Integer)
+    }
+}
+
+impl EvaluateArithmetic for PrimaryExpression {
+    fn evaluate_arithmetic(
+        &self,
+        operand_type: OperandType,
+    ) -> Result<ArithmeticConstant, ConstantError> {
+        match self {
+            PrimaryExpression::Constant {
+                value:
+                    Constant::Floating {
+                        value,
+                        r#type: CType::Floating(kind),
+                    },
+                ..
+            } => Ok(ArithmeticConstant::floating(*value, *kind)),
+            PrimaryExpression::Parenthesized(expression) => match expression.as_ref() {
+                Expression::AssignmentExpression(expression) => {
+                    expression.evaluate_arithmetic(operand_type)
+                }
+            },
+            _ => self.evaluate_integer(operand_type),
+        }
+    }
+}
+
+impl EvaluateArithmetic for PostfixExpression {
+    fn evaluate_arithmetic(
+        &self,
+        operand_type: OperandType,
+    ) -> Result<ArithmeticConstant, ConstantError> {
+        match self {
+            PostfixExpression::PrimaryExpression(primary_expression) => {
+                primary_expression.evaluate_arithmetic(operand_type)
+            }
+            _ => self.evaluate_integer(operand_type),
+        }
+    }
+}
+
+impl EvaluateArithmetic for UnaryExpression {
+    fn evaluate_arithmetic(
+        &self,
+        operand_type: OperandType,
+    ) -> Result<ArithmeticConstant, ConstantError> {
+        match self {
+            UnaryExpression::PostfixExpression(postfix_expression) => {
+                postfix_expression.evaluate_arithmetic(operand_type)
+            }
+            UnaryExpression::Plus { expression, .. } => {
+                match expression.evaluate_arithmetic(operand_type)? {
+                    ArithmeticConstant::Integer(operand) => Ok(ArithmeticConstant::Integer(
+                        operand.convert(&operand.r#type.integer_promotion()),
+                    )),
+                    floating => Ok(floating),
+                }
+            }
+            UnaryExpression::Minus {
+                expression,
+                location,
+            } => match expression.evaluate_arithmetic(operand_type)? {
+                ArithmeticConstant::Integer(operand) => {
+                    let zero = IntegerConstant {
+                        value: 0,
+                        r#type: operand.r#type.integer_promotion(),
+                    };
+                    IntegerConstant::arithmetic(
+                        zero,
+                        operand,
+                        location,
+                        i128::checked_sub,
+                        u128::wrapping_sub,
+                    )
+                    .map(ArithmeticConstant::Integer)
+                }
+                ArithmeticConstant::Floating { value, kind } => Ok(ArithmeticConstant::Floating {
+                    value: -value,
+                    kind,
+                }),
+            },
+            _ => self.evaluate_integer(operand_type),
+        }
+    }
+}
+
+impl EvaluateArithmetic for CastExpression {
+    fn evaluate_arithmetic(
+        &self,
+        operand_type: OperandType,
+    ) -> Result<ArithmeticConstant, ConstantError> {
+        match self {
+            CastExpression::UnaryExpression(unary_expression) => {
+                unary_expression.evaluate_arithmetic(operand_type)
+            }
+            CastExpression::Cast {
+                type_name,
+                expression,
+                location,
+            } => match type_name.r#type() {
+                r#type if r#type.is_arithmetic() => expression
+                    .evaluate_arithmetic(operand_type)?
+                    .convert(&r#type, location),
+                _ => Err(ConstantError::NotConstant(location.clone())),
+            },
+        }
+    }
+}
+
+impl EvaluateArithmetic for MultiplicativeExpression {
+    fn evaluate_arithmetic(
+        &self,
+        operand_type: OperandType,
+    ) -> Result<ArithmeticConstant, ConstantError> {
+        let (lhs, rhs, location) = match self {
+            MultiplicativeExpression::CastExpression(cast_expression) => {
+                return cast_expression.evaluate_arithmetic(operand_type)
+            }
+            MultiplicativeExpression::Mul { lhs, rhs, location }
+            | MultiplicativeExpression::Div { lhs, rhs, location } => (lhs, rhs, location),
+            MultiplicativeExpression::Rem { .. } => return self.evaluate_integer(operand_type),
+        };
+        let lhs = lhs.evaluate_arithmetic(operand_type)?;
+        let rhs = rhs.evaluate_arithmetic(operand_type)?;
+        let multiply = matches!(self, MultiplicativeExpression::Mul { .. });
+        match (lhs, rhs) {
+            (ArithmeticConstant::Integer(lhs), ArithmeticConstant::Integer(rhs)) if multiply => {
+                IntegerConstant::arithmetic(
+                    lhs,
+                    rhs,
+                    location,
+                    i128::checked_mul,
+                    u128::wrapping_mul,
+                )
+                .map(ArithmeticConstant::Integer)
+            }
+            (ArithmeticConstant::Integer(lhs), ArithmeticConstant::Integer(rhs)) => {
+                IntegerConstant::division(lhs, rhs, location, false)
+                    .map(ArithmeticConstant::Integer)
+            }
+            (lhs, rhs) if multiply => Ok(ArithmeticConstant::floating_arithmetic(
+                lhs,
+                rhs,
+                |lhs, rhs| lhs * rhs,
+            )),
+            (lhs, rhs) => Ok(ArithmeticConstant::floating_arithmetic(
+                lhs,
+                rhs,
+                |lhs, rhs| lhs / rhs,
+            )),
+        }
+    }
+}
+
+impl EvaluateArithmetic for AdditiveExpression {
+    fn evaluate_arithmetic(
+        &self,
+        operand_type: OperandType,
+    ) -> Result<ArithmeticConstant, ConstantError> {
+        let (lhs, rhs, location) = match self {
+            AdditiveExpression::PrimaryExpression(multiplicative_expression) => {
+                return multiplicative_expression.evaluate_arithmetic(operand_type)
+            }
+            AdditiveExpression::Add { lhs, rhs, location }
+            | AdditiveExpression::Minus { lhs, rhs, location } => (lhs, rhs, location),
+        };
+        let lhs = lhs.evaluate_arithmetic(operand_type)?;
+        let rhs = rhs.evaluate_arithmetic(operand_type)?;
+        let add = matches!(self, AdditiveExpression::Add { .. });
+        match (lhs, rhs) {
+            (ArithmeticConstant::Integer(lhs), ArithmeticConstant::Integer(rhs)) if add => {
+                IntegerConstant::arithmetic(
+                    lhs,
+                    rhs,
+                    location,
+                    i128::checked_add,
+                    u128::wrapping_add,
+                )
+                .map(ArithmeticConstant::Integer)
+            }
+            (ArithmeticConstant::Integer(lhs), ArithmeticConstant::Integer(rhs)) => {
+                IntegerConstant::arithmetic(
+                    lhs,
+                    rhs,
+                    location,
+                    i128::checked_sub,
+                    u128::wrapping_sub,
+                )
+                .map(ArithmeticConstant::Integer)
+            }
+            (lhs, rhs) if add => Ok(ArithmeticConstant::floating_arithmetic(
+                lhs,
+                rhs,
+                |lhs, rhs| lhs + rhs,
+            )),
+            (lhs, rhs) => Ok(ArithmeticConstant::floating_arithmetic(
+                lhs,
+                rhs,
+                |lhs, rhs| lhs - rhs,
+            )),
+        }
+    }
+}
+
+impl EvaluateArithmetic for ShiftExpression {
+    fn evaluate_arithmetic(
+        &self,
+        operand_type: OperandType,
+    ) -> Result<ArithmeticConstant, ConstantError> {
+        match self {
+            ShiftExpression::AdditiveExpression(additive_expression) => {
+                additive_expression.evaluate_arithmetic(operand_type)
+            }
+            _ => self.evaluate_integer(operand_type),
+        }
+    }
+}
+
+impl EvaluateArithmetic for RelationalExpression {
+    fn evaluate_arithmetic(
+        &self,
+        operand_type: OperandType,
+    ) -> Result<ArithmeticConstant, ConstantError> {
+        let (lhs, rhs, predicate): (_, _, fn(Ordering) -> bool) = match self {
+            RelationalExpression::ShiftExpression(shift_expression) => {
+                return shift_expression.evaluate_arithmetic(operand_type)
+            }
+            RelationalExpression::Less { lhs, rhs, .. } => (lhs, rhs, Ordering::is_lt),
+            RelationalExpression::Greater { lhs, rhs, .. } => (lhs, rhs, Ordering::is_gt),
+            RelationalExpression::LessEqual { lhs, rhs, .. } => (lhs, rhs, Ordering::is_le),
+            RelationalExpression::GreaterEqual { lhs, rhs, .. } => (lhs, rhs, Ordering::is_ge),
+        };
+        Ok(ArithmeticConstant::comparison(
+            lhs.evaluate_arithmetic(operand_type)?,
+            rhs.evaluate_arithmetic(operand_type)?,
+            predicate,
+            false,
+        ))
+    }
+}
+
+impl EvaluateArithmetic for EqualityExpression {
+    fn evaluate_arithmetic(
+        &self,
+        operand_type: OperandType,
+    ) -> Result<ArithmeticConstant, ConstantError> {
+        let (lhs, rhs, predicate, unordered): (_, _, fn(Ordering) -> bool, _) = match self {
+            EqualityExpression::RelationalExpression(relational_expression) => {
+                return relational_expression.evaluate_arithmetic(operand_type)
+            }
+            EqualityExpression::Equal { lhs, rhs, .. } => (lhs, rhs, Ordering::is_eq, false),
+            EqualityExpression::NotEqual { lhs, rhs, .. } => (lhs, rhs, Ordering::is_ne, true),
+        };
+        Ok(ArithmeticConstant::comparison(
+            lhs.evaluate_arithmetic(operand_type)?,
+            rhs.evaluate_arithmetic(operand_type)?,
+            predicate,
+            unordered,
+        ))
+    }
+}
+
+impl EvaluateArithmetic for AssignmentExpression {
+    fn evaluate_arithmetic(
+        &self,
+        operand_type: OperandType,
+    ) -> Result<ArithmeticConstant, ConstantError> {
+        match self {
+            AssignmentExpression::EqualityExpression(equality_expression) => {
+                equality_expression.evaluate_arithmetic(operand_type)
+            }
+            AssignmentExpression::Assign { .. } => self.evaluate_integer(operand_type),
+        }
+    }
+}
+
+// Used to recognize simple patterns such as `i < n` and `i = i + 1`
+
+impl PrimaryExpression {
+    pub fn as_identifier(&self) -> Option<&EcoString> {
+        match self {
+            PrimaryExpression::Identifier { identifier, .. } => Some(identifier),
+            PrimaryExpression::Parenthesized(expression) => expression
+                .as_unary_expression()
+                .and_then(UnaryExpression::as_identifier),
+            PrimaryExpression::Constant { .. }
+            | PrimaryExpression::StringLiteral { .. }
+            | PrimaryExpression::VaBuiltin(_)
+            | PrimaryExpression::GenericSelection(_) => None,
+        }
+    }
+}
+
+impl PostfixExpression {
+    pub fn as_identifier(&self) -> Option<&EcoString> {
+        match self {
+            PostfixExpression::PrimaryExpression(primary_expression) => {
+                primary_expression.as_identifier()
+            }
+            _ => None,
+        }
+    }
+}
+
+impl UnaryExpression {
+    pub fn as_identifier(&self) -> Option<&EcoString> {
+        match self {
+            UnaryExpression::PostfixExpression(postfix_expression) => {
+                postfix_expression.as_identifier()
+            }
+            _ => None,
+        }
+    }
+}
+
+impl CastExpression {
+    pub fn as_identifier(&self) -> Option<&EcoString> {
+        self.as_unary_expression()
+            .and_then(UnaryExpression::as_identifier)
+    }
+
+    pub fn as_unary_expression(&self) -> Option<&UnaryExpression> {
+        match self {
+            CastExpression::UnaryExpression(unary_expression) => Some(unary_expression),
+            CastExpression::Cast { .. } => None,
+        }
+    }
+}
+
+impl MultiplicativeExpression {
+    pub fn as_cast_expression(&self) -> Option<&CastExpression> {
+        match self {
+            MultiplicativeExpression::CastExpression(cast_expression) => Some(cast_expression),
+            _ => None,
+        }
+    }
+
+    pub fn as_unary_expression(&self) -> Option<&UnaryExpression> {
+        self.as_cast_expression()
+            .and_then(CastExpression::as_unary_expression)
+    }
+}
+
+impl AdditiveExpression {
+    pub fn as_unary_expression(&self) -> Option<&UnaryExpression> {
+        match self {
+            AdditiveExpression::PrimaryExpression(multiplicative_expression) => {
+                multiplicative_expression.as_unary_expression()
+            }
+            _ => None,
+        }
+    }
+}
+
+impl ShiftExpression {
+    pub fn as_unary_expression(&self) -> Option<&UnaryExpression> {
+        match self {
+            ShiftExpression::AdditiveExpression(additive_expression) => {
+                additive_expression.as_unary_expression()
+            }
+            _ => None,
+        }
+    }
+}
+
+impl RelationalExpression {
+    pub fn as_unary_expression(&self) -> Option<&UnaryExpression> {
+        match self {
+            RelationalExpression::ShiftExpression(shift_expression) => {
+                shift_expression.as_unary_expression()
+            }
+            _ => None,
+        }
+    }
+}
+
+impl EqualityExpression {
+    pub fn as_unary_expression(&self) -> Option<&UnaryExpression> {
+        match self {
+            EqualityExpression::RelationalExpression(relational_expression) => {
+                relational_expression.as_unary_expression()
+            }
+            _ => None,
+        }
+    }
+}
+
+impl AssignmentExpression {
+    pub fn as_cast_expression(&self) -> Option<&CastExpression> {
+        match self {
+            AssignmentExpression::EqualityExpression(EqualityExpression::RelationalExpression(
+                RelationalExpression::ShiftExpression(ShiftExpression::AdditiveExpression(
+                    AdditiveExpression::PrimaryExpression(multiplicative_expression),
+                )),
+            )) => multiplicative_expression.as_cast_expression(),
+            _ => None,
+        }
+    }
+
+    pub fn as_unary_expression(&self) -> Option<&UnaryExpression> {
+        match self {
+            AssignmentExpression::EqualityExpression(equality_expression) => {
+                equality_expression.as_unary_expression()
+            }
+            AssignmentExpression::Assign { .. } => None,
+        }
+    }
+}
+
+impl Expression {
+    pub fn as_unary_expression(&self) -> Option<&UnaryExpression> {
+        match self {
+            Expression::AssignmentExpression(assignment_expression) => {
+                assignment_expression.as_unary_expression()
+            }
+        }
+    }
+}
+
+#[test]
+fn test_evaluate() {
+    use crate::{lexer::Lexer, types::IntegerKind};
+
+    let evaluate = |src: &str| {
+        let mut parser = Parser::new(Lexer::new("test.c".into(), src.as_bytes().to_vec()));
+        ConstantExpression::parse(&mut parser).unwrap().evaluate()
+    };
+    let unsigned_int = CType::Integer {
+        kind: IntegerKind::Int,
+        signed: false,
+    };
+
+    assert_eq!(
+        evaluate("1 + 2 * 3").unwrap(),
+        IntegerConstant {
+            value: 7,
+            r#type: CType::INT
+        }
+    );
+    // Unsigned arithmetic wraps around
+    assert_eq!(
+        evaluate("0u - 1").unwrap(),
+        IntegerConstant {
+            value: 0xFFFF_FFFF,
+            r#type: unsigned_int
+        }
+    );
+    // -1 is converted to unsigned int
+    assert_eq!(evaluate("0 - 1 < 0u").unwrap().value, 0);
+    assert!(matches!(
+        evaluate("2147483647 + 1"),
+        Err(ConstantError::Undefined { .. })
+    ));
+    assert!(matches!(
+        evaluate("1 / 0"),
+        Err(ConstantError::Undefined { .. })
+    ));
+    // The quotient of INT_MIN % -1 is not representable
+    assert!(matches!(
+        evaluate("(-2147483647 - 1) % -1"),
+        Err(ConstantError::Undefined { .. })
+    ));
+    assert!(matches!(
+        evaluate("(-9223372036854775807l - 1) % -1"),
+        Err(ConstantError::Undefined { .. })
+    ));
+    assert_eq!(evaluate("-7 % 2").unwrap().value, -1);
+    assert!(matches!(
+        evaluate("1 << 32"),
+        Err(ConstantError::Undefined { .. })
+    ));
+    assert!(matches!(
+        evaluate("1 + x"),
+        Err(ConstantError::NotConstant(_))
+    ));
+    assert_eq!(
+        evaluate("sizeof(int *) * (2 + 1)").unwrap(),
+        IntegerConstant {
+            value: 24,
+            r#type: CType::UNSIGNED_LONG
+        }
+    );
+    assert_eq!(
+        evaluate("_Generic(1u, int: 1, unsigned int: 2, default: 3)")
+            .unwrap()
+            .value,
+        2
+    );
+    assert_eq!(
+        evaluate("_Generic(sizeof(int), int: 1, default: 3)")
+            .unwrap()
+            .value,
+        3
+    );
+}
+
+#[test]
+fn test_evaluate_unary() {
+    use crate::{lexer::Lexer, types::IntegerKind};
+
+    let parse = |src: &str| {
+        let mut parser = Parser::new(Lexer::new("test.c".into(), src.as_bytes().to_vec()));
+        ConstantExpression::parse(&mut parser).unwrap()
+    };
+    let evaluate = |src: &str| parse(src).evaluate();
+    let unsigned_int = CType::Integer {
+        kind: IntegerKind::Int,
+        signed: false,
+    };
+
+    assert_eq!(
+        evaluate("-1").unwrap(),
+        IntegerConstant {
+            value: -1,
+            r#type: CType::INT
+        }
+    );
+    assert_eq!(evaluate("- -1 + +2").unwrap().value, 3);
+    // The operand is promoted
+    assert_eq!(
+        evaluate("-sizeof(char) == 0xFFFFFFFFFFFFFFFFul")
+            .unwrap()
+            .value,
+        1
+    );
+    assert_eq!(
+        evaluate("~0u").unwrap(),
+        IntegerConstant {
+            value: 0xFFFF_FFFF,
+            r#type: unsigned_int
+        }
+    );
+    assert_eq!(evaluate("~5").unwrap().value, -6);
+    assert_eq!(evaluate("!0").unwrap().value, 1);
+    assert_eq!(evaluate("!!7").unwrap().value, 1);
+    assert!(matches!(
+        evaluate("-(-2147483647 - 1)"),
+        Err(ConstantError::Undefined { .. })
+    ));
+    assert!(matches!(evaluate("-x"), Err(ConstantError::NotConstant(_))));
+
+    // `sizeof` of an expression needs the declarations
+    let expression = parse("sizeof x / sizeof x[0]");
+    assert!(matches!(
+        expression.evaluate(),
+        Err(ConstantError::NotConstant(_))
+    ));
+    let operand_type = |operand: &UnaryExpression, _: &Location| match operand.as_identifier() {
+        Some(_) => Some(CType::Array {
+            element: Box::new(CType::INT),
+            length: 4,
+        }),
+        None => Some(CType::INT),
+    };
+    assert_eq!(expression.evaluate_with(&operand_type).unwrap().value, 4);
+    // A variable length array has no constant size
+    let operand_type = |_: &UnaryExpression, _: &Location| {
+        Some(CType::VariableArray {
+            element: Box::new(CType::INT),
+            length: crate::types::VariableLength("n".into()),
+        })
+    };
+    assert!(matches!(
+        parse("sizeof x").evaluate_with(&operand_type),
+        Err(ConstantError::NotConstant(_))
+    ));
+}
+
+#[test]
+fn test_cast() {
+    use crate::{lexer::Lexer, types::IntegerKind};
+
+    let parse = |src: &str| {
+        let mut parser = Parser::new(Lexer::new("test.c".into(), src.as_bytes().to_vec()));
+        AssignmentExpression::parse(&mut parser).unwrap()
+    };
+    let unsigned_char = CType::Integer {
+        kind: IntegerKind::Char,
+        signed: false,
+    };
+
+    assert_eq!(
+        parse("(unsigned char)300").evaluate().unwrap(),
+        IntegerConstant {
+            value: 44,
+            r#type: unsigned_char
+        }
+    );
+    assert_eq!(parse("-(signed char)255").evaluate().unwrap().value, 1);
+    assert_eq!(
+        parse("(int)(unsigned char)-1 * 2")
+            .evaluate()
+            .unwrap()
+            .value,
+        510
+    );
+    assert_eq!(parse("(long)1 << 40").evaluate().unwrap().value, 1 << 40);
+    assert_eq!(parse("sizeof (int) * 2").evaluate().unwrap().value, 8);
+    // Only casts to integer types are integer constant expressions
+    assert!(matches!(
+        parse("(double)1").evaluate(),
+        Err(ConstantError::NotConstant(_))
+    ));
+    assert!(matches!(
+        parse("(int *)0").evaluate(),
+        Err(ConstantError::NotConstant(_))
+    ));
+
+    // A parenthesized expression is not a cast
+    assert!(matches!(
+        parse("(x)").as_cast_expression(),
+        Some(CastExpression::UnaryExpression(_))
+    ));
+    let expression = parse("*(int *)p");
+    let Some(UnaryExpression::Indirection { expression, .. }) = expression.as_unary_expression()
+    else {
+        panic!("{:?}", expression)
+    };
+    let CastExpression::Cast {
+        type_name,
+        expression,
+        ..
+    } = expression.as_ref()
+    else {
+        panic!("{:?}", expression)
+    };
+    assert_eq!(type_name.r#type().to_string(), "int *");
+    assert_eq!(expression.as_identifier().unwrap(), "p");
+
+    let expression = parse("(void)f()");
+    let Some(CastExpression::Cast {
+        type_name,
+        expression,
+        ..
+    }) = expression.as_cast_expression()
+    else {
+        panic!("{:?}", expression)
+    };
+    assert!(type_name.r#type().is_void());
+    assert!(matches!(
+        expression.as_unary_expression(),
+        Some(UnaryExpression::PostfixExpression(
+            PostfixExpression::FunctionCall { .. }
+        ))
+    ));
+}
+
+#[test]
+fn test_evaluate_arithmetic() {
+    use crate::lexer::Lexer;
+
+    let evaluate = |src: &str| {
+        let mut parser = Parser::new(Lexer::new("test.c".into(), src.as_bytes().to_vec()));
+        AssignmentExpression::parse(&mut parser)
+            .unwrap()
+            .evaluate_arithmetic(&|_, _| None)
+    };
+    let floating = |value: f64, kind: FloatingKind| ArithmeticConstant::Floating { value, kind };
+
+    assert_eq!(
+        evaluate("-1.0").unwrap(),
+        floating(-1.0, FloatingKind::Double)
+    );
+    assert_eq!(
+        evaluate("1.0 / 3").unwrap(),
+        floating(1.0 / 3.0, FloatingKind::Double)
+    );
+    // Rounded to float, unless the other operand is a double
+    assert_eq!(
+        evaluate("1.0f / 3").unwrap(),
+        floating((1.0f32 / 3.0) as f64, FloatingKind::Float)
+    );
+    assert_eq!(
+        evaluate("1.0f / 3.0").unwrap(),
+        floating(1.0 / 3.0, FloatingKind::Double)
+    );
+    assert_eq!(
+        evaluate("(float)0.1").unwrap(),
+        floating(0.1f32 as f64, FloatingKind::Float)
+    );
+    // Integer operations are done before the conversion
+    assert_eq!(
+        evaluate("1 / 2 * 2.0").unwrap(),
+        floating(0.0, FloatingKind::Double)
+    );
+    assert_eq!(
+        evaluate("(int)2.5 + 1").unwrap(),
+        ArithmeticConstant::Integer(IntegerConstant {
+            value: 3,
+            r#type: CType::INT
+        })
+    );
+    assert_eq!(evaluate("(int)-2.5").unwrap().to_f64(), -2.0);
+    assert_eq!(evaluate("(_Bool)0.5").unwrap().to_f64(), 1.0);
+    assert!(matches!(
+        evaluate("(signed char)300.0"),
+        Err(ConstantError::Undefined { .. })
+    ));
+    // Comparisons are ints
+    assert_eq!(
+        evaluate("1.0 < 2").unwrap(),
+        ArithmeticConstant::Integer(IntegerConstant {
+            value: 1,
+            r#type: CType::INT
+        })
+    );
+    assert_eq!(evaluate("0.1f == 0.1").unwrap().to_f64(), 0.0);
+    assert_eq!(evaluate("16777217 == 16777216.0f").unwrap().to_f64(), 1.0);
+    assert_eq!(evaluate("0.0 / 0.0 >= 0").unwrap().to_f64(), 0.0);
+    assert_eq!(evaluate("0.0 / 0.0 != 0.0 / 0.0").unwrap().to_f64(), 1.0);
+    assert_eq!(evaluate("(1.0 > 2.0) + 0.5").unwrap().to_f64(), 0.5);
+
+    // A floating constant may be the immediate operand of a cast in an integer constant
+    // expression
+    let mut parser = Parser::new(Lexer::new("test.c".into(), b"(int)2.5 * 2".to_vec()));
+    let expression = ConstantExpression::parse(&mut parser).unwrap();
+    assert_eq!(expression.evaluate().unwrap().value, 4);
+    let mut parser = Parser::new(Lexer::new("test.c".into(), b"(int)(2.5 * 2)".to_vec()));
+    let expression = ConstantExpression::parse(&mut parser).unwrap();
+    assert!(matches!(
+        expression.evaluate(),
+        Err(ConstantError::NotConstant(_))
+    ));
+    let mut parser = Parser::new(Lexer::new("test.c".into(), b"1.0 < 2.0".to_vec()));
+    let expression = ConstantExpression::parse(&mut parser).unwrap();
+    assert!(matches!(
+        expression.evaluate(),
+        Err(ConstantError::NotConstant(_))
+    ));
+}
+
+#[test]
+fn test_generic_selection_char() {
+    use crate::{lexer::Lexer, types::IntegerKind};
+
+    // Plain char is distinct from both, even with the range of unsigned char
+    let src = "_Generic(0, char: 0, signed char: 1, unsigned char: 2)";
+    let mut parser = Parser::new(Lexer::new("test.c".into(), src.as_bytes().to_vec()));
+    parser.set_unsigned_char(true);
+    let generic_selection = GenericSelection::parse(&mut parser).unwrap();
+    let types = generic_selection
+        .associations
+        .iter()
+        .map(|association| association.type_name.as_ref().unwrap().r#type())
+        .collect::<Vec<_>>();
+    assert_ne!(types[0], types[1]);
+    assert_ne!(types[0], types[2]);
+    assert_ne!(types[1], types[2]);
+
+    let select = |r#type: CType| {
+        generic_selection
+            .select(&r#type)
+            .unwrap()
+            .evaluate()
+            .unwrap()
+            .value
+    };
+    assert_eq!(select(parser.char_type()), 0);
+    assert_eq!(
+        select(CType::Integer {
+            kind: IntegerKind::Char,
+            signed: true
+        }),
+        1
+    );
+    assert_eq!(
+        select(CType::Integer {
+            kind: IntegerKind::Char,
+            signed: false
+        }),
+        2
+    );
+}
+
+#[test]
+fn test_generic_selection() {
+    use crate::lexer::Lexer;
+
+    let parse = |src: &str| {
+        let mut parser = Parser::new(Lexer::new("test.c".into(), src.as_bytes().to_vec()));
+        GenericSelection::parse(&mut parser)
+    };
+    let generic_selection =
+        parse("_Generic(x + 1, int: 1, default: 0, double: 2, long *: 3)").unwrap();
+    let types = generic_selection
+        .associations
+        .iter()
+        .map(|association| {
+            association
+                .type_name
+                .as_ref()
+                .map(|type_name| type_name.r#type().to_string())
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        types,
+        vec![
+            Some("int".to_string()),
+            None,
+            Some("double".to_string()),
+            Some("long *".to_string())
+        ]
+    );
+
+    let select = |r#type: CType| {
+        generic_selection
+            .select(&r#type)
+            .unwrap()
+            .evaluate()
+            .unwrap()
+            .value
+    };
+    assert_eq!(select(CType::INT), 1);
+    assert_eq!(select(CType::DOUBLE), 2);
+    assert_eq!(
+        select(CType::Pointer {
+            pointee: Box::new(CType::LONG),
+            r#const: false
+        }),
+        3
+    );
+    // Anything else, wherever `default` is
+    assert_eq!(select(CType::LONG), 0);
+
+    // No match without `default`
+    let generic_selection = parse("_Generic(1, long: 1, char: 2)").unwrap();
+    assert!(generic_selection.select(&CType::INT).is_none());
+    assert_eq!(
+        generic_selection
+            .select(&CType::LONG)
+            .unwrap()
+            .evaluate()
+            .unwrap()
+            .value,
+        1
+    );
+
+    // Only `default`
+    let generic_selection = parse("_Generic(1.0, default: 4)").unwrap();
+    assert_eq!(generic_selection.associations.len(), 1);
+    assert!(generic_selection.select(&CType::DOUBLE).is_some());
+
+    assert!(parse("_Generic(1)").is_err());
+    assert!(parse("_Generic(1, int 1)").is_err());
+    assert!(parse("_Generic(1, int: 1,)").is_err());
+}
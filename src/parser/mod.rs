@@ -1,9 +1,16 @@
-use std::fmt::Display;
+use std::{collections::HashMap, fmt::Display, rc::Rc};
 
+use declaration::{
+    declarator, Declaration, DeclarationSpecifiers, DeclaratorKind, Derivation, ParameterTypeList,
+    StorageClassSpecifier,
+};
 use ecow::EcoString;
 use statement::CompoundStatement;
 
-use crate::lexer::{Lexer, Location, Token, TokenKind};
+use crate::{
+    lexer::{FloatingSuffix, Lexer, Location, Token, TokenKind},
+    types::{CType, FloatingKind, IntegerKind, Record},
+};
 pub mod declaration;
 pub mod expression;
 pub mod statement;
@@ -15,7 +22,8 @@ pub mod statement;
 
 #[derive(Debug)]
 pub enum Constant {
-    Integer(i64),
+    Integer { value: u64, r#type: CType },
+    Floating { value: f64, r#type: CType },
 }
 
 // 6.9
@@ -25,8 +33,11 @@ pub struct TranslationUnit(pub Vec<ExternalDeclaration>);
 
 impl Parse for TranslationUnit {
     fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
-        let external_declarations = parser.many1()?;
-        parser.expect_eof()?;
+        // Parse up to EOF so that the error of any external declaration is reported
+        let mut external_declarations = vec![ExternalDeclaration::parse(parser)?];
+        while !parser.at_eof() {
+            external_declarations.push(ExternalDeclaration::parse(parser)?);
+        }
         Ok(TranslationUnit(external_declarations))
     }
 }
@@ -34,42 +45,124 @@ impl Parse for TranslationUnit {
 #[derive(Debug)]
 pub enum ExternalDeclaration {
     FunctionDefinition(FunctionDefinition),
+    FunctionDeclaration(FunctionDeclaration),
+    Declaration(Declaration),
 }
 
 impl Parse for ExternalDeclaration {
     fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
-        let function_definition = FunctionDefinition::parse(parser)?;
-        Ok(ExternalDeclaration::FunctionDefinition(function_definition))
+        let pos = parser.lexer.current_position();
+        // 6.7.8 A typedef of a function type is an ordinary declaration
+        if let Some(declaration) = FunctionDeclaration::parse(parser)
+            .ok()
+            .filter(|declaration| {
+                declaration.declaration_specifiers.storage_class
+                    != Some(StorageClassSpecifier::Typedef)
+            })
+        {
+            if parser.expect(TokenKind::SemiColon).is_ok() {
+                return Ok(ExternalDeclaration::FunctionDeclaration(declaration));
+            }
+            // Followed by other declarators, it is an ordinary declaration
+            if parser.expect(TokenKind::Comma).is_err() {
+                // 6.2.1 Parameters are in the scope of the body
+                parser.push_scope();
+                let body = declaration
+                    .parameter_type_list
+                    .parameters
+                    .iter()
+                    .try_for_each(|parameter| match &parameter.declarator {
+                        Some(identifier) => parser.declare_object(identifier, &parameter.location),
+                        None => Ok(()),
+                    })
+                    .and_then(|()| CompoundStatement::parse(parser));
+                parser.pop_scope();
+                let body = body?;
+                return Ok(ExternalDeclaration::FunctionDefinition(
+                    FunctionDefinition { declaration, body },
+                ));
+            }
+        }
+        parser.lexer.set_position(pos);
+        let declaration = Declaration::parse(parser)?;
+        Ok(ExternalDeclaration::Declaration(declaration))
     }
 }
 
-// 6.9.1
-
+// 6.7 A declaration of a single function without the trailing semicolon
 #[derive(Debug)]
-pub struct FunctionDefinition {
+pub struct FunctionDeclaration {
     pub location: Location,
+    pub declaration_specifiers: DeclarationSpecifiers,
     pub identifier: EcoString,
-    pub body: CompoundStatement,
+    // Of the return type
+    pub derivations: Vec<Derivation>,
+    pub parameter_type_list: ParameterTypeList,
 }
 
-impl Parse for FunctionDefinition {
+impl Parse for FunctionDeclaration {
     fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
-        let int = parser.expect(TokenKind::Int)?;
-        let identifier = parser.expect_identifier()?;
-        parser.expect(TokenKind::LParen)?;
-        parser.expect(TokenKind::RParen)?;
-        let body = CompoundStatement::parse(parser)?;
-
-        Ok(FunctionDefinition {
-            location: int.location,
-            identifier,
-            body,
+        let declaration_specifiers = DeclarationSpecifiers::parse(parser)?;
+        let (location, identifier, mut derivations) = declarator(parser, DeclaratorKind::Concrete)?;
+        // The function derivation applies last, as in `int (*f(void))[4]`
+        let Some(Derivation::Function(parameter_type_list)) = derivations.pop() else {
+            return Err(ParseError::new(
+                location,
+                parser.lexer.current_line().to_string(),
+                "expected a function declarator".to_string(),
+            ));
+        };
+
+        Ok(FunctionDeclaration {
+            location,
+            declaration_specifiers,
+            identifier: identifier.unwrap(),
+            derivations,
+            parameter_type_list,
         })
     }
 }
 
+// 6.9.1
+
+#[derive(Debug)]
+pub struct FunctionDefinition {
+    pub declaration: FunctionDeclaration,
+    pub body: CompoundStatement,
+}
+
 pub struct Parser {
     lexer: Lexer,
+    // 6.2.1 Innermost last
+    scopes: Vec<Scope>,
+    // Whether plain char is unsigned, which is implementation-defined 6.2.5
+    unsigned_char: bool,
+}
+
+// The identifiers declared in a scope that are needed to parse the rest of it
+#[derive(Default)]
+struct Scope {
+    // 6.2.3 Tags of structures, unions and enumerations
+    tags: HashMap<EcoString, Tag>,
+    // 6.2.3 Ordinary identifiers, where objects only hide enumeration constants of outer scopes
+    ordinary: HashMap<EcoString, Ordinary>,
+}
+
+#[derive(Clone)]
+enum Tag {
+    Record(Rc<Record>),
+    // 6.7.2.2 The compatible integer type, and the opening brace of the definition which tells
+    // it parsed again after backtracking
+    Enum { r#type: CType, location: Location },
+}
+
+#[derive(Clone)]
+enum Ordinary {
+    Object,
+    // 6.7.2.2 An enumeration constant of type int, and where it is declared
+    Enumerator { value: i128, location: Location },
+    // 6.7.8 A synonym for the type, with whether it is const
+    Typedef { r#type: CType, r#const: bool },
 }
 
 #[derive(Debug)]
@@ -118,7 +211,187 @@ impl Display for ParseError {
 
 impl Parser {
     pub fn new(lexer: Lexer) -> Self {
-        Self { lexer }
+        Self {
+            lexer,
+            scopes: vec![Scope::default()],
+            unsigned_char: false,
+        }
+    }
+
+    /// Makes plain char unsigned, like `-funsigned-char` of GCC
+    pub fn set_unsigned_char(&mut self, unsigned_char: bool) {
+        self.unsigned_char = unsigned_char;
+    }
+
+    // The type of plain char, which has the same range as either signed or unsigned char
+    pub fn char_type(&self) -> CType {
+        CType::Integer {
+            kind: IntegerKind::PlainChar,
+            signed: !self.unsigned_char,
+        }
+    }
+
+    pub fn push_scope(&mut self) {
+        self.scopes.push(Scope::default());
+    }
+
+    pub fn pop_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn lookup_tag(&self, tag: &str) -> Option<Tag> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.tags.get(tag))
+            .cloned()
+    }
+
+    // In the innermost scope
+    fn current_tag(&self, tag: &str) -> Option<Tag> {
+        self.scopes.last().unwrap().tags.get(tag).cloned()
+    }
+
+    fn declare_tag(&mut self, tag: EcoString, value: Tag) {
+        self.scopes.last_mut().unwrap().tags.insert(tag, value);
+    }
+
+    fn lookup_ordinary(&self, identifier: &str) -> Option<Ordinary> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.ordinary.get(identifier))
+            .cloned()
+    }
+
+    // In the innermost scope
+    fn current_ordinary(&self, identifier: &str) -> Option<Ordinary> {
+        self.scopes
+            .last()
+            .unwrap()
+            .ordinary
+            .get(identifier)
+            .cloned()
+    }
+
+    /// Declares an object or a function so that it hides enumeration constants. Other
+    /// redeclarations are checked while lowering.
+    pub fn declare_object(
+        &mut self,
+        identifier: &EcoString,
+        location: &Location,
+    ) -> Result<(), ParseError> {
+        if let Some(Ordinary::Enumerator { .. } | Ordinary::Typedef { .. }) =
+            self.current_ordinary(identifier)
+        {
+            return Err(ParseError::new(
+                location.clone(),
+                self.lexer.current_line().to_string(),
+                format!(
+                    "redefinition of '{}' as different kind of symbol",
+                    identifier
+                ),
+            ));
+        }
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .ordinary
+            .insert(identifier.clone(), Ordinary::Object);
+        Ok(())
+    }
+
+    // 6.7 A typedef name may be redefined in the same scope only to the same type
+    fn declare_typedef(
+        &mut self,
+        identifier: &EcoString,
+        r#type: CType,
+        r#const: bool,
+        location: &Location,
+    ) -> Result<(), ParseError> {
+        let message = match self.current_ordinary(identifier) {
+            None => None,
+            Some(Ordinary::Typedef {
+                r#type: previous,
+                r#const: previous_const,
+            }) => (previous != r#type || previous_const != r#const).then(|| {
+                format!(
+                    "typedef redefinition with different types ('{}' vs '{}')",
+                    r#type, previous
+                )
+            }),
+            Some(Ordinary::Object | Ordinary::Enumerator { .. }) => Some(format!(
+                "redefinition of '{}' as different kind of symbol",
+                identifier
+            )),
+        };
+        if let Some(message) = message {
+            return Err(ParseError::new(
+                location.clone(),
+                self.lexer.current_line().to_string(),
+                message,
+            ));
+        }
+        self.declare_ordinary(identifier.clone(), Ordinary::Typedef { r#type, r#const });
+        Ok(())
+    }
+
+    /// Tells whether a declaration follows rather than a statement
+    pub fn at_declaration(&mut self) -> bool {
+        let pos = self.lexer.current_position();
+        let static_assert = self.expect(TokenKind::StaticAssert).is_ok();
+        self.lexer.set_position(pos);
+        static_assert || self.at_declaration_specifiers()
+    }
+
+    /// Tells whether declaration specifiers follow, where an identifier may be a typedef name
+    /// 6.7.8. A declaration and an expression are told apart by this, since `T * x;` is either.
+    pub fn at_declaration_specifiers(&mut self) -> bool {
+        let pos = self.lexer.current_position();
+        let token = self.lexer.next();
+        let next = self.lexer.next();
+        self.lexer.set_position(pos);
+        match token.map(|token| token.kind) {
+            Some(
+                TokenKind::Typedef
+                | TokenKind::Extern
+                | TokenKind::Static
+                | TokenKind::Const
+                | TokenKind::Void
+                | TokenKind::VaList
+                | TokenKind::Bool
+                | TokenKind::Char
+                | TokenKind::Short
+                | TokenKind::Int
+                | TokenKind::Long
+                | TokenKind::Int128
+                | TokenKind::Float
+                | TokenKind::Double
+                | TokenKind::Complex
+                | TokenKind::Signed
+                | TokenKind::Unsigned
+                | TokenKind::Struct
+                | TokenKind::Union
+                | TokenKind::Enum
+                | TokenKind::Alignas,
+            ) => true,
+            // A label has its own name space 6.2.3
+            Some(TokenKind::Identifier(identifier)) => {
+                matches!(
+                    self.lookup_ordinary(&identifier),
+                    Some(Ordinary::Typedef { .. })
+                ) && !next.is_some_and(|token| token.kind == TokenKind::Colon)
+            }
+            _ => false,
+        }
+    }
+
+    fn declare_ordinary(&mut self, identifier: EcoString, value: Ordinary) {
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .ordinary
+            .insert(identifier, value);
     }
 
     pub fn expect(&mut self, token_kind: TokenKind) -> Result<Token, ParseError> {
@@ -144,24 +417,56 @@ impl Parser {
         }
     }
 
-    pub fn expect_integer(&mut self) -> Result<(Location, i64), ParseError> {
+    // 6.4.4.1, 6.4.4.2
+    pub fn expect_constant(&mut self) -> Result<(Location, Constant), ParseError> {
         let pos = self.lexer.current_position();
         match self.lexer.next() {
             Some(token) => match token.kind {
-                TokenKind::Integer(value) => Ok((token.location, value)),
+                TokenKind::Integer {
+                    value,
+                    decimal,
+                    suffix,
+                } => match CType::integer_constant(value, decimal, suffix) {
+                    Some(r#type) => Ok((token.location, Constant::Integer { value, r#type })),
+                    None => Err(ParseError::new(
+                        token.location,
+                        self.lexer.current_line().to_string(),
+                        "integer constant is too large for its type".to_string(),
+                    )),
+                },
+                TokenKind::TooLargeInteger => Err(ParseError::new(
+                    token.location,
+                    self.lexer.current_line().to_string(),
+                    "integer literal is too large to be represented in any integer type"
+                        .to_string(),
+                )),
+                TokenKind::Floating { value, suffix } => {
+                    let r#type = match suffix {
+                        FloatingSuffix::None => CType::DOUBLE,
+                        FloatingSuffix::Float => CType::Floating(FloatingKind::Float),
+                        FloatingSuffix::Long => {
+                            return Err(ParseError::new(
+                                token.location,
+                                self.lexer.current_line().to_string(),
+                                "'long double' is not supported".to_string(),
+                            ))
+                        }
+                    };
+                    Ok((token.location, Constant::Floating { value, r#type }))
+                }
                 _ => {
                     self.lexer.set_position(pos);
                     Err(ParseError::new(
                         token.location,
                         self.lexer.current_line().to_string(),
-                        format!("expected integer, found {:?}", token.kind),
+                        format!("expected constant, found {:?}", token.kind),
                     ))
                 }
             },
             None => Err(ParseError::new(
                 self.lexer.current_location(),
                 self.lexer.current_line().to_string(),
-                "expected integer, found EOF".to_string(),
+                "expected constant, found EOF".to_string(),
             )),
         }
     }
@@ -188,6 +493,37 @@ impl Parser {
         }
     }
 
+    pub fn expect_string_literal(&mut self) -> Option<Vec<u8>> {
+        let pos = self.lexer.current_position();
+        match self.lexer.next() {
+            Some(Token {
+                kind: TokenKind::StringLiteral(value),
+                ..
+            }) => Some(value),
+            _ => {
+                self.lexer.set_position(pos);
+                None
+            }
+        }
+    }
+
+    pub fn peek_location(&mut self) -> Location {
+        let pos = self.lexer.current_position();
+        let location = match self.lexer.next() {
+            Some(token) => token.location,
+            None => self.lexer.current_location(),
+        };
+        self.lexer.set_position(pos);
+        location
+    }
+
+    pub fn at_eof(&mut self) -> bool {
+        let pos = self.lexer.current_position();
+        let eof = self.lexer.next().is_none();
+        self.lexer.set_position(pos);
+        eof
+    }
+
     pub fn expect_eof(&mut self) -> Result<(), ParseError> {
         match self.lexer.next() {
             Some(token) => Err(ParseError::new(
@@ -199,9 +535,8 @@ impl Parser {
         }
     }
 
-    pub fn many1<P: Parse>(&mut self) -> Result<Vec<P>, ParseError> {
+    pub fn many0<P: Parse>(&mut self) -> Result<Vec<P>, ParseError> {
         let mut items = Vec::new();
-        items.push(P::parse(self)?);
         while {
             let pos = self.lexer.current_position();
             match P::parse(self) {
@@ -217,4 +552,102 @@ impl Parser {
         } {}
         Ok(items)
     }
+
+    pub fn many1<P: Parse>(&mut self) -> Result<Vec<P>, ParseError> {
+        let mut items = vec![P::parse(self)?];
+        items.extend(self.many0()?);
+        Ok(items)
+    }
+}
+
+#[test]
+fn test_function_declaration() {
+    let src = "int f(int a, ...); static const int x = 1; void g(void) { f(1, 2); }"
+        .as_bytes()
+        .to_vec();
+    let mut parser = Parser::new(Lexer::new("test.c".into(), src));
+
+    let TranslationUnit(external_declarations) = TranslationUnit::parse(&mut parser).unwrap();
+    let [ExternalDeclaration::FunctionDeclaration(declaration), ExternalDeclaration::Declaration(_), ExternalDeclaration::FunctionDefinition(definition)] =
+        external_declarations.as_slice()
+    else {
+        panic!("expected declarations and a definition");
+    };
+    assert_eq!(declaration.identifier, "f");
+    assert_eq!(declaration.parameter_type_list.parameters.len(), 1);
+    assert!(declaration.parameter_type_list.variadic);
+    assert_eq!(definition.declaration.identifier, "g");
+    assert!(definition
+        .declaration
+        .parameter_type_list
+        .parameters
+        .is_empty());
+    assert_eq!(definition.body.block_items.len(), 1);
+}
+
+#[test]
+fn test_function_declarator() {
+    let src = "int (*f(void))[4]; typedef int g(void); int (*h)(void);"
+        .as_bytes()
+        .to_vec();
+    let mut parser = Parser::new(Lexer::new("test.c".into(), src));
+
+    let TranslationUnit(external_declarations) = TranslationUnit::parse(&mut parser).unwrap();
+    let [ExternalDeclaration::FunctionDeclaration(declaration), ExternalDeclaration::Declaration(Declaration::Typedef { .. }), ExternalDeclaration::Declaration(Declaration::NoAttr { .. })] =
+        external_declarations.as_slice()
+    else {
+        panic!("expected a function, a typedef and a pointer");
+    };
+    // It returns a pointer to an array
+    assert_eq!(declaration.identifier, "f");
+    assert!(matches!(
+        declaration.derivations.as_slice(),
+        [Derivation::Array(_), Derivation::Pointer(_)]
+    ));
+}
+
+#[test]
+fn test_function_declarator_list() {
+    let src = "int f(void), g(void), *h; int f(void) { return g(); }"
+        .as_bytes()
+        .to_vec();
+    let mut parser = Parser::new(Lexer::new("test.c".into(), src));
+
+    let TranslationUnit(external_declarations) = TranslationUnit::parse(&mut parser).unwrap();
+    let [ExternalDeclaration::Declaration(Declaration::NoAttr {
+        init_declarator_list,
+        ..
+    }), ExternalDeclaration::FunctionDefinition(definition)] = external_declarations.as_slice()
+    else {
+        panic!("expected a declaration and a function definition");
+    };
+    let identifiers = init_declarator_list
+        .iter()
+        .map(|init_declarator| init_declarator.declarator.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(identifiers, vec!["f", "g", "h"]);
+    assert_eq!(definition.declaration.identifier, "f");
+
+    let src = "int f(void), { return 0; }".as_bytes().to_vec();
+    let mut parser = Parser::new(Lexer::new("test.c".into(), src));
+    assert!(TranslationUnit::parse(&mut parser).is_err());
+}
+
+#[test]
+fn test_translation_unit_error() {
+    let parse = |src: &str| {
+        let mut parser = Parser::new(Lexer::new("test.c".into(), src.as_bytes().to_vec()));
+        TranslationUnit::parse(&mut parser)
+    };
+    // The error of a later declaration is not replaced by "expected EOF"
+    let error = parse("int x;\n_Static_assert(1, 2);").unwrap_err();
+    assert_eq!(error.message, "expected string literal");
+    assert_eq!((error.location.line, error.location.column), (1, 18));
+    let error = parse("int x; _Static_assert(1, \"a\";").unwrap_err();
+    assert!(
+        error.message.starts_with("expected RParen"),
+        "{}",
+        error.message
+    );
+    assert!(parse("int x; int y;").is_ok());
 }
@@ -1,21 +1,126 @@
-use super::{declaration::Declaration, expression::Expression, Parse, ParseError, Parser};
+use ecow::EcoString;
+
+use super::{
+    declaration::Declaration,
+    expression::{ConstantExpression, Expression},
+    Parse, ParseError, Parser,
+};
 
 use crate::lexer::{Location, TokenKind};
 
 // 6.8
 
+#[derive(Debug)]
+pub enum Statement {
+    LabeledStatement(LabeledStatement),
+    UnlabeledStatement(UnlabeledStatement),
+}
+
+impl Parse for Statement {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let pos = parser.lexer.current_position();
+        if let Ok(labeled_statement) = LabeledStatement::parse(parser) {
+            return Ok(Statement::LabeledStatement(labeled_statement));
+        }
+        parser.lexer.set_position(pos);
+        let unlabeled_statement = UnlabeledStatement::parse(parser)?;
+        Ok(Statement::UnlabeledStatement(unlabeled_statement))
+    }
+}
+
 #[derive(Debug)]
 pub enum UnlabeledStatement {
+    CompoundStatement(CompoundStatement),
+    ExpressionStatement(ExpressionStatement),
+    SelectionStatement(SelectionStatement),
+    IterationStatement(IterationStatement),
     JumpStatement(JumpStatement),
 }
 
 impl Parse for UnlabeledStatement {
     fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let pos = parser.lexer.current_position();
+        if let Ok(compound_statement) = CompoundStatement::parse(parser) {
+            return Ok(UnlabeledStatement::CompoundStatement(compound_statement));
+        }
+        parser.lexer.set_position(pos);
+        if let Ok(expression_statement) = ExpressionStatement::parse(parser) {
+            return Ok(UnlabeledStatement::ExpressionStatement(
+                expression_statement,
+            ));
+        }
+        parser.lexer.set_position(pos);
+        if let Ok(selection_statement) = SelectionStatement::parse(parser) {
+            return Ok(UnlabeledStatement::SelectionStatement(selection_statement));
+        }
+        parser.lexer.set_position(pos);
+        if let Ok(iteration_statement) = IterationStatement::parse(parser) {
+            return Ok(UnlabeledStatement::IterationStatement(iteration_statement));
+        }
+        parser.lexer.set_position(pos);
         let jump_statement = JumpStatement::parse(parser)?;
         Ok(UnlabeledStatement::JumpStatement(jump_statement))
     }
 }
 
+// 6.8.1
+
+#[derive(Debug)]
+pub enum Label {
+    Identifier {
+        location: Location,
+        identifier: EcoString,
+    },
+    Case {
+        location: Location,
+        expression: Box<ConstantExpression>,
+    },
+    Default {
+        location: Location,
+    },
+}
+
+impl Parse for Label {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let location = parser.peek_location();
+        if let Ok(identifier) = parser.expect_identifier() {
+            parser.expect(TokenKind::Colon)?;
+            return Ok(Label::Identifier {
+                location,
+                identifier,
+            });
+        }
+        if let Ok(t) = parser.expect(TokenKind::Case) {
+            let expression = ConstantExpression::parse(parser)?;
+            parser.expect(TokenKind::Colon)?;
+            return Ok(Label::Case {
+                location: t.location,
+                expression: Box::new(expression),
+            });
+        }
+        let location = parser.expect(TokenKind::Default)?.location;
+        parser.expect(TokenKind::Colon)?;
+        Ok(Label::Default { location })
+    }
+}
+
+#[derive(Debug)]
+pub struct LabeledStatement {
+    pub label: Label,
+    pub statement: Box<Statement>,
+}
+
+impl Parse for LabeledStatement {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let label = Label::parse(parser)?;
+        let statement = Statement::parse(parser)?;
+        Ok(LabeledStatement {
+            label,
+            statement: Box::new(statement),
+        })
+    }
+}
+
 // 6.8.2
 #[derive(Debug)]
 pub struct CompoundStatement {
@@ -25,7 +130,10 @@ pub struct CompoundStatement {
 impl Parse for CompoundStatement {
     fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
         parser.expect(TokenKind::LBrace)?;
-        let block_items = parser.many1()?;
+        parser.push_scope();
+        let block_items = parser.many0();
+        parser.pop_scope();
+        let block_items = block_items?;
         parser.expect(TokenKind::RBrace)?;
         Ok(CompoundStatement { block_items })
     }
@@ -35,37 +143,422 @@ impl Parse for CompoundStatement {
 pub enum BlockItem {
     Declaration(Declaration),
     UnlabeledStatement(UnlabeledStatement),
+    Label(Label),
 }
 
 impl Parse for BlockItem {
     fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
         let pos = parser.lexer.current_position();
-        if let Ok(unlabeled_statement) = UnlabeledStatement::parse(parser) {
-            return Ok(BlockItem::UnlabeledStatement(unlabeled_statement));
+        if let Ok(label) = Label::parse(parser) {
+            return Ok(BlockItem::Label(label));
         }
         parser.lexer.set_position(pos);
-        let declaration = Declaration::parse(parser)?;
-        Ok(BlockItem::Declaration(declaration))
+        if parser.at_declaration() {
+            let declaration = Declaration::parse(parser)?;
+            return Ok(BlockItem::Declaration(declaration));
+        }
+        let unlabeled_statement = UnlabeledStatement::parse(parser)?;
+        Ok(BlockItem::UnlabeledStatement(unlabeled_statement))
+    }
+}
+
+// 6.8.3
+#[derive(Debug)]
+pub struct ExpressionStatement {
+    // `None` for a null statement
+    pub expression: Option<Expression>,
+}
+
+impl Parse for ExpressionStatement {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let expression = optional_expression(parser, TokenKind::SemiColon)?;
+        Ok(ExpressionStatement { expression })
+    }
+}
+
+// 6.8.4
+
+#[derive(Debug)]
+pub enum SelectionStatement {
+    If {
+        location: Location,
+        condition: Expression,
+        then_statement: Box<Statement>,
+        else_statement: Option<Box<Statement>>,
+    },
+    Switch {
+        location: Location,
+        condition: Expression,
+        body: Box<Statement>,
+    },
+}
+
+impl Parse for SelectionStatement {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        if let Ok(t) = parser.expect(TokenKind::If) {
+            parser.expect(TokenKind::LParen)?;
+            let condition = Expression::parse(parser)?;
+            parser.expect(TokenKind::RParen)?;
+            let then_statement = Statement::parse(parser)?;
+            // An else is associated with the lexically nearest preceding if
+            let else_statement = if parser.expect(TokenKind::Else).is_ok() {
+                Some(Box::new(Statement::parse(parser)?))
+            } else {
+                None
+            };
+            return Ok(SelectionStatement::If {
+                location: t.location,
+                condition,
+                then_statement: Box::new(then_statement),
+                else_statement,
+            });
+        }
+        let location = parser.expect(TokenKind::Switch)?.location;
+        parser.expect(TokenKind::LParen)?;
+        let condition = Expression::parse(parser)?;
+        parser.expect(TokenKind::RParen)?;
+        let body = Statement::parse(parser)?;
+        Ok(SelectionStatement::Switch {
+            location,
+            condition,
+            body: Box::new(body),
+        })
     }
 }
 
+// 6.8.5
+
+#[derive(Debug)]
+pub enum IterationStatement {
+    While {
+        location: Location,
+        condition: Expression,
+        body: Box<Statement>,
+    },
+    DoWhile {
+        location: Location,
+        body: Box<Statement>,
+        condition: Expression,
+    },
+    For {
+        location: Location,
+        init: Option<ForInit>,
+        condition: Option<Expression>,
+        step: Option<Expression>,
+        body: Box<Statement>,
+    },
+}
+
+#[derive(Debug)]
+pub enum ForInit {
+    Expression(Expression),
+    Declaration(Box<Declaration>),
+}
+
+impl Parse for IterationStatement {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        if let Ok(t) = parser.expect(TokenKind::While) {
+            parser.expect(TokenKind::LParen)?;
+            let condition = Expression::parse(parser)?;
+            parser.expect(TokenKind::RParen)?;
+            let body = Statement::parse(parser)?;
+            return Ok(IterationStatement::While {
+                location: t.location,
+                condition,
+                body: Box::new(body),
+            });
+        }
+        if let Ok(t) = parser.expect(TokenKind::Do) {
+            let body = Statement::parse(parser)?;
+            parser.expect(TokenKind::While)?;
+            parser.expect(TokenKind::LParen)?;
+            let condition = Expression::parse(parser)?;
+            parser.expect(TokenKind::RParen)?;
+            parser.expect(TokenKind::SemiColon)?;
+            return Ok(IterationStatement::DoWhile {
+                location: t.location,
+                body: Box::new(body),
+                condition,
+            });
+        }
+        let location = parser.expect(TokenKind::For)?.location;
+        // 6.8.5 Identifiers declared in the init clause go out of scope after the statement
+        parser.push_scope();
+        let for_statement = for_statement(parser, location);
+        parser.pop_scope();
+        for_statement
+    }
+}
+
+// The rest of a for statement following `for`
+fn for_statement(
+    parser: &mut Parser,
+    location: Location,
+) -> Result<IterationStatement, ParseError> {
+    parser.expect(TokenKind::LParen)?;
+    let init = ForInit::parse(parser)?;
+    let condition = optional_expression(parser, TokenKind::SemiColon)?;
+    let step = optional_expression(parser, TokenKind::RParen)?;
+    let body = Statement::parse(parser)?;
+    Ok(IterationStatement::For {
+        location,
+        init,
+        condition,
+        step,
+        body: Box::new(body),
+    })
+}
+
+impl ForInit {
+    // The declaration consumes its own semicolon
+    fn parse(parser: &mut Parser) -> Result<Option<Self>, ParseError> {
+        if parser.at_declaration_specifiers() {
+            let declaration = Declaration::parse(parser)?;
+            return Ok(Some(ForInit::Declaration(Box::new(declaration))));
+        }
+        Ok(optional_expression(parser, TokenKind::SemiColon)?.map(ForInit::Expression))
+    }
+}
+
+// Parses `expression? terminator` as found in the clauses of `for`
+fn optional_expression(
+    parser: &mut Parser,
+    terminator: TokenKind,
+) -> Result<Option<Expression>, ParseError> {
+    if parser.expect(terminator.clone()).is_ok() {
+        return Ok(None);
+    }
+    let expression = Expression::parse(parser)?;
+    parser.expect(terminator)?;
+    Ok(Some(expression))
+}
+
 // 6.8.6
 #[derive(Debug)]
 pub enum JumpStatement {
+    Goto {
+        location: Location,
+        identifier: EcoString,
+    },
+    Break {
+        location: Location,
+    },
+    Continue {
+        location: Location,
+    },
     Return {
         location: Location,
-        expression: Expression,
+        expression: Option<Expression>,
     },
 }
 
 impl Parse for JumpStatement {
     fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        if let Ok(t) = parser.expect(TokenKind::Goto) {
+            let identifier = parser.expect_identifier()?;
+            parser.expect(TokenKind::SemiColon)?;
+            return Ok(JumpStatement::Goto {
+                location: t.location,
+                identifier,
+            });
+        }
+        if let Ok(t) = parser.expect(TokenKind::Break) {
+            parser.expect(TokenKind::SemiColon)?;
+            return Ok(JumpStatement::Break {
+                location: t.location,
+            });
+        }
+        if let Ok(t) = parser.expect(TokenKind::Continue) {
+            parser.expect(TokenKind::SemiColon)?;
+            return Ok(JumpStatement::Continue {
+                location: t.location,
+            });
+        }
         let location = parser.expect(TokenKind::Return)?.location;
-        let expression = Expression::parse(parser)?;
-        parser.expect(TokenKind::SemiColon)?;
+        let expression = optional_expression(parser, TokenKind::SemiColon)?;
         Ok(JumpStatement::Return {
             location,
             expression,
         })
     }
 }
+
+#[test]
+fn test_dangling_else() {
+    use crate::lexer::Lexer;
+
+    let src = "if (1) if (0) return 1; else return 2;".as_bytes().to_vec();
+    let mut parser = Parser::new(Lexer::new("test.c".into(), src));
+
+    let Statement::UnlabeledStatement(UnlabeledStatement::SelectionStatement(
+        SelectionStatement::If {
+            then_statement,
+            else_statement,
+            ..
+        },
+    )) = Statement::parse(&mut parser).unwrap()
+    else {
+        panic!("expected if statement");
+    };
+    assert!(else_statement.is_none());
+    assert!(matches!(
+        *then_statement,
+        Statement::UnlabeledStatement(UnlabeledStatement::SelectionStatement(
+            SelectionStatement::If {
+                else_statement: Some(_),
+                ..
+            }
+        ))
+    ));
+}
+
+#[test]
+fn test_for_statement() {
+    use crate::lexer::Lexer;
+
+    let src = "for (int i = 0; i < 10; i = i + 1) continue;"
+        .as_bytes()
+        .to_vec();
+    let mut parser = Parser::new(Lexer::new("test.c".into(), src));
+
+    let Statement::UnlabeledStatement(UnlabeledStatement::IterationStatement(
+        IterationStatement::For {
+            init,
+            condition,
+            step,
+            body,
+            ..
+        },
+    )) = Statement::parse(&mut parser).unwrap()
+    else {
+        panic!("expected for statement");
+    };
+    assert!(matches!(init, Some(ForInit::Declaration(_))));
+    assert!(condition.is_some());
+    assert!(step.is_some());
+    assert!(matches!(
+        *body,
+        Statement::UnlabeledStatement(UnlabeledStatement::JumpStatement(
+            JumpStatement::Continue { .. }
+        ))
+    ));
+
+    let src = "for (;;) break;".as_bytes().to_vec();
+    let mut parser = Parser::new(Lexer::new("test.c".into(), src));
+    assert!(matches!(
+        Statement::parse(&mut parser).unwrap(),
+        Statement::UnlabeledStatement(UnlabeledStatement::IterationStatement(
+            IterationStatement::For {
+                init: None,
+                condition: None,
+                step: None,
+                ..
+            }
+        ))
+    ));
+}
+
+#[test]
+fn test_goto_and_label() {
+    use crate::lexer::Lexer;
+
+    let src = "{ goto end; end: return 0; }".as_bytes().to_vec();
+    let mut parser = Parser::new(Lexer::new("test.c".into(), src));
+
+    let compound_statement = CompoundStatement::parse(&mut parser).unwrap();
+    assert!(matches!(
+        &compound_statement.block_items[..],
+        [
+            BlockItem::UnlabeledStatement(UnlabeledStatement::JumpStatement(
+                JumpStatement::Goto { identifier, .. }
+            )),
+            BlockItem::Label(Label::Identifier {
+                identifier: label,
+                ..
+            }),
+            BlockItem::UnlabeledStatement(UnlabeledStatement::JumpStatement(
+                JumpStatement::Return { .. }
+            )),
+        ] if identifier == "end" && label == "end"
+    ));
+}
+
+#[test]
+fn test_expression_statement() {
+    use crate::lexer::Lexer;
+
+    let src = "{ ; {} x = 1; x++; }".as_bytes().to_vec();
+    let mut parser = Parser::new(Lexer::new("test.c".into(), src));
+
+    let compound_statement = CompoundStatement::parse(&mut parser).unwrap();
+    assert!(matches!(
+        compound_statement.block_items.as_slice(),
+        [
+            BlockItem::UnlabeledStatement(UnlabeledStatement::ExpressionStatement(
+                ExpressionStatement { expression: None }
+            )),
+            BlockItem::UnlabeledStatement(UnlabeledStatement::CompoundStatement(
+                CompoundStatement { block_items: empty }
+            )),
+            BlockItem::UnlabeledStatement(UnlabeledStatement::ExpressionStatement(
+                ExpressionStatement {
+                    expression: Some(_)
+                }
+            )),
+            BlockItem::UnlabeledStatement(UnlabeledStatement::ExpressionStatement(
+                ExpressionStatement {
+                    expression: Some(_)
+                }
+            )),
+        ] if empty.is_empty()
+    ));
+}
+
+#[test]
+fn test_typedef_name() {
+    use crate::lexer::Lexer;
+
+    let src = "typedef int T; { T * x; T: x; { int T; T * 2; } }"
+        .as_bytes()
+        .to_vec();
+    let mut parser = Parser::new(Lexer::new("test.c".into(), src));
+
+    assert!(matches!(
+        Declaration::parse(&mut parser).unwrap(),
+        Declaration::Typedef { .. }
+    ));
+    let compound_statement = CompoundStatement::parse(&mut parser).unwrap();
+    let [BlockItem::Declaration(_), BlockItem::Label(_), BlockItem::UnlabeledStatement(_), BlockItem::UnlabeledStatement(UnlabeledStatement::CompoundStatement(inner))] =
+        &compound_statement.block_items[..]
+    else {
+        panic!("expected a declaration, a labeled statement and a block");
+    };
+    // `T` is hidden by the object
+    assert!(matches!(
+        &inner.block_items[..],
+        [BlockItem::Declaration(_), BlockItem::UnlabeledStatement(_)]
+    ));
+}
+
+#[test]
+fn test_for_scope() {
+    use crate::lexer::Lexer;
+
+    let src = "{ typedef int T; for (int T = 0; T < 1; T++) T * 2; T * x; }"
+        .as_bytes()
+        .to_vec();
+    let mut parser = Parser::new(Lexer::new("test.c".into(), src));
+
+    let compound_statement = CompoundStatement::parse(&mut parser).unwrap();
+    // `T` names the type again after the loop
+    assert!(matches!(
+        &compound_statement.block_items[..],
+        [
+            BlockItem::Declaration(Declaration::Typedef { .. }),
+            BlockItem::UnlabeledStatement(UnlabeledStatement::IterationStatement(
+                IterationStatement::For { .. }
+            )),
+            BlockItem::Declaration(Declaration::NoAttr { .. }),
+        ]
+    ));
+}
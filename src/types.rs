@@ -0,0 +1,1038 @@
+use std::{
+    cell::{Ref, RefCell},
+    fmt::{Debug, Display},
+    rc::Rc,
+};
+
+use ecow::EcoString;
+
+use crate::lexer::{IntegerSuffix, Location};
+
+// 6.2.5
+// Sizes follow the LP64 data model
+
+// Ordered by integer conversion rank 6.3.1.1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IntegerKind {
+    // 6.2.5 An unsigned type holding 0 or 1
+    Bool,
+    // `signed char` or `unsigned char`
+    Char,
+    // 6.2.5 Plain `char` is a distinct type of the same rank, which has the range of either
+    // `signed char` or `unsigned char` as chosen by the parser
+    PlainChar,
+    Short,
+    Int,
+    Long,
+    LongLong,
+    // A GCC extension ranked above the standard integer types
+    Int128,
+}
+
+impl IntegerKind {
+    // In bits of its MLIR type. `_Bool` takes a byte in memory like the other types.
+    pub fn width(self) -> u32 {
+        match self {
+            IntegerKind::Bool | IntegerKind::Char | IntegerKind::PlainChar => 8,
+            IntegerKind::Short => 16,
+            IntegerKind::Int => 32,
+            IntegerKind::Long => 64,
+            IntegerKind::LongLong => 64,
+            IntegerKind::Int128 => 128,
+        }
+    }
+}
+
+// 6.2.5 The real floating types, which are also the real parts of the complex types. `long
+// double` is not supported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FloatingKind {
+    Float,
+    Double,
+}
+
+impl FloatingKind {
+    // IEEE 754 binary32 and binary64
+    pub fn width(self) -> u32 {
+        match self {
+            FloatingKind::Float => 32,
+            FloatingKind::Double => 64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CType {
+    // 6.2.5 An incomplete object type that cannot be completed
+    Void,
+    Integer {
+        kind: IntegerKind,
+        signed: bool,
+    },
+    Floating(FloatingKind),
+    // 6.2.5 Laid out like an array of the real and the imaginary part
+    Complex(FloatingKind),
+    // 6.7.6.3 `()` is taken as `(void)` like C23
+    Function {
+        return_type: Box<CType>,
+        parameters: Vec<CType>,
+        variadic: bool,
+    },
+    // 6.7.6.1 `r#const` qualifies the pointee
+    Pointer {
+        pointee: Box<CType>,
+        r#const: bool,
+    },
+    // 6.7.6.2 Qualifiers of an array apply to its elements
+    Array {
+        element: Box<CType>,
+        length: u64,
+    },
+    // 6.7.6.2 An array whose length is only known at run time, where its declaration is reached
+    VariableArray {
+        element: Box<CType>,
+        length: VariableLength,
+    },
+    // 7.16 The x86-64 System V layout
+    VaList,
+    // 6.7.2.1 A structure or union
+    Record(Rc<Record>),
+}
+
+/// The hidden local holding the length of a variable length array. Two of them are compatible
+/// 6.7.6.2, since their values are not known until run time.
+#[derive(Debug, Clone)]
+pub struct VariableLength(pub EcoString);
+
+impl PartialEq for VariableLength {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for VariableLength {}
+
+// 6.7.2.1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordKind {
+    Struct,
+    Union,
+}
+
+/// 6.7.2.3 A structure or union type. Each declaration of a new tag declares a distinct type, so
+/// records are compared by identity.
+pub struct Record {
+    pub kind: RecordKind,
+    pub tag: Option<EcoString>,
+    // `None` until the closing brace of the definition
+    layout: RefCell<Option<Layout>>,
+}
+
+impl PartialEq for Record {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Eq for Record {}
+
+// Members are left out, since a record may contain a pointer to itself
+impl Debug for Record {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}({:?})", self.kind, self.tag)
+    }
+}
+
+impl Record {
+    /// An incomplete type, which is completed by `define`
+    pub fn new(kind: RecordKind, tag: Option<EcoString>) -> Self {
+        Self {
+            kind,
+            tag,
+            layout: RefCell::new(None),
+        }
+    }
+
+    pub fn layout(&self) -> Option<Ref<'_, Layout>> {
+        Ref::filter_map(self.layout.borrow(), Option::as_ref).ok()
+    }
+
+    pub fn define(&self, layout: Layout) {
+        *self.layout.borrow_mut() = Some(layout);
+    }
+
+    /// Finds a member, which may be in an anonymous structure or union 6.7.2.1. Returns the
+    /// indices of the members on the way from the outermost.
+    pub fn find_member(&self, identifier: &str) -> Option<Vec<usize>> {
+        let layout = self.layout()?;
+        layout
+            .members
+            .iter()
+            .enumerate()
+            .find_map(
+                |(index, member)| match (&member.identifier, &member.r#type) {
+                    (Some(name), _) if name == identifier => Some(vec![index]),
+                    (None, CType::Record(record)) => {
+                        let mut path = record.find_member(identifier)?;
+                        path.insert(0, index);
+                        Some(path)
+                    }
+                    _ => None,
+                },
+            )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Member {
+    // `None` for an anonymous structure or union
+    pub identifier: Option<EcoString>,
+    pub r#type: CType,
+    pub r#const: bool,
+    // Of the type, raised by `_Alignas` and capped by `#pragma pack`
+    pub alignment: u64,
+    // In bytes. Of the byte holding the lowest bit of a bit-field.
+    pub offset: u64,
+    pub bit_field: Option<BitField>,
+}
+
+impl Member {
+    /// The alignment the member is accessed with if it is less than that of its type, which is
+    /// the case in a packed structure. `alignment` is that of the structure if it is less.
+    pub fn access_alignment(&self, alignment: Option<u64>) -> Option<u64> {
+        let alignment = alignment.map_or(self.alignment, |alignment| alignment.min(self.alignment));
+        (alignment < self.r#type.alignment()).then_some(alignment)
+    }
+}
+
+// 6.7.2.1 A member of `width` bits starting at `bit_offset` of the byte at its offset
+#[derive(Debug, Clone, Copy)]
+pub struct BitField {
+    pub width: u64,
+    pub bit_offset: u64,
+}
+
+#[derive(Debug)]
+pub struct Layout {
+    // Of the opening brace, which tells a definition parsed again after backtracking
+    pub location: Location,
+    pub members: Vec<Member>,
+    pub size: u64,
+    pub alignment: u64,
+}
+
+impl Layout {
+    /// The alignment of the bytes at `offset`, which is that of the record unless the offset is
+    /// not a multiple of it. `alignment` is that of the record if it is less.
+    pub fn offset_alignment(&self, offset: u64, alignment: Option<u64>) -> u64 {
+        let alignment = alignment.map_or(self.alignment, |alignment| alignment.min(self.alignment));
+        match offset {
+            0 => alignment,
+            offset => alignment.min(1 << offset.trailing_zeros()),
+        }
+    }
+
+    /// Places the members following the x86-64 System V ABI. Members of a structure are
+    /// allocated in order at the next offset aligned for them, and those of a union overlap at
+    /// 0. The size is rounded up to the strictest alignment.
+    ///
+    /// A bit-field takes the next bits unless they cross a unit of the size of its type, in
+    /// which case it starts at the next unit. A zero-width one also moves to the next unit.
+    /// Unnamed bit-fields do not affect the alignment of the record. Under `#pragma pack`
+    /// bit-fields are not kept within units, as in GCC.
+    pub fn new(kind: RecordKind, location: Location, mut members: Vec<Member>) -> Self {
+        // In bits, since bit-fields are not aligned to bytes
+        let mut size: u64 = 0;
+        let mut alignment: u64 = 1;
+        for member in &mut members {
+            let start = match kind {
+                RecordKind::Struct => size,
+                RecordKind::Union => 0,
+            };
+            let (start, end) = match member.bit_field {
+                None => {
+                    let start = start.div_ceil(8).next_multiple_of(member.alignment) * 8;
+                    (start, start + member.r#type.size() * 8)
+                }
+                Some(BitField { width: 0, .. }) => {
+                    let start = start.next_multiple_of(member.alignment * 8);
+                    (start, start)
+                }
+                Some(BitField { width, .. }) => {
+                    let unit = member.r#type.size() * 8;
+                    let packed = member.alignment < member.r#type.alignment();
+                    let start = if !packed && start / unit != (start + width - 1) / unit {
+                        start.next_multiple_of(unit)
+                    } else {
+                        start
+                    };
+                    (start, start + width)
+                }
+            };
+            member.offset = start / 8;
+            if let Some(bit_field) = &mut member.bit_field {
+                bit_field.bit_offset = start % 8;
+            }
+            size = size.max(end);
+            if member.bit_field.is_none() || member.identifier.is_some() {
+                alignment = alignment.max(member.alignment);
+            }
+        }
+        Layout {
+            location,
+            members,
+            size: size.div_ceil(8).next_multiple_of(alignment),
+            alignment,
+        }
+    }
+}
+
+impl CType {
+    pub const BOOL: CType = CType::Integer {
+        kind: IntegerKind::Bool,
+        signed: false,
+    };
+    // Plain char, which is signed unless the parser is told otherwise
+    pub const CHAR: CType = CType::Integer {
+        kind: IntegerKind::PlainChar,
+        signed: true,
+    };
+    pub const DOUBLE: CType = CType::Floating(FloatingKind::Double);
+    pub const INT: CType = CType::Integer {
+        kind: IntegerKind::Int,
+        signed: true,
+    };
+    // ptrdiff_t 7.19
+    pub const LONG: CType = CType::Integer {
+        kind: IntegerKind::Long,
+        signed: true,
+    };
+    // size_t 7.19
+    pub const UNSIGNED_LONG: CType = CType::Integer {
+        kind: IntegerKind::Long,
+        signed: false,
+    };
+
+    pub fn is_void(&self) -> bool {
+        matches!(self, CType::Void)
+    }
+
+    pub fn is_integer(&self) -> bool {
+        matches!(self, CType::Integer { .. })
+    }
+
+    // 6.2.5 Real floating types
+    pub fn is_floating(&self) -> bool {
+        matches!(self, CType::Floating(_))
+    }
+
+    pub fn is_complex(&self) -> bool {
+        matches!(self, CType::Complex(_))
+    }
+
+    // 6.2.5 Integer and floating types
+    pub fn is_arithmetic(&self) -> bool {
+        self.is_integer() || self.is_floating() || self.is_complex()
+    }
+
+    pub fn is_pointer(&self) -> bool {
+        matches!(self, CType::Pointer { .. })
+    }
+
+    pub fn is_function(&self) -> bool {
+        matches!(self, CType::Function { .. })
+    }
+
+    pub fn is_array(&self) -> bool {
+        matches!(self, CType::Array { .. } | CType::VariableArray { .. })
+    }
+
+    // 6.7.6.2 A variable length array, or an array of them
+    pub fn is_variably_sized(&self) -> bool {
+        match self {
+            CType::VariableArray { .. } => true,
+            CType::Array { element, .. } => element.is_variably_sized(),
+            _ => false,
+        }
+    }
+
+    // 6.7.6 Derived from a variable length array, which may be pointed to
+    pub fn is_variably_modified(&self) -> bool {
+        match self {
+            CType::VariableArray { .. } => true,
+            CType::Array { element, .. } => element.is_variably_modified(),
+            CType::Pointer { pointee, .. } => pointee.is_variably_modified(),
+            _ => false,
+        }
+    }
+
+    pub fn is_record(&self) -> bool {
+        matches!(self, CType::Record(_))
+    }
+
+    // 6.2.5 An object type whose size is known
+    pub fn is_complete(&self) -> bool {
+        match self {
+            CType::Void | CType::Function { .. } => false,
+            CType::Record(record) => record.layout().is_some(),
+            CType::Array { element, .. } | CType::VariableArray { element, .. } => {
+                element.is_complete()
+            }
+            CType::Integer { .. }
+            | CType::Floating(_)
+            | CType::Complex(_)
+            | CType::Pointer { .. }
+            | CType::VaList => true,
+        }
+    }
+
+    // 6.2.5 Arithmetic and pointer types
+    pub fn is_scalar(&self) -> bool {
+        self.is_arithmetic() || self.is_pointer()
+    }
+
+    pub fn is_signed(&self) -> bool {
+        match self {
+            CType::Void
+            | CType::Function { .. }
+            | CType::Floating(_)
+            | CType::Complex(_)
+            | CType::Pointer { .. }
+            | CType::Array { .. }
+            | CType::VariableArray { .. }
+            | CType::VaList
+            | CType::Record(_) => false,
+            CType::Integer { signed, .. } => *signed,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        match self {
+            CType::Void
+            | CType::Function { .. }
+            | CType::Complex(_)
+            | CType::Pointer { .. }
+            | CType::Array { .. }
+            | CType::VariableArray { .. }
+            | CType::VaList
+            | CType::Record(_) => unreachable!("{:?} has no width", self),
+            CType::Integer { kind, .. } => kind.width(),
+            CType::Floating(kind) => kind.width(),
+        }
+    }
+
+    /// `sizeof` in bytes 6.5.3.4. That of a variable length array is computed at run time.
+    pub fn size(&self) -> u64 {
+        match self {
+            CType::Void | CType::Function { .. } | CType::VariableArray { .. } => {
+                unreachable!("{:?} has no constant size", self)
+            }
+            CType::Integer { kind, .. } => kind.width() as u64 / 8,
+            CType::Floating(kind) => kind.width() as u64 / 8,
+            CType::Complex(kind) => kind.width() as u64 / 4,
+            CType::Pointer { .. } => 8,
+            CType::Array { element, length } => element.size() * length,
+            CType::VaList => 24,
+            CType::Record(record) => match record.layout() {
+                Some(layout) => layout.size,
+                None => unreachable!("{:?} is incomplete", record),
+            },
+        }
+    }
+
+    /// `_Alignof` in bytes 6.5.3.4
+    pub fn alignment(&self) -> u64 {
+        match self {
+            CType::Void | CType::Function { .. } => unreachable!("{:?} has no alignment", self),
+            CType::Integer { .. } | CType::Floating(_) | CType::Pointer { .. } => self.size(),
+            CType::Complex(kind) => kind.width() as u64 / 8,
+            CType::Array { element, .. } | CType::VariableArray { element, .. } => {
+                element.alignment()
+            }
+            CType::VaList => 8,
+            CType::Record(record) => match record.layout() {
+                Some(layout) => layout.alignment,
+                None => unreachable!("{:?} is incomplete", record),
+            },
+        }
+    }
+
+    // 6.2.7 The same types, except that an array whose length is not a constant is compatible
+    // with an array of any length
+    pub fn is_compatible(&self, other: &CType) -> bool {
+        match (self, other) {
+            (
+                CType::Pointer {
+                    pointee: lhs,
+                    r#const: lhs_const,
+                },
+                CType::Pointer {
+                    pointee: rhs,
+                    r#const: rhs_const,
+                },
+            ) => lhs_const == rhs_const && lhs.is_compatible(rhs),
+            (
+                CType::Array {
+                    element: lhs,
+                    length: lhs_length,
+                },
+                CType::Array {
+                    element: rhs,
+                    length: rhs_length,
+                },
+            ) => lhs_length == rhs_length && lhs.is_compatible(rhs),
+            (
+                CType::Array { element: lhs, .. } | CType::VariableArray { element: lhs, .. },
+                CType::Array { element: rhs, .. } | CType::VariableArray { element: rhs, .. },
+            ) => lhs.is_compatible(rhs),
+            _ => self == other,
+        }
+    }
+
+    // 6.5.16.1 Pointers may be converted to pointers to compatible types with more qualifiers, or
+    // from and to pointers to void
+    pub fn is_pointer_convertible(&self, to: &CType) -> bool {
+        let (
+            CType::Pointer {
+                pointee: from_pointee,
+                r#const: from_const,
+            },
+            CType::Pointer {
+                pointee: to_pointee,
+                r#const: to_const,
+            },
+        ) = (self, to)
+        else {
+            return false;
+        };
+        (from_pointee.is_compatible(to_pointee) || from_pointee.is_void() || to_pointee.is_void())
+            && (!from_const || *to_const)
+    }
+
+    // 6.3.1.3 Converts an integer constant to this type
+    pub fn convert_constant(&self, value: i128) -> i128 {
+        // 6.3.1.2
+        if *self == CType::BOOL {
+            return (value != 0) as i128;
+        }
+        let shift = 128 - self.width();
+        if self.is_signed() {
+            (value << shift) >> shift
+        } else {
+            (((value as u128) << shift) >> shift) as i128
+        }
+    }
+
+    // 6.3.1.1 Types ranked below int are promoted to int, which represents all of their values
+    pub fn integer_promotion(&self) -> CType {
+        match self {
+            CType::Integer { kind, .. } if *kind < IntegerKind::Int => CType::INT,
+            _ => self.clone(),
+        }
+    }
+
+    // 6.5.2.2 Applied to arguments without a parameter type, which also promotes float to double
+    pub fn default_argument_promotion(&self) -> CType {
+        match self {
+            CType::Floating(FloatingKind::Float) => CType::DOUBLE,
+            _ => self.integer_promotion(),
+        }
+    }
+
+    // 6.3.1.8 If either operand is floating, both are converted to the wider real type, which is
+    // complex if either is
+    pub fn usual_arithmetic_conversion(lhs: &CType, rhs: &CType) -> CType {
+        let real = |r#type: &CType| match r#type {
+            CType::Floating(kind) | CType::Complex(kind) => Some(*kind),
+            _ => None,
+        };
+        if let Some(kind) = real(lhs).max(real(rhs)) {
+            return if lhs.is_complex() || rhs.is_complex() {
+                CType::Complex(kind)
+            } else {
+                CType::Floating(kind)
+            };
+        }
+        let (
+            CType::Integer {
+                kind: lhs_kind,
+                signed: lhs_signed,
+            },
+            CType::Integer {
+                kind: rhs_kind,
+                signed: rhs_signed,
+            },
+        ) = (lhs.integer_promotion(), rhs.integer_promotion())
+        else {
+            unreachable!("usual arithmetic conversion of a non-arithmetic type");
+        };
+
+        if lhs_signed == rhs_signed {
+            return CType::Integer {
+                kind: lhs_kind.max(rhs_kind),
+                signed: lhs_signed,
+            };
+        }
+
+        let (signed_kind, unsigned_kind) = if lhs_signed {
+            (lhs_kind, rhs_kind)
+        } else {
+            (rhs_kind, lhs_kind)
+        };
+
+        if unsigned_kind >= signed_kind {
+            CType::Integer {
+                kind: unsigned_kind,
+                signed: false,
+            }
+        } else if signed_kind.width() > unsigned_kind.width() {
+            CType::Integer {
+                kind: signed_kind,
+                signed: true,
+            }
+        } else {
+            CType::Integer {
+                kind: signed_kind,
+                signed: false,
+            }
+        }
+    }
+
+    // 6.4.4.1 The type of an integer constant is the first of the list in which its value can be represented
+    pub fn integer_constant(value: u64, decimal: bool, suffix: IntegerSuffix) -> Option<CType> {
+        use IntegerKind::*;
+
+        let candidates: &[(IntegerKind, bool)] = match (suffix, decimal) {
+            (IntegerSuffix::None, true) => &[(Int, true), (Long, true), (LongLong, true)],
+            (IntegerSuffix::None, false) => &[
+                (Int, true),
+                (Int, false),
+                (Long, true),
+                (Long, false),
+                (LongLong, true),
+                (LongLong, false),
+            ],
+            (IntegerSuffix::Unsigned, _) => &[(Int, false), (Long, false), (LongLong, false)],
+            (IntegerSuffix::Long, true) => &[(Long, true), (LongLong, true)],
+            (IntegerSuffix::Long, false) => &[
+                (Long, true),
+                (Long, false),
+                (LongLong, true),
+                (LongLong, false),
+            ],
+            (IntegerSuffix::UnsignedLong, _) => &[(Long, false), (LongLong, false)],
+            (IntegerSuffix::LongLong, true) => &[(LongLong, true)],
+            (IntegerSuffix::LongLong, false) => &[(LongLong, true), (LongLong, false)],
+            (IntegerSuffix::UnsignedLongLong, _) => &[(LongLong, false)],
+        };
+
+        candidates.iter().find_map(|&(kind, signed)| {
+            let width = kind.width();
+            let max = if signed {
+                (1u64 << (width - 1)) - 1
+            } else {
+                u64::MAX >> (64 - width)
+            };
+            (value <= max).then_some(CType::Integer { kind, signed })
+        })
+    }
+}
+
+// Spelled like the declarations of the type
+impl Display for CType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.spell(false, String::new()))
+    }
+}
+
+impl Display for FloatingKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FloatingKind::Float => write!(f, "float"),
+            FloatingKind::Double => write!(f, "double"),
+        }
+    }
+}
+
+impl CType {
+    // Wraps the abstract declarator `declarator` inside out, like `int (*)[4]`. `r#const`
+    // qualifies this type.
+    fn spell(&self, r#const: bool, declarator: String) -> String {
+        // Postfix declarators bind tighter than `*`
+        let parenthesize = |declarator: String| {
+            if declarator.starts_with('*') {
+                format!("({})", declarator)
+            } else {
+                declarator
+            }
+        };
+        let specifiers = match self {
+            CType::Void => "void".to_string(),
+            CType::Integer {
+                kind: IntegerKind::Bool,
+                ..
+            } => "_Bool".to_string(),
+            CType::Integer {
+                kind: IntegerKind::PlainChar,
+                ..
+            } => "char".to_string(),
+            CType::Integer {
+                kind: IntegerKind::Char,
+                signed: true,
+            } => "signed char".to_string(),
+            CType::Integer { kind, signed } => {
+                let kind = match kind {
+                    IntegerKind::Bool | IntegerKind::PlainChar => unreachable!(),
+                    IntegerKind::Char => "char",
+                    IntegerKind::Short => "short",
+                    IntegerKind::Int => "int",
+                    IntegerKind::Long => "long",
+                    IntegerKind::LongLong => "long long",
+                    IntegerKind::Int128 => "__int128",
+                };
+                if *signed {
+                    kind.to_string()
+                } else {
+                    format!("unsigned {}", kind)
+                }
+            }
+            CType::Floating(kind) => kind.to_string(),
+            CType::Complex(kind) => format!("_Complex {}", kind),
+            CType::VaList => "va_list".to_string(),
+            CType::Record(record) => {
+                let kind = match record.kind {
+                    RecordKind::Struct => "struct",
+                    RecordKind::Union => "union",
+                };
+                match &record.tag {
+                    Some(tag) => format!("{} {}", kind, tag),
+                    None => format!("{} (anonymous)", kind),
+                }
+            }
+            CType::Function {
+                return_type,
+                parameters,
+                variadic,
+            } => {
+                let mut parameters = parameters
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>();
+                if *variadic {
+                    parameters.push("...".to_string());
+                } else if parameters.is_empty() {
+                    parameters.push("void".to_string());
+                }
+                let declarator = format!("{}({})", parenthesize(declarator), parameters.join(", "));
+                return return_type.spell(false, declarator);
+            }
+            CType::Pointer {
+                pointee,
+                r#const: pointee_const,
+            } => {
+                let declarator = if r#const {
+                    format!("*const {}", declarator)
+                } else {
+                    format!("*{}", declarator)
+                };
+                return pointee.spell(*pointee_const, declarator.trim_end().to_string());
+            }
+            CType::Array { element, length } => {
+                let declarator = format!("{}[{}]", parenthesize(declarator), length);
+                return element.spell(r#const, declarator);
+            }
+            CType::VariableArray { element, .. } => {
+                let declarator = format!("{}[*]", parenthesize(declarator));
+                return element.spell(r#const, declarator);
+            }
+        };
+        let specifiers = if r#const {
+            format!("const {}", specifiers)
+        } else {
+            specifiers
+        };
+        if declarator.is_empty() {
+            specifiers
+        } else {
+            format!("{} {}", specifiers, declarator)
+        }
+    }
+}
+
+#[test]
+fn test_integer_constant_type() {
+    assert_eq!(
+        CType::integer_constant(42, true, IntegerSuffix::None),
+        Some(CType::INT)
+    );
+    assert_eq!(
+        CType::integer_constant(0xFFFF_FFFF, false, IntegerSuffix::None),
+        Some(CType::Integer {
+            kind: IntegerKind::Int,
+            signed: false
+        })
+    );
+    assert_eq!(
+        CType::integer_constant(0xFFFF_FFFF, true, IntegerSuffix::None),
+        Some(CType::Integer {
+            kind: IntegerKind::Long,
+            signed: true
+        })
+    );
+    assert_eq!(
+        CType::integer_constant(u64::MAX, true, IntegerSuffix::None),
+        None
+    );
+}
+
+#[test]
+fn test_usual_arithmetic_conversion() {
+    let unsigned_int = CType::Integer {
+        kind: IntegerKind::Int,
+        signed: false,
+    };
+    let long = CType::Integer {
+        kind: IntegerKind::Long,
+        signed: true,
+    };
+    assert_eq!(
+        CType::usual_arithmetic_conversion(&CType::INT, &unsigned_int),
+        unsigned_int
+    );
+    assert_eq!(
+        CType::usual_arithmetic_conversion(&unsigned_int, &long),
+        long
+    );
+    let unsigned_short = CType::Integer {
+        kind: IntegerKind::Short,
+        signed: false,
+    };
+    assert_eq!(
+        CType::usual_arithmetic_conversion(&unsigned_short, &CType::BOOL),
+        CType::INT
+    );
+    let int128 = CType::Integer {
+        kind: IntegerKind::Int128,
+        signed: true,
+    };
+    let unsigned_long_long = CType::Integer {
+        kind: IntegerKind::LongLong,
+        signed: false,
+    };
+    assert_eq!(
+        CType::usual_arithmetic_conversion(&unsigned_long_long, &int128),
+        int128
+    );
+    assert_eq!(CType::BOOL.convert_constant(256), 1);
+    let float = CType::Floating(FloatingKind::Float);
+    assert_eq!(CType::usual_arithmetic_conversion(&long, &float), float);
+    assert_eq!(
+        CType::usual_arithmetic_conversion(&CType::Complex(FloatingKind::Float), &CType::DOUBLE),
+        CType::Complex(FloatingKind::Double)
+    );
+    assert_eq!(float.default_argument_promotion(), CType::DOUBLE);
+}
+
+#[test]
+fn test_display() {
+    let array = CType::Array {
+        element: Box::new(CType::Array {
+            element: Box::new(CType::INT),
+            length: 4,
+        }),
+        length: 2,
+    };
+    assert_eq!(array.to_string(), "int [2][4]");
+    let pointer = CType::Pointer {
+        pointee: Box::new(CType::Pointer {
+            pointee: Box::new(CType::CHAR),
+            r#const: true,
+        }),
+        r#const: true,
+    };
+    assert_eq!(pointer.to_string(), "const char *const *");
+    let pointer = CType::Pointer {
+        pointee: Box::new(CType::Array {
+            element: Box::new(CType::INT),
+            length: 4,
+        }),
+        r#const: false,
+    };
+    assert_eq!(pointer.to_string(), "int (*)[4]");
+    let pointer = CType::Pointer {
+        pointee: Box::new(CType::VariableArray {
+            element: Box::new(CType::INT),
+            length: VariableLength("a[1]".into()),
+        }),
+        r#const: false,
+    };
+    assert_eq!(pointer.to_string(), "int (*)[*]");
+    let array = CType::Array {
+        element: Box::new(CType::Complex(FloatingKind::Float)),
+        length: 2,
+    };
+    assert_eq!(array.to_string(), "_Complex float [2]");
+    assert!(!pointer.is_variably_sized());
+    assert!(pointer.is_variably_modified());
+}
+
+#[test]
+fn test_compatible() {
+    let array = |length| CType::Array {
+        element: Box::new(CType::INT),
+        length,
+    };
+    let variable_array = CType::VariableArray {
+        element: Box::new(CType::INT),
+        length: VariableLength("a[0]".into()),
+    };
+    assert!(array(4).is_compatible(&variable_array));
+    assert!(variable_array.is_compatible(&array(2)));
+    assert!(!array(4).is_compatible(&array(2)));
+    assert!(!variable_array.is_compatible(&CType::INT));
+}
+
+#[test]
+fn test_layout() {
+    let location = Location {
+        filename: "test.c".into(),
+        line: 0,
+        column: 0,
+    };
+    let member = |r#type: CType, alignment: u64| Member {
+        identifier: None,
+        r#type,
+        r#const: false,
+        alignment,
+        offset: 0,
+        bit_field: None,
+    };
+    let layout = Layout::new(
+        RecordKind::Struct,
+        location.clone(),
+        vec![
+            member(CType::CHAR, 1),
+            member(CType::LONG, 8),
+            member(CType::INT, 4),
+        ],
+    );
+    let offsets = layout.members.iter().map(|member| member.offset);
+    assert_eq!(offsets.collect::<Vec<_>>(), vec![0, 8, 16]);
+    assert_eq!((layout.size, layout.alignment), (24, 8));
+    // Under `#pragma pack(1)`
+    let layout = Layout::new(
+        RecordKind::Struct,
+        location.clone(),
+        vec![member(CType::CHAR, 1), member(CType::INT, 1)],
+    );
+    assert_eq!((layout.members[1].offset, layout.size), (1, 5));
+    assert_eq!(layout.members[1].access_alignment(None), Some(1));
+    assert_eq!(layout.members[0].access_alignment(None), None);
+    // A structure in a member of a packed one
+    let layout = Layout::new(
+        RecordKind::Struct,
+        location.clone(),
+        vec![member(CType::INT, 4)],
+    );
+    assert_eq!(layout.members[0].access_alignment(None), None);
+    assert_eq!(layout.members[0].access_alignment(Some(2)), Some(2));
+    assert_eq!(layout.members[0].access_alignment(Some(8)), None);
+    let layout = Layout::new(
+        RecordKind::Union,
+        location.clone(),
+        vec![member(CType::CHAR, 1), member(CType::INT, 16)],
+    );
+    assert_eq!((layout.size, layout.alignment), (16, 16));
+    // `struct { char a : 3; int b : 30; char c : 7; int : 0; char d; }`
+    let bit_field = |identifier: Option<&str>, r#type: CType, width: u64| Member {
+        identifier: identifier.map(Into::into),
+        bit_field: Some(BitField {
+            width,
+            bit_offset: 0,
+        }),
+        ..member(r#type.clone(), r#type.alignment())
+    };
+    let layout = Layout::new(
+        RecordKind::Struct,
+        location,
+        vec![
+            bit_field(Some("a"), CType::CHAR, 3),
+            bit_field(Some("b"), CType::INT, 30),
+            bit_field(Some("c"), CType::CHAR, 7),
+            bit_field(None, CType::INT, 0),
+            member(CType::CHAR, 1),
+        ],
+    );
+    let offsets = layout.members.iter().map(|member| {
+        let bit_offset = member.bit_field.map_or(0, |bit_field| bit_field.bit_offset);
+        member.offset * 8 + bit_offset
+    });
+    assert_eq!(offsets.collect::<Vec<_>>(), vec![0, 32, 64, 96, 96]);
+    assert_eq!((layout.size, layout.alignment), (16, 4));
+}
+
+#[test]
+fn test_offset_alignment() {
+    let location = Location {
+        filename: "test.c".into(),
+        line: 0,
+        column: 0,
+    };
+    let member = |r#type: CType, alignment: u64, width: Option<u64>| Member {
+        identifier: Some("m".into()),
+        r#type,
+        r#const: false,
+        alignment,
+        offset: 0,
+        bit_field: width.map(|width| BitField {
+            width,
+            bit_offset: 0,
+        }),
+    };
+    // `struct { char c; int b : 20; short s; int d : 8; }`, where `b` is 3 bytes at 1
+    let layout = Layout::new(
+        RecordKind::Struct,
+        location.clone(),
+        vec![
+            member(CType::CHAR, 1, None),
+            member(CType::INT, 4, Some(20)),
+            member(
+                CType::Integer {
+                    kind: IntegerKind::Short,
+                    signed: true,
+                },
+                2,
+                None,
+            ),
+            member(CType::INT, 4, Some(8)),
+        ],
+    );
+    let offsets = layout.members.iter().map(|member| member.offset);
+    assert_eq!(offsets.collect::<Vec<_>>(), vec![0, 1, 4, 6]);
+    assert_eq!(layout.alignment, 4);
+    let alignments = layout
+        .members
+        .iter()
+        .map(|member| layout.offset_alignment(member.offset, None));
+    assert_eq!(alignments.collect::<Vec<_>>(), vec![4, 1, 4, 2]);
+    // In a member of a packed structure
+    assert_eq!(layout.offset_alignment(0, Some(2)), 2);
+    assert_eq!(layout.offset_alignment(4, Some(1)), 1);
+
+    // Under `#pragma pack(1)`, `struct { char c; int b : 20; int d : 12; }`
+    let layout = Layout::new(
+        RecordKind::Struct,
+        location,
+        vec![
+            member(CType::CHAR, 1, None),
+            member(CType::INT, 1, Some(20)),
+            member(CType::INT, 1, Some(12)),
+        ],
+    );
+    assert_eq!(layout.alignment, 1);
+    let alignments = layout
+        .members
+        .iter()
+        .map(|member| layout.offset_alignment(member.offset, None));
+    assert_eq!(alignments.collect::<Vec<_>>(), vec![1, 1, 1]);
+}